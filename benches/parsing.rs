@@ -0,0 +1,109 @@
+//! Benchmarks of parsing and rendering with the library functions the
+//! `datetime` tool uses. Run with `cargo bench`.
+//!
+//! * `parse/*` guards the detection of single inputs: RFC 3339 timestamps,
+//!   epoch seconds and epoch milliseconds with the default detectors, each
+//!   compared to trying the formats one after another without pre-filters.
+//! * `lines/table` guards the default path of standard input, parsing a
+//!   batch of mixed lines and rendering each as table.
+//! * `lines/only-millis` guards the machine output of `--only millis`, where
+//!   rendering is cheap and parsing dominates.
+
+use std::hint::black_box;
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use datetime_helper::output::{format_output, OutputField, OutputOptions};
+use datetime_helper::{DateTimeError, Detectors, InputKind, ParsedDateTime};
+
+/// The number of lines of the line benchmarks.
+const LINES: usize = 100_000;
+
+/// Tries the formats without pre-filters, like the detection did before the
+/// detectors.
+fn chained(input: &str) -> Result<ParsedDateTime, DateTimeError> {
+    let input = input.trim();
+    let parsed = |kind, datetime: DateTime<FixedOffset>| ParsedDateTime::new(input, kind, datetime);
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(input) {
+        return Ok(parsed(InputKind::Rfc3339, datetime));
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc2822(input) {
+        return Ok(parsed(InputKind::Rfc2822, datetime));
+    }
+    let epoch_time: i64 = input.parse()?;
+    match Utc.timestamp_opt(epoch_time, 0).single() {
+        Some(datetime) if epoch_time < 32_503_680_000 => {
+            Ok(parsed(InputKind::EpochSeconds, datetime.into()))
+        }
+        _ => match Utc.timestamp_millis_opt(epoch_time).single() {
+            Some(datetime) => Ok(parsed(InputKind::EpochMillis, datetime.into())),
+            None => Err(DateTimeError::InvalidEpochTime(epoch_time)),
+        },
+    }
+}
+
+/// Lines cycling through all default kinds and an invalid input.
+fn mixed_lines() -> Vec<String> {
+    (0..LINES as i64)
+        .map(|i| {
+            let seconds = 1_676_550_896 + i * 37;
+            match i % 5 {
+                0 => Utc.timestamp_opt(seconds, 0).unwrap().to_rfc3339(),
+                1 => Utc.timestamp_opt(seconds, 0).unwrap().to_rfc2822(),
+                2 => seconds.to_string(),
+                3 => (seconds * 1000 + i % 1000).to_string(),
+                _ => format!("not a date {i}"),
+            }
+        })
+        .collect()
+}
+
+fn parse(c: &mut Criterion) {
+    let detectors = Detectors::default();
+    let mut group = c.benchmark_group("parse");
+    for (name, input) in [
+        ("rfc3339", "2023-02-16T12:34:56.789Z"),
+        ("epoch-seconds", "1676550896"),
+        ("epoch-millis", "1676550896789"),
+    ] {
+        group.bench_function(name, |b| b.iter(|| detectors.parse(black_box(input))));
+        group.bench_function(format!("{name}/chained"), |b| {
+            b.iter(|| chained(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+fn lines(c: &mut Criterion) {
+    let detectors = Detectors::default();
+    let lines = mixed_lines();
+    let mut group = c.benchmark_group("lines");
+    group.throughput(Throughput::Elements(LINES as u64));
+    group.sample_size(10);
+    for (name, output) in [
+        ("table", OutputOptions::default()),
+        (
+            "only-millis",
+            OutputOptions {
+                only: Some(OutputField::Millis),
+                ..Default::default()
+            },
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut rendered = 0;
+                for line in &lines {
+                    if let Ok(parsed) = detectors.parse(line) {
+                        rendered += format_output(&parsed, &output).len();
+                    }
+                }
+                rendered
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, lines);
+criterion_main!(benches);
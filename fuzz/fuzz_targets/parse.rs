@@ -0,0 +1,22 @@
+//! Parses arbitrary bytes as lossy UTF-8. Parsing must not panic, and a
+//! parsed date time must parse to the same result again, from its input and
+//! from its RFC 3339 timestamp if its year can be written as RFC 3339.
+
+#![no_main]
+
+use chrono::Datelike;
+use datetime_helper::{parse, Precision};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let Ok(parsed) = parse(&input) else {
+        return;
+    };
+    assert_eq!(parse(&parsed.input).ok().as_ref(), Some(&parsed));
+    if (0..=9999).contains(&parsed.datetime.year()) {
+        let timestamp = parsed.to_rfc3339_with(Precision::Nanos);
+        let reparsed = parse(&timestamp).expect("RFC 3339 timestamps are parsed");
+        assert_eq!(reparsed.datetime, parsed.datetime, "{timestamp}");
+    }
+});
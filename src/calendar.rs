@@ -0,0 +1,211 @@
+use chrono::Month;
+
+/// Explains why an ISO 8601 date time like `2023-02-30T12:34:56Z` which
+/// failed to parse is invalid, if one of its components is out of range. The
+/// date has to be `YYYY-MM-DD`, the time is optional and may be followed by
+/// anything like fractional seconds or an offset. Returns `None` if the input
+/// does not have this shape or all components are valid.
+pub(crate) fn invalid_component(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let number = |start: usize, length: usize| -> Option<u32> {
+        let digits = bytes.get(start..start + length)?;
+        digits.iter().all(u8::is_ascii_digit).then(|| {
+            digits
+                .iter()
+                .fold(0, |number, digit| number * 10 + u32::from(digit - b'0'))
+        })
+    };
+    let separated = |position: usize, separators: &[u8]| {
+        bytes
+            .get(position)
+            .is_some_and(|byte| separators.contains(byte))
+    };
+    if !separated(4, b"-") || !separated(7, b"-") {
+        return None;
+    }
+    let (year, month, day) = (number(0, 4)?, number(5, 2)?, number(8, 2)?);
+    let Some(name) = u8::try_from(month)
+        .ok()
+        .and_then(|month| Month::try_from(month).ok())
+    else {
+        return Some(format!("month {} is invalid", month));
+    };
+    if day == 0 {
+        return Some(format!("day {} is invalid", day));
+    }
+    let max = days_in_month(year, month);
+    if day > max {
+        return Some(format!(
+            "day {} is out of range for {} {} (max {})",
+            day,
+            name.name(),
+            year,
+            max
+        ));
+    }
+    if !separated(10, b"Tt ") {
+        return None;
+    }
+    let checks = [
+        (11, "hour", 23, ""),
+        (14, "minute", 59, ""),
+        (17, "second", 60, " for a leap second"),
+    ];
+    for (start, field, max, note) in checks {
+        if start > 11 && !separated(start - 1, b":") {
+            return None;
+        }
+        let value = number(start, 2)?;
+        if value > max {
+            return Some(format!(
+                "{} {} is out of range (max {}{})",
+                field, value, max, note
+            ));
+        }
+    }
+    None
+}
+
+/// The number of days of `month` in the proleptic Gregorian calendar.
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::invalid_component;
+
+    fn explain(input: &str) -> String {
+        invalid_component(input).unwrap_or_else(|| panic!("{input} should be invalid"))
+    }
+
+    #[test]
+    fn months_range_from_1_to_12() {
+        assert_eq!(explain("2023-00-16T12:34:56Z"), "month 0 is invalid");
+        assert_eq!(explain("2023-13-16T12:34:56Z"), "month 13 is invalid");
+        assert_eq!(explain("2023-99-16"), "month 99 is invalid");
+        assert_eq!(invalid_component("2023-01-16T12:34:56Z"), None);
+        assert_eq!(invalid_component("2023-12-16T12:34:56Z"), None);
+    }
+
+    #[test]
+    fn days_range_up_to_the_length_of_the_month() {
+        assert_eq!(explain("2023-01-00T12:34:56Z"), "day 0 is invalid");
+        assert_eq!(invalid_component("2023-01-31T12:34:56Z"), None);
+        assert_eq!(
+            explain("2023-01-32T12:34:56Z"),
+            "day 32 is out of range for January 2023 (max 31)"
+        );
+        assert_eq!(invalid_component("2023-04-30T12:34:56Z"), None);
+        assert_eq!(
+            explain("2023-04-31T12:34:56Z"),
+            "day 31 is out of range for April 2023 (max 30)"
+        );
+        assert_eq!(
+            explain("2023-11-31"),
+            "day 31 is out of range for November 2023 (max 30)"
+        );
+    }
+
+    #[test]
+    fn february_has_29_days_in_leap_years() {
+        assert_eq!(invalid_component("2023-02-28T12:34:56Z"), None);
+        assert_eq!(
+            explain("2023-02-29T12:34:56Z"),
+            "day 29 is out of range for February 2023 (max 28)"
+        );
+        assert_eq!(
+            explain("2023-02-30T12:34:56Z"),
+            "day 30 is out of range for February 2023 (max 28)"
+        );
+        assert_eq!(invalid_component("2024-02-29T12:34:56Z"), None);
+        assert_eq!(
+            explain("2024-02-30T12:34:56Z"),
+            "day 30 is out of range for February 2024 (max 29)"
+        );
+        assert_eq!(
+            explain("1900-02-29T12:34:56Z"),
+            "day 29 is out of range for February 1900 (max 28)"
+        );
+        assert_eq!(invalid_component("2000-02-29T12:34:56Z"), None);
+    }
+
+    #[test]
+    fn hours_range_up_to_23() {
+        assert_eq!(invalid_component("2023-02-16T23:34:56Z"), None);
+        assert_eq!(
+            explain("2023-02-16T24:00:00Z"),
+            "hour 24 is out of range (max 23)"
+        );
+        assert_eq!(
+            explain("2023-02-16 25:00"),
+            "hour 25 is out of range (max 23)"
+        );
+        assert_eq!(
+            explain("2023-02-16t24:00:00z"),
+            "hour 24 is out of range (max 23)"
+        );
+    }
+
+    #[test]
+    fn minutes_range_up_to_59() {
+        assert_eq!(invalid_component("2023-02-16T12:59:56Z"), None);
+        assert_eq!(
+            explain("2023-02-16T12:60:00Z"),
+            "minute 60 is out of range (max 59)"
+        );
+        assert_eq!(
+            explain("2023-02-16 12:60"),
+            "minute 60 is out of range (max 59)"
+        );
+    }
+
+    #[test]
+    fn seconds_range_up_to_a_leap_second() {
+        assert_eq!(invalid_component("2023-02-16T12:34:59Z"), None);
+        assert_eq!(invalid_component("2016-12-31T23:59:60Z"), None);
+        assert_eq!(
+            explain("2023-02-16T12:34:61Z"),
+            "second 61 is out of range (max 60 for a leap second)"
+        );
+        assert_eq!(
+            explain("2023-02-16T12:34:99.5+01:00"),
+            "second 99 is out of range (max 60 for a leap second)"
+        );
+    }
+
+    #[test]
+    fn the_first_invalid_component_is_explained() {
+        assert_eq!(explain("2023-13-32T24:60:61Z"), "month 13 is invalid");
+        assert_eq!(
+            explain("2023-02-16T24:60:61Z"),
+            "hour 24 is out of range (max 23)"
+        );
+    }
+
+    #[test]
+    fn other_shapes_are_not_explained() {
+        for input in [
+            "",
+            "abc",
+            "1676550896",
+            "2023-2-30T12:34:56Z",
+            "2023/02/30T12:34:56Z",
+            "20230230T123456Z",
+            "2023-02-3x",
+            "2023-02-16X24:00:00Z",
+            "2023-02-16T2",
+            "2023-02-16T12.60",
+            "+2023-02-30",
+            "Thu, 30 Feb 2023 12:34:56 +0000",
+        ] {
+            assert_eq!(invalid_component(input), None, "{input}");
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use crate::output::OutputField;
+use crate::{CliParams, DateTimeError};
+
+/// Puts `value` on the system clipboard.
+///
+/// On Linux, the clipboard is owned by the process which set it. The value
+/// is handed over to a running clipboard manager when the process exits and
+/// lost without one.
+#[cfg(feature = "clipboard")]
+pub(crate) fn copy(value: &str) -> Result<(), DateTimeError> {
+    let error = |e: arboard::Error| DateTimeError::Clipboard(value.to_string(), e.to_string());
+    arboard::Clipboard::new()
+        .map_err(error)?
+        .set_text(value)
+        .map_err(error)
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub(crate) fn copy(value: &str) -> Result<(), DateTimeError> {
+    Err(DateTimeError::Clipboard(
+        value.to_string(),
+        "built without the clipboard feature".to_string(),
+    ))
+}
+
+/// Copies the value selected by --copy to the clipboard if requested and
+/// confirms it on `errors`.
+pub(crate) fn copy_value(
+    params: &CliParams,
+    errors: &mut impl Write,
+    value: impl FnOnce(OutputField) -> String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(field) = params.copy else {
+        return Ok(());
+    };
+    let value = value(field);
+    copy(&value)?;
+    if !params.silent {
+        writeln!(errors, "Copied {} to the clipboard", value)?;
+    }
+    Ok(())
+}
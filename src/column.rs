@@ -0,0 +1,283 @@
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::rc::Rc;
+
+use chrono::{DateTime, Utc};
+
+use crate::stats::Stats;
+use crate::{get_datetime, DateTimeError, Detectors};
+
+/// The column of --column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Column {
+    /// The number of the column, starting at 1.
+    Number(usize),
+    /// The name of the column in the header.
+    Header(String),
+}
+
+/// Parses a column, which is a number starting at 1 or otherwise the name of
+/// the column in the header.
+pub(crate) fn parse_column(input: &str) -> Result<Column, DateTimeError> {
+    match input.parse::<usize>() {
+        Ok(0) => Err(DateTimeError::InvalidColumn(input.to_string())),
+        Ok(number) => Ok(Column::Number(number)),
+        Err(_) if input.is_empty() => Err(DateTimeError::InvalidColumn(input.to_string())),
+        Err(_) => Ok(Column::Header(input.to_string())),
+    }
+}
+
+/// Keeps the bytes read through it until they are taken, so that the CSV
+/// reader can parse records which are then copied as they were read.
+struct Recording<R> {
+    reader: R,
+    bytes: Rc<RefCell<Vec<u8>>>,
+}
+
+impl<R: Read> Read for Recording<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.reader.read(buf)?;
+        self.bytes.borrow_mut().extend_from_slice(&buf[..length]);
+        Ok(length)
+    }
+}
+
+/// Copies the CSV records of `input` to `out` with the date time in `column`
+/// replaced by the result of `render`. The first record is the header. It is
+/// copied unchanged like records whose cell cannot be parsed or which do not
+/// have the column, which are reported to `errors` unless `quiet` is set, and
+/// counted as failures. All other bytes are copied unchanged, including the
+/// quotes of the replaced cell. Returns the statistics of the parsed cells.
+pub(crate) fn convert_column(
+    input: impl Read,
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    column: &Column,
+    detectors: &Detectors,
+    render: impl Fn(DateTime<Utc>) -> String,
+    quiet: bool,
+) -> std::io::Result<Stats> {
+    let bytes = Rc::new(RefCell::new(Vec::new()));
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(Recording {
+            reader: input,
+            bytes: Rc::clone(&bytes),
+        });
+    let mut record = csv::ByteRecord::new();
+    let mut stats = Stats::default();
+    // The offset in the input of the first byte not yet copied and the
+    // number of line breaks before it.
+    let (mut copied, mut breaks) = (0, 0);
+    let mut index = None;
+    while reader.read_byte_record(&mut record)? {
+        let end = reader.position().byte();
+        let raw: Vec<u8> = bytes
+            .borrow_mut()
+            .drain(..(end - copied) as usize)
+            .collect();
+        // Blank lines before the record are skipped by the reader.
+        let start = raw.iter().position(|&byte| byte != b'\r' && byte != b'\n');
+        let (skipped, raw) = raw.split_at(start.unwrap_or(raw.len()));
+        copied = end;
+        let line = breaks + count_breaks(skipped) + 1;
+        breaks = line - 1 + count_breaks(raw);
+        out.write_all(skipped)?;
+        let Some(index) = index else {
+            index = Some(column_index(&record, column)?);
+            out.write_all(raw)?;
+            continue;
+        };
+        let Some(cell) = record.get(index) else {
+            stats.add_failure(line);
+            if !quiet {
+                writeln!(errors, "line {}: Record has no column {}", line, index + 1)?;
+            }
+            out.write_all(raw)?;
+            continue;
+        };
+        let parsed = match std::str::from_utf8(cell) {
+            Ok(cell) => get_datetime(cell, detectors).map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Column {} is not valid UTF-8: {}", index + 1, e)),
+        };
+        match parsed {
+            Ok(datetime) => {
+                stats.add(datetime);
+                let range = field_ranges(raw).swap_remove(index);
+                let quoted = raw[range.clone()].starts_with(b"\"");
+                out.write_all(&raw[..range.start])?;
+                out.write_all(quote(&render(datetime), quoted).as_bytes())?;
+                out.write_all(&raw[range.end..])?;
+            }
+            Err(e) => {
+                stats.add_failure(line);
+                if !quiet {
+                    writeln!(errors, "line {}: {}", line, e)?;
+                }
+                out.write_all(raw)?;
+            }
+        }
+    }
+    // Blank lines after the last record.
+    out.write_all(&bytes.borrow())?;
+    Ok(stats)
+}
+
+fn count_breaks(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&byte| byte == b'\n').count()
+}
+
+/// The index of `column` in the `header` record.
+fn column_index(header: &csv::ByteRecord, column: &Column) -> std::io::Result<usize> {
+    match column {
+        Column::Number(number) => Ok(number - 1),
+        Column::Header(name) => header
+            .iter()
+            .position(|cell| cell == name.as_bytes())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("No column \"{}\" in the header", name),
+                )
+            }),
+    }
+}
+
+/// The byte ranges of the fields of the raw CSV `record`, including the
+/// quotes of quoted fields, but neither the commas between the fields nor
+/// the line break ending the record.
+fn field_ranges(record: &[u8]) -> Vec<Range<usize>> {
+    let record = record.strip_suffix(b"\n").unwrap_or(record);
+    let record = record.strip_suffix(b"\r").unwrap_or(record);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (position, &byte) in record.iter().enumerate() {
+        match byte {
+            // Escaped quotes within quoted fields toggle twice.
+            b'"' if record[start] == b'"' => quoted = !quoted,
+            b',' if !quoted => {
+                ranges.push(start..position);
+                start = position + 1;
+            }
+            _ => {}
+        }
+    }
+    ranges.push(start..record.len());
+    ranges
+}
+
+/// Quotes `value` if it was `quoted` before or needs to be quoted.
+fn quote(value: &str, quoted: bool) -> String {
+    if quoted || value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use datetime_helper::Detectors;
+
+    use crate::column::{convert_column, field_ranges, parse_column, Column};
+
+    fn convert(input: &str, column: &str) -> (String, String, u64) {
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let render = |datetime: DateTime<Utc>| datetime.format("%Y-%m-%d %H:%M:%S").to_string();
+        let stats = convert_column(
+            input.as_bytes(),
+            &mut out,
+            &mut errors,
+            &parse_column(column).unwrap(),
+            &Detectors::default(),
+            render,
+            false,
+        )
+        .unwrap();
+        let string = |bytes| String::from_utf8(bytes).unwrap();
+        (string(out), string(errors), stats.failed)
+    }
+
+    #[test]
+    fn columns_are_numbers_or_headers() {
+        assert_eq!(parse_column("3").unwrap(), Column::Number(3));
+        assert_eq!(
+            parse_column("created").unwrap(),
+            Column::Header("created".to_string())
+        );
+        assert!(parse_column("0").is_err());
+        assert!(parse_column("").is_err());
+    }
+
+    #[test]
+    fn only_the_column_is_replaced() {
+        let input = "id,\"name\",created\r\n1,\"a, b\",1676550896789\r\n2,c,\"1676550896\"\r\n";
+        let (out, errors, failed) = convert(input, "3");
+        assert_eq!(
+            out,
+            "id,\"name\",created\r\n\
+             1,\"a, b\",2023-02-16 12:34:56\r\n\
+             2,c,\"2023-02-16 12:34:56\"\r\n"
+        );
+        assert_eq!((errors.as_str(), failed), ("", 0));
+        assert_eq!(convert(input, "created").0, out);
+    }
+
+    #[test]
+    fn quoted_fields_with_line_breaks() {
+        let input = "note,time\n\"two\nlines, \"\"quoted\"\"\",1676550896\nx,1676550896";
+        let (out, _, failed) = convert(input, "time");
+        assert_eq!(
+            out,
+            "note,time\n\"two\nlines, \"\"quoted\"\"\",2023-02-16 12:34:56\nx,2023-02-16 12:34:56"
+        );
+        assert_eq!(failed, 0);
+    }
+
+    #[test]
+    fn ragged_and_unparsable_rows_are_copied() {
+        let input = "a,b,time\n1,2\n\n1,2,soon,extra\n\r\n1,2,1676550896,extra\n\n";
+        let (out, errors, failed) = convert(input, "3");
+        assert_eq!(
+            out,
+            "a,b,time\n1,2\n\n1,2,soon,extra\n\r\n1,2,2023-02-16 12:34:56,extra\n\n"
+        );
+        assert_eq!(failed, 2);
+        let errors: Vec<&str> = errors.lines().collect();
+        assert_eq!(errors[0], "line 2: Record has no column 3");
+        assert!(
+            errors[1].starts_with("line 4: Cannot parse \"soon\""),
+            "{errors:?}"
+        );
+    }
+
+    #[test]
+    fn unknown_headers_are_an_error() {
+        let column = parse_column("created").unwrap();
+        let result = convert_column(
+            &b"id,time\n1,1676550896\n"[..],
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &column,
+            &Detectors::default(),
+            |datetime| datetime.to_string(),
+            false,
+        );
+        let Err(error) = result else {
+            panic!("expected an unknown column");
+        };
+        assert_eq!(error.to_string(), "No column \"created\" in the header");
+    }
+
+    #[test]
+    fn ranges_of_raw_fields() {
+        assert_eq!(field_ranges(b"a,,\"b,c\"\r\n"), [0..1, 2..2, 3..8]);
+        assert_eq!(field_ranges(b"\"a\"\"\",b\n"), [0..5, 6..7]);
+        assert_eq!(field_ranges(b"a\"b,c"), [0..3, 4..5]);
+        assert_eq!(field_ranges(b"a\n"), vec![0..1]);
+    }
+}
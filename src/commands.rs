@@ -0,0 +1,380 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Offset, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use datetime_helper::duration::Age;
+use datetime_helper::workdays::Workdays;
+use datetime_helper::zone::{self, Transition, ZoneInfo};
+use datetime_helper::{DateTimeError, Detectors, ParsedDateTime};
+
+use crate::duration::CalendarDuration;
+use crate::output::{
+    write_age, write_duration, write_header, write_record, write_time, write_transition,
+    write_workdays, write_zone_info, OutputField, OutputFormat, OutputOptions,
+};
+use crate::range::Range;
+use crate::{clipboard, get_datetime, CliParams, Command, NowArgs, RangeArgs, ZoneArgs};
+
+/// Runs `command` and copies its result with --copy. Returns whether it
+/// found a result, which only the transition commands may not.
+pub(crate) fn run(
+    command: &Command,
+    params: &CliParams,
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    match command {
+        Command::Now(args) => {
+            let parsed = now(args, Utc::now())?.into();
+            write_time(out, &parsed, output)?;
+            clipboard::copy_value(params, errors, |field| field.format(&parsed, output))?;
+        }
+        Command::Diff(args) => {
+            let now = Utc::now();
+            let from = get_argument(&args.from, detectors, now)?;
+            let to = match &args.to {
+                Some(to) => get_argument(to, detectors, now)?,
+                None => now,
+            };
+            write_duration(out, from, to, output)?;
+            clipboard::copy_value(params, errors, |field| field.format_duration(to - from))?;
+        }
+        Command::Add(args) | Command::Sub(args) => {
+            let duration = match command {
+                Command::Sub(_) => -args.duration,
+                _ => args.duration,
+            };
+            let datetime = get_date_argument(&args.date_time, detectors, Utc::now(), output)?;
+            let parsed = shift(datetime, duration)?.into();
+            write_time(out, &parsed, output)?;
+            clipboard::copy_value(params, errors, |field| field.format(&parsed, output))?;
+        }
+        Command::Range(args) => {
+            if let Some(last) = write_range(out, args, detectors, output)? {
+                clipboard::copy_value(params, errors, |field| field.format(&last, output))?;
+            }
+        }
+        Command::Age(args) => {
+            let now = Utc::now();
+            let at = args.at.as_deref().unwrap_or("now");
+            let (from, at) = (
+                moment(&args.date_time, detectors, now, output)?,
+                moment(at, detectors, now, output)?,
+            );
+            let dates = from.date || at.date;
+            let local = |moment: &Moment| match dates {
+                true => moment.local.date().and_time(NaiveTime::MIN),
+                false => moment.local,
+            };
+            let age = Age::between(local(&from), local(&at)).ok_or_else(|| {
+                DateTimeError::OutOfRange {
+                    value: format!("{} at {}", from.label, at.label),
+                    unit: None,
+                }
+            })?;
+            write_age(out, &from.label, &at.label, &age, dates, output)?;
+        }
+        Command::Workdays(args) => {
+            let now = Utc::now();
+            let date =
+                |input| Ok::<_, DateTimeError>(moment(input, detectors, now, output)?.local.date());
+            let holidays = match &args.holidays {
+                Some(path) => read_holidays(path)?,
+                None => Vec::new(),
+            };
+            let workdays = Workdays::new(args.weekend, holidays);
+            let start = date(&args.start)?;
+            match (args.add, &args.end) {
+                (Some(n), _) => {
+                    let end = workdays
+                        .add(start, n)
+                        .ok_or_else(|| DateTimeError::OutOfRange {
+                            value: format!("{} + {} workdays", start, n),
+                            unit: None,
+                        })?;
+                    write_workdays(out, start, end, n, output)?;
+                }
+                (None, Some(end)) => {
+                    let end = date(end)?;
+                    write_workdays(out, start, end, workdays.count(start, end), output)?;
+                }
+                (None, None) => unreachable!("clap requires the end or --add"),
+            }
+        }
+        Command::Tzinfo(args) => {
+            let at = args.at(detectors)?;
+            write_zone_info(out, &ZoneInfo::new(args.timezone, at), output)?;
+        }
+        Command::DstNext(args) => {
+            let at = args.at(detectors)?;
+            return Ok(write_found_transition(
+                out,
+                errors,
+                args.timezone,
+                zone::next_transition(args.timezone, at),
+                &format!("after {}", output.format_value(at)),
+                output,
+            )?);
+        }
+        Command::DstPrev(args) => {
+            let at = args.at(detectors)?;
+            return Ok(write_found_transition(
+                out,
+                errors,
+                args.timezone,
+                zone::previous_transition(args.timezone, at),
+                &format!("up to {}", output.format_value(at)),
+                output,
+            )?);
+        }
+    }
+    Ok(true)
+}
+
+/// Applies the offsets of the `now` command to `now`.
+fn now(args: &NowArgs, now: DateTime<Utc>) -> Result<DateTime<Utc>, DateTimeError> {
+    let mut datetime = now;
+    if let Some(plus) = args.plus {
+        datetime = shift(datetime, plus)?;
+    }
+    if let Some(minus) = args.minus {
+        datetime = shift(datetime, -minus)?;
+    }
+    Ok(datetime)
+}
+
+/// Writes the date times of the `range` command. Without a selected output
+/// format, each date time is written as a single value per line. Returns the
+/// last date time of the range.
+fn write_range(
+    out: &mut impl Write,
+    args: &RangeArgs,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> Result<Option<ParsedDateTime>, Box<dyn std::error::Error>> {
+    let now = Utc::now();
+    let start = get_date_argument(&args.start, detectors, now, output)?;
+    let end = get_date_argument(&args.end, detectors, now, output)?;
+    let range = || Range::new(start, end, args.step, args.exclusive_end);
+    if range()?.nth(args.limit).is_some() {
+        return Err(DateTimeError::RangeLimitExceeded(args.limit).into());
+    }
+    let output = OutputOptions {
+        only: match (output.format, &output.time_format) {
+            (OutputFormat::Table, None) => Some(output.only.unwrap_or(OutputField::Iso)),
+            _ => output.only,
+        },
+        ..output.clone()
+    };
+    write_header(out, &output)?;
+    let mut last = None;
+    for datetime in range()? {
+        let parsed = datetime.into();
+        write_record(out, &parsed, &output)?;
+        last = Some(parsed);
+    }
+    Ok(last)
+}
+
+/// Adds `duration` to `datetime` with an error if the result is out of range.
+fn shift(
+    datetime: DateTime<Utc>,
+    duration: CalendarDuration,
+) -> Result<DateTime<Utc>, DateTimeError> {
+    duration.add_to(datetime).ok_or_else(|| {
+        let iso = datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        DateTimeError::OutOfRange {
+            value: format!("{} + {}", iso, duration),
+            unit: None,
+        }
+    })
+}
+
+/// A date or date time of `age` with its local time in the time zone of
+/// --tz and how it is printed.
+struct Moment {
+    local: NaiveDateTime,
+    label: String,
+    /// Whether it is a date without a time.
+    date: bool,
+}
+
+fn moment(
+    input: &str,
+    detectors: &Detectors,
+    now: DateTime<Utc>,
+    output: &OutputOptions,
+) -> Result<Moment, DateTimeError> {
+    if let Ok(date) = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d") {
+        return Ok(Moment {
+            local: date.and_time(NaiveTime::MIN),
+            label: date.to_string(),
+            date: true,
+        });
+    }
+    let datetime = get_argument(input, detectors, now)?;
+    let timezone = output.timezone.unwrap_or(Tz::UTC);
+    let offset = timezone
+        .offset_from_utc_datetime(&datetime.naive_utc())
+        .fix();
+    let local = datetime.naive_utc().checked_add_offset(offset);
+    Ok(Moment {
+        local: local.ok_or_else(|| DateTimeError::OutOfRange {
+            value: input.to_string(),
+            unit: None,
+        })?,
+        label: output.format_value(datetime),
+        date: false,
+    })
+}
+
+/// Reads the dates of --holidays.
+fn read_holidays(path: &PathBuf) -> Result<Vec<NaiveDate>, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut holidays = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let date = NaiveDate::parse_from_str(line, "%Y-%m-%d").map_err(|_| {
+            format!(
+                "{}: line {}: \"{}\" is not a date like 2023-12-25",
+                path.display(),
+                number + 1,
+                line
+            )
+        })?;
+        holidays.push(date);
+    }
+    Ok(holidays)
+}
+
+impl ZoneArgs {
+    fn at(&self, detectors: &Detectors) -> Result<DateTime<Utc>, DateTimeError> {
+        match &self.date_time {
+            Some(date_time) => get_argument(date_time, detectors, Utc::now()),
+            None => Ok(Utc::now()),
+        }
+    }
+}
+
+/// Writes the transition of `dst-next` or `dst-prev`, or reports that there
+/// is none `when`, writing `null` for JSON output. Returns whether there is
+/// one.
+fn write_found_transition(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    timezone: Tz,
+    transition: Option<Transition>,
+    when: &str,
+    output: &OutputOptions,
+) -> std::io::Result<bool> {
+    match &transition {
+        Some(transition) => write_transition(out, timezone, transition, output)?,
+        None => {
+            // Scripts reading JSON still get a value to parse.
+            if output.format == OutputFormat::Json {
+                writeln!(out, "null")?;
+            }
+            writeln!(errors, "{} has no transitions {}", timezone.name(), when)?;
+        }
+    }
+    Ok(transition.is_some())
+}
+
+/// Parses a date time argument of a command, which may also be `now`.
+fn get_argument(
+    input: &str,
+    detectors: &Detectors,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, DateTimeError> {
+    if input.trim().eq_ignore_ascii_case("now") {
+        Ok(now)
+    } else {
+        get_datetime(input, detectors)
+    }
+}
+
+/// Parses a date time argument like [get_argument], where a date like
+/// `2023-01-31` is its midnight in the time zone of --tz. A midnight skipped
+/// by a daylight saving time transition is shifted by the length of the gap.
+fn get_date_argument(
+    input: &str,
+    detectors: &Detectors,
+    now: DateTime<Utc>,
+    output: &OutputOptions,
+) -> Result<DateTime<Utc>, DateTimeError> {
+    let Ok(date) = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d") else {
+        return get_argument(input, detectors, now);
+    };
+    let timezone = output.timezone.unwrap_or(Tz::UTC);
+    let midnight = date.and_time(NaiveTime::MIN);
+    let datetime = timezone.from_local_datetime(&midnight).earliest();
+    let datetime = datetime
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .or_else(|| {
+            let before = timezone.offset_from_utc_datetime(&(midnight - chrono::Duration::days(1)));
+            midnight
+                .checked_sub_offset(before.fix())
+                .map(|utc| utc.and_utc())
+        });
+    datetime.ok_or_else(|| DateTimeError::OutOfRange {
+        value: input.to_string(),
+        unit: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use datetime_helper::Detectors;
+
+    use crate::commands::{get_argument, now};
+    use crate::NowArgs;
+
+    #[test]
+    fn now_without_offset() {
+        let instant = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let args = NowArgs {
+            plus: None,
+            minus: None,
+        };
+        assert_eq!(now(&args, instant).unwrap(), instant);
+    }
+
+    #[test]
+    fn now_with_offsets() {
+        let instant = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let args = NowArgs {
+            plus: Some(Duration::hours(1).into()),
+            minus: Some(Duration::minutes(15).into()),
+        };
+        let expected = Utc.timestamp_millis_opt(1676553596789).unwrap();
+        assert_eq!(now(&args, instant).unwrap(), expected);
+    }
+
+    #[test]
+    fn now_out_of_range() {
+        let instant = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let args = NowArgs {
+            plus: Some(Duration::weeks(100_000_000).into()),
+            minus: None,
+        };
+        assert!(now(&args, instant).is_err());
+    }
+
+    #[test]
+    fn argument_now() {
+        let instant = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let detectors = Detectors::default();
+        assert_eq!(get_argument("now", &detectors, instant).unwrap(), instant);
+        assert_eq!(get_argument(" NOW ", &detectors, instant).unwrap(), instant);
+        let parsed = get_argument("1676550896", &detectors, instant).unwrap();
+        assert_eq!(parsed.timestamp_millis(), 1676550896000);
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command};
+use thiserror::Error;
+use toml::{Spanned, Value};
+
+/// Options which select a mode, require or exclude one, or select the config
+/// file itself and therefore cannot be set in a config file.
+const NOT_CONFIGURABLE: [&str; 32] = [
+    "config",
+    "no-config",
+    "help",
+    "interactive",
+    "watch",
+    "rewrite",
+    "annotate",
+    "annotate-eol",
+    "sort",
+    "stats",
+    "stats-only",
+    "histogram",
+    "check",
+    "csv",
+    "column",
+    "json-rewrite",
+    "json-key",
+    "url",
+    "mtime",
+    "atime",
+    "ctime",
+    "created",
+    "pair",
+    "pair-delimiter",
+    "deltas",
+    "deltas-reset-on-error",
+    "copy",
+    "reverse",
+    "sort-drop-unparsed",
+    "file",
+    "completions",
+    "generate-man",
+];
+
+#[derive(Error, Debug)]
+pub(crate) enum ConfigError {
+    #[error("Cannot read config file {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Invalid config file {path}: {message}")]
+    Syntax { path: String, message: String },
+    #[error("{path}:{line}: Unknown key \"{key}\"")]
+    UnknownKey {
+        path: String,
+        line: usize,
+        key: String,
+    },
+    #[error("{path}:{line}: Invalid value for key \"{key}\": {message}")]
+    InvalidValue {
+        path: String,
+        line: usize,
+        key: String,
+        message: String,
+    },
+}
+
+/// The config file used without --config, e.g.
+/// `$XDG_CONFIG_HOME/datetime-helper/config.toml` on Linux.
+fn default_path() -> Option<PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join("datetime-helper")
+            .join("config.toml"),
+    )
+}
+
+/// Reads the config file selected by `matches` and returns its options as
+/// command line arguments for `command`.
+///
+/// Options given on the command line or by environment variables as well as
+/// options conflicting with them are left out, so they take precedence over
+/// the config file. A missing config file at the default location is ignored.
+pub(crate) fn config_args(
+    command: &Command,
+    matches: &ArgMatches,
+) -> Result<Vec<String>, ConfigError> {
+    if matches.get_flag("no_config") {
+        return Ok(Vec::new());
+    }
+    let (path, required) = match matches.get_one::<PathBuf>("config") {
+        Some(path) => (path.clone(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(Vec::new()),
+        },
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => to_args(command, matches, &path, &text),
+        Err(e) if !required && e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(ConfigError::Read {
+            path: path.display().to_string(),
+            source,
+        }),
+    }
+}
+
+/// Converts the keys of the config file `text` to command line arguments.
+fn to_args(
+    command: &Command,
+    matches: &ArgMatches,
+    path: &Path,
+    text: &str,
+) -> Result<Vec<String>, ConfigError> {
+    let path = path.display().to_string();
+    let entries: BTreeMap<Spanned<String>, Spanned<Value>> =
+        toml::from_str(text).map_err(|e| ConfigError::Syntax {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| is_given(matches, arg))
+        .collect();
+    let mut args = Vec::new();
+    for (key, value) in entries {
+        let line = text[..key.span().start].matches('\n').count() + 1;
+        let key = key.into_inner();
+        let configurable =
+            |arg: &&Arg| arg.get_long() == Some(&key) && !NOT_CONFIGURABLE.contains(&key.as_str());
+        let Some(arg) = command.get_arguments().find(configurable) else {
+            return Err(ConfigError::UnknownKey { path, line, key });
+        };
+        let overridden = given.iter().any(|other| {
+            other.get_id() == arg.get_id()
+                || command.get_arg_conflicts_with(arg).contains(other)
+                || command.get_arg_conflicts_with(other).contains(&arg)
+        });
+        if overridden {
+            continue;
+        }
+        let key_args = arg_values(arg, value.get_ref())
+            .and_then(|key_args| validate(command, key_args))
+            .map_err(|message| ConfigError::InvalidValue {
+                path: path.clone(),
+                line,
+                key,
+                message,
+            })?;
+        args.extend(key_args);
+    }
+    Ok(args)
+}
+
+fn is_given(matches: &ArgMatches, arg: &Arg) -> bool {
+    matches!(
+        matches.value_source(arg.get_id().as_str()),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
+}
+
+/// The command line arguments setting `arg` to `value`.
+fn arg_values(arg: &Arg, value: &Value) -> Result<Vec<String>, String> {
+    let option = format!("--{}", arg.get_long().unwrap_or_default());
+    if !arg.get_action().takes_values() {
+        return match value {
+            Value::Boolean(true) => Ok(vec![option]),
+            Value::Boolean(false) => Ok(Vec::new()),
+            _ => Err("expected true or false".to_string()),
+        };
+    }
+    let values = match value {
+        Value::Array(values) => values.iter().map(scalar).collect::<Result<_, _>>()?,
+        value => vec![scalar(value)?],
+    };
+    let values_per_option = arg.get_num_args().map_or(1, |range| range.min_values());
+    if values_per_option > 1 {
+        Ok([vec![option], values].concat())
+    } else {
+        Ok(values
+            .into_iter()
+            .map(|value| format!("{}={}", option, value))
+            .collect())
+    }
+}
+
+fn scalar(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        Value::Datetime(value) => Ok(value.to_string()),
+        Value::Array(_) | Value::Table(_) => Err("expected a single value".to_string()),
+    }
+}
+
+/// Replaces a parse error caused by the value of an environment variable by
+/// an error naming the variable. Returns `None` for other errors.
+pub(crate) fn env_error(command: &Command, error: &clap::Error) -> Option<clap::Error> {
+    if !matches!(
+        error.kind(),
+        clap::error::ErrorKind::InvalidValue | clap::error::ErrorKind::ValueValidation
+    ) {
+        return None;
+    }
+    command.get_arguments().find_map(|arg| {
+        let variable = arg.get_env()?;
+        let value = std::env::var(variable).ok()?;
+        let option = format!("--{}={}", arg.get_long()?, value);
+        let message = validate(command, vec![option]).err()?;
+        Some(command.clone().error(
+            error.kind(),
+            format!(
+                "{} (from environment variable {})",
+                message,
+                variable.to_string_lossy()
+            ),
+        ))
+    })
+}
+
+/// Checks `args` with the parser of `command` to report invalid values with
+/// the location in the config file.
+fn validate(command: &Command, args: Vec<String>) -> Result<Vec<String>, String> {
+    let argv = [command.get_name().to_string()]
+        .into_iter()
+        .chain(args.clone());
+    match command.clone().try_get_matches_from(argv) {
+        Ok(_) => Ok(args),
+        Err(e) => {
+            let message = e.to_string();
+            let first_line = message.lines().next().unwrap_or_default();
+            Err(first_line.trim_start_matches("error: ").to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::CommandFactory;
+
+    use crate::config::{to_args, ConfigError};
+    use crate::CliParams;
+
+    fn args(text: &str, cli: &[&str]) -> Result<Vec<String>, ConfigError> {
+        let command = CliParams::command();
+        let matches = command
+            .clone()
+            .get_matches_from([&["datetime"], cli].concat());
+        to_args(&command, &matches, Path::new("config.toml"), text)
+    }
+
+    #[test]
+    fn keys_mirror_options() {
+        let text = "\
+output = \"json\"
+quiet = true
+silent = false
+epoch-cutoff-year = 2500
+strict = [\"rfc3339\", \"epoch-seconds\"]
+delimiters = [\" (\", \")\"]
+";
+        assert_eq!(
+            args(text, &[]).unwrap(),
+            [
+                "--delimiters",
+                " (",
+                ")",
+                "--epoch-cutoff-year=2500",
+                "--output=json",
+                "--quiet",
+                "--strict=rfc3339",
+                "--strict=epoch-seconds",
+            ]
+        );
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        let text = "output = \"json\"\nunit = \"ms\"\nformat = \"%H\"\n";
+        assert_eq!(
+            args(text, &["--output", "csv", "--only", "iso"]).unwrap(),
+            ["--unit=ms"]
+        );
+    }
+
+    #[test]
+    fn unknown_key_names_file_and_line() {
+        let error = args("quiet = true\n\nno-such-key = 1\n", &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "config.toml:3: Unknown key \"no-such-key\""
+        );
+        let error = args("watch = true\n", &[]).unwrap_err();
+        assert!(matches!(error, ConfigError::UnknownKey { line: 1, .. }));
+    }
+
+    #[test]
+    fn mode_options_are_not_configurable() {
+        for text in [
+            "copy = \"iso\"\n",
+            "reverse = true\n",
+            "sort-drop-unparsed = true\n",
+            "deltas = true\n",
+            "deltas-reset-on-error = true\n",
+            "pair = true\n",
+            "pair-delimiter = \"|\"\n",
+            "mtime = [\"a\"]\n",
+            "atime = true\n",
+            "url = \"http://localhost\"\n",
+            "json-rewrite = true\n",
+            "json-key = \"a\"\n",
+            "csv = true\n",
+            "column = 2\n",
+        ] {
+            let error = args(text, &[]).unwrap_err();
+            assert!(matches!(error, ConfigError::UnknownKey { .. }), "{text}");
+        }
+    }
+
+    #[test]
+    fn invalid_value_names_file_line_and_key() {
+        let error = args("quiet = true\nunit = \"days\"\n", &[]).unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.starts_with("config.toml:2: Invalid value for key \"unit\": "),
+            "{message}"
+        );
+        assert!(message.contains("days"), "{message}");
+        let error = args("quiet = \"yes\"\n", &[]).unwrap_err();
+        assert!(error.to_string().ends_with("expected true or false"));
+    }
+
+    #[test]
+    fn syntax_error_names_file_and_line() {
+        let message = args("quiet = \n", &[]).unwrap_err().to_string();
+        assert!(message.starts_with("Invalid config file config.toml: "));
+        assert!(message.contains("line 1"), "{message}");
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::input::Lines;
+use crate::output::{write_delta, write_delta_header, write_gaps, OutputOptions};
+use crate::stats::{Durations, Stats};
+use crate::Detectors;
+
+/// Writes the date time of each of the `lines` after the first together with
+/// the delta from the date time of the previous line, which is negative for
+/// lines out of order, followed by the smallest, mean and largest delta and
+/// the line of the largest one. Lines which cannot be parsed are reported to
+/// `errors` unless `quiet` is set and skipped, so the next delta is from the
+/// line before them, or with `reset_on_error` the next line starts anew.
+/// Lines are read one by one. Returns the statistics of the lines.
+pub(crate) fn write_deltas(
+    lines: &mut Lines<impl BufRead>,
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    detectors: &Detectors,
+    output: &OutputOptions,
+    reset_on_error: bool,
+    quiet: bool,
+) -> std::io::Result<Stats> {
+    let mut stats = Stats::default();
+    let mut gaps = Durations::default();
+    let mut previous: Option<DateTime<Utc>> = None;
+    let mut largest: Option<(usize, Duration)> = None;
+    write_delta_header(out, output)?;
+    while let Some((number, line)) = lines.next_line()? {
+        let datetime = match line.and_then(|line| detectors.parse(line)) {
+            Ok(parsed) => parsed.datetime,
+            Err(e) => {
+                stats.add_failure(number);
+                if !quiet {
+                    writeln!(errors, "line {}: {}", number, e)?;
+                }
+                if reset_on_error {
+                    previous = None;
+                }
+                continue;
+            }
+        };
+        stats.add(datetime);
+        if let Some(previous) = previous {
+            let delta = datetime - previous;
+            gaps.add(delta);
+            if largest.is_none_or(|(_, gap)| delta > gap) {
+                largest = Some((number, delta));
+            }
+            write_delta(out, number, datetime, delta, output)?;
+        }
+        previous = Some(datetime);
+    }
+    stats.blank = lines.blank();
+    write_gaps(out, &gaps, largest.map(|(line, _)| line), output)?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::deltas::write_deltas;
+    use crate::input::Lines;
+    use crate::output::{OutputFormat, OutputOptions};
+    use crate::Detectors;
+
+    /// The CSV output of `input`.
+    fn deltas(input: &str, reset_on_error: bool) -> String {
+        let mut lines = Lines::new(Cursor::new(input.to_string()), 0, None).skip_blank();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_deltas(
+            &mut lines,
+            &mut out,
+            &mut std::io::sink(),
+            &Detectors::default(),
+            &output,
+            reset_on_error,
+            true,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn deltas_out_of_order_are_negative() {
+        let input = "2023-02-16T12:34:56Z\n2023-02-16T12:34:58.5Z\n2023-02-16T12:34:57Z\n";
+        assert_eq!(
+            deltas(input, false),
+            "line,at,delta_ms,delta\n\
+             2,2023-02-16T12:34:58.500Z,2500,2.500 seconds\n\
+             3,2023-02-16T12:34:57.000Z,-1500,minus 1.500 seconds\n\
+             gaps,min_gap_ms,mean_gap_ms,max_gap_ms,max_gap_line\n2,-1500,500,2500,2\n"
+        );
+    }
+
+    #[test]
+    fn unparsable_lines_keep_or_reset_the_previous_date_time() {
+        let input = "1676550896\nsoon\n1676550899\n1676550900\n";
+        let kept = deltas(input, false);
+        assert!(
+            kept.contains("\n3,2023-02-16T12:34:59.000Z,3000,3 seconds\n"),
+            "{kept}"
+        );
+        let reset = deltas(input, true);
+        assert!(
+            reset.starts_with("line,at,delta_ms,delta\n4,2023-02-16T12:35:00.000Z,1000,1 second\n"),
+            "{reset}"
+        );
+    }
+}
@@ -0,0 +1,824 @@
+use std::fmt::{Debug, Formatter};
+use std::num::IntErrorKind;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::calendar::invalid_component;
+use crate::ical::IcalDetector;
+use crate::names;
+use crate::protobuf::ProtobufDetector;
+use crate::DateTimeError::{StrictMismatch, Unparsable};
+use crate::{DateTimeError, EpochUnit, InputKind, ParseFailure, ParsedDateTime};
+
+/// Recognizes inputs of one kind.
+///
+/// [Detectors::parse] only calls [Detector::parse] for inputs passing the
+/// cheap pre-filter [Detector::looks_like], so the pre-filter must accept
+/// every input [Detector::parse] accepts.
+///
+/// ```
+/// use chrono::{DateTime, NaiveDateTime};
+/// use datetime_helper::{DateTimeError, Detector, Detectors, InputKind, ParsedDateTime};
+///
+/// struct Compact;
+///
+/// impl Detector for Compact {
+///     fn kind(&self) -> InputKind {
+///         InputKind::Custom("compact")
+///     }
+///
+///     fn looks_like(&self, input: &str) -> bool {
+///         input.len() == 15 && input.as_bytes()[8] == b'T'
+///     }
+///
+///     fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+///         let datetime = NaiveDateTime::parse_from_str(input, "%Y%m%dT%H%M%S")?;
+///         Ok(ParsedDateTime::new(input, self.kind(), datetime.and_utc().into()))
+///     }
+/// }
+///
+/// let detectors = Detectors::builder().defaults().push(Compact).build();
+/// let parsed = detectors.parse("20230216T123456").unwrap();
+/// assert_eq!(parsed.datetime.timestamp(), 1676550896);
+/// ```
+pub trait Detector: Send + Sync {
+    /// The kind of the recognized inputs, whose name identifies the detector.
+    fn kind(&self) -> InputKind;
+
+    /// Whether `input` may be of the kind of this detector.
+    fn looks_like(&self, input: &str) -> bool;
+
+    /// Parses `input`, which has no surrounding whitespace.
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError>;
+
+    /// Why `input` is accepted or not besides the result of
+    /// [Detector::parse], for tracing heuristics like the epoch cutoff.
+    fn reason(&self, _input: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Receives the steps of [Detectors::parse_traced], e.g. to print them.
+pub trait Trace {
+    /// Called before `detector` looks at the input.
+    fn start(&mut self, _detector: &dyn Detector) {}
+
+    /// Called after `detector` looked at `input`, with `None` if its
+    /// pre-filter rejected the input.
+    fn finish(
+        &mut self,
+        detector: &dyn Detector,
+        input: &str,
+        result: Option<&Result<ParsedDateTime, DateTimeError>>,
+    );
+
+    /// Called before the detectors look at `cleaned`, which is the input
+    /// without surrounding punctuation after it failed to parse.
+    fn clean(&mut self, _cleaned: &str) {}
+}
+
+/// RFC 3339 timestamps like 2023-02-16T12:34:56.789Z.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rfc3339Detector;
+
+impl Detector for Rfc3339Detector {
+    fn kind(&self) -> InputKind {
+        InputKind::Rfc3339
+    }
+
+    /// RFC 3339 requires a year of four digits followed by `-`.
+    fn looks_like(&self, input: &str) -> bool {
+        input.as_bytes().get(4) == Some(&b'-')
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let datetime = DateTime::parse_from_rfc3339(input).map_err(|e| {
+            invalid_component(input).map_or(e.into(), DateTimeError::InvalidComponent)
+        })?;
+        Ok(ParsedDateTime::new(input, self.kind(), datetime))
+    }
+}
+
+/// RFC 2822 date times like Thu, 16 Feb 2023 12:34:56 +0000. Names of
+/// months and weekdays may be written in any case and in full, like
+/// `THURSDAY, 16 february 2023 12:34:56 +0000`. A weekday which does not
+/// match the date is ignored, see [ParsedDateTime::weekday_mismatch].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rfc2822Detector;
+
+impl Detector for Rfc2822Detector {
+    fn kind(&self) -> InputKind {
+        InputKind::Rfc2822
+    }
+
+    /// RFC 2822 requires the name of the month.
+    fn looks_like(&self, input: &str) -> bool {
+        input.bytes().any(|b| b.is_ascii_alphabetic())
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        // chrono rejects a wrong weekday and full names of months.
+        let date = names::split_weekday(input).map_or(input, |(_, date)| date);
+        let mut tokens: Vec<&str> = date.split_whitespace().collect();
+        if let Some(month) = tokens.get(1).and_then(|token| names::month(token)) {
+            tokens[1] = names::month_abbreviation(month);
+        }
+        let datetime = DateTime::parse_from_rfc2822(&tokens.join(" "))?;
+        Ok(ParsedDateTime::new(input, self.kind(), datetime))
+    }
+}
+
+/// Epoch times in one unit, i.e. integers counting from
+/// 1970-01-01T00:00:00Z.
+#[derive(Clone, Copy, Debug)]
+pub struct EpochDetector {
+    unit: EpochUnit,
+    /// The cutoff year and the epoch seconds of its start.
+    cutoff: Option<(i32, i64)>,
+}
+
+impl EpochDetector {
+    /// Detects all epoch times in `unit`.
+    pub fn new(unit: EpochUnit) -> Self {
+        EpochDetector { unit, cutoff: None }
+    }
+
+    /// Only accepts epoch times in seconds before `year`, which leaves larger
+    /// values to a following detector of epoch milliseconds.
+    pub fn seconds_before(year: i32) -> Self {
+        let cutoff = match NaiveDate::from_ymd_opt(year, 1, 1) {
+            Some(start) => start.and_time(NaiveTime::MIN).and_utc().timestamp(),
+            None if year > 0 => i64::MAX,
+            None => i64::MIN,
+        };
+        EpochDetector {
+            unit: EpochUnit::S,
+            cutoff: Some((year, cutoff)),
+        }
+    }
+}
+
+impl Detector for EpochDetector {
+    fn kind(&self) -> InputKind {
+        InputKind::of_unit(self.unit)
+    }
+
+    /// Epoch times are integers with an optional sign.
+    fn looks_like(&self, input: &str) -> bool {
+        let digits = input.strip_prefix(['+', '-']).unwrap_or(input);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let datetime = match self.cutoff {
+            Some((_, cutoff)) => epoch_seconds_to_datetime(input, cutoff)?,
+            None => epoch_to_datetime(input, self.unit)?,
+        };
+        Ok(ParsedDateTime::new(input, self.kind(), datetime.into()))
+    }
+
+    /// Compares the year of guessed epoch seconds with the cutoff year.
+    fn reason(&self, input: &str) -> Option<String> {
+        let (year, cutoff) = self.cutoff?;
+        let epoch_time: i64 = input.parse().ok()?;
+        let epoch_year = Utc.timestamp_opt(epoch_time, 0).single()?.year();
+        Some(match epoch_time < cutoff {
+            true => format!("year {} < cutoff {}", epoch_year, year),
+            false => format!("year {} ≥ cutoff {}", epoch_year, year),
+        })
+    }
+}
+
+/// Epoch seconds with an optional fraction like `1676550896.789`, as used by
+/// the HTTP API of Prometheus. Digits beyond nanoseconds are ignored.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FloatSecondsDetector;
+
+impl Detector for FloatSecondsDetector {
+    fn kind(&self) -> InputKind {
+        InputKind::FloatSeconds
+    }
+
+    /// Float seconds are digits with an optional sign and fraction.
+    fn looks_like(&self, input: &str) -> bool {
+        let digits = input.strip_prefix(['+', '-']).unwrap_or(input);
+        let (seconds, fraction) = digits.split_once('.').unwrap_or((digits, "0"));
+        [seconds, fraction]
+            .iter()
+            .all(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let (seconds, nanos) = match input.split_once('.') {
+            Some((seconds, fraction)) => (seconds, parse_nanos(fraction)?),
+            None => (input, 0),
+        };
+        let whole = Utc
+            .timestamp_opt(parse_epoch_time(seconds, EpochUnit::S)?, 0)
+            .single()
+            .ok_or_else(|| out_of_range(input, EpochUnit::S))?;
+        let fraction = Duration::nanoseconds(nanos.into());
+        // The epoch range leaves a day to spare for the fraction.
+        let datetime = match seconds.starts_with('-') {
+            true => whole - fraction,
+            false => whole + fraction,
+        };
+        Ok(ParsedDateTime::new(input, self.kind(), datetime.into()))
+    }
+}
+
+/// Parses the fractional digits of seconds as nanoseconds.
+fn parse_nanos(fraction: &str) -> Result<u32, DateTimeError> {
+    if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(DateTimeError::InvalidFraction(fraction.to_string()));
+    }
+    Ok(format!("{:0<9.9}", fraction).parse()?)
+}
+
+/// An ordered list of detectors. An input is of the kind of the first
+/// detector accepting it.
+#[derive(Clone)]
+pub struct Detectors {
+    detectors: Vec<Arc<dyn Detector>>,
+    strict: bool,
+}
+
+impl Detectors {
+    /// A builder starting without detectors.
+    pub fn builder() -> DetectorsBuilder {
+        DetectorsBuilder {
+            detectors: Vec::new(),
+            strict: false,
+        }
+    }
+
+    /// Whether a detector is of `kind`.
+    pub fn contains(&self, kind: InputKind) -> bool {
+        self.detectors
+            .iter()
+            .any(|detector| detector.kind() == kind)
+    }
+
+    /// The kinds of the detectors in their order.
+    pub fn kinds(&self) -> Vec<InputKind> {
+        self.detectors.iter().map(|d| d.kind()).collect()
+    }
+
+    /// Parses `input`, which may be surrounded by whitespace, with the first
+    /// detector accepting it. Unless only the kinds of
+    /// [DetectorsBuilder::strict] are allowed, an input which fails to parse
+    /// is tried again without one trailing punctuation mark like `.` or `,`
+    /// and one pair of surrounding brackets, so `(1676550896).` is accepted.
+    /// A trailing `.` is always punctuation, `1676550896.` does not have an
+    /// empty fraction.
+    pub fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let trimmed = input.trim();
+        if let Some(parsed) = self.detect(trimmed) {
+            return Ok(parsed);
+        }
+        if let Some(parsed) = self
+            .cleaned(trimmed)
+            .and_then(|cleaned| self.detect(cleaned))
+        {
+            return Ok(parsed);
+        }
+        self.fail(trimmed)
+    }
+
+    fn detect(&self, input: &str) -> Option<ParsedDateTime> {
+        self.detectors
+            .iter()
+            .filter(|detector| detector.looks_like(input))
+            .find_map(|detector| detector.parse(input).ok())
+    }
+
+    /// Parses `input` like [Detectors::parse] and reports each detector
+    /// looking at the input to `trace`.
+    ///
+    /// ```
+    /// use datetime_helper::{DateTimeError, Detector, Detectors, ParsedDateTime, Trace};
+    ///
+    /// struct Kinds(Vec<String>);
+    ///
+    /// impl Trace for Kinds {
+    ///     fn finish(
+    ///         &mut self,
+    ///         detector: &dyn Detector,
+    ///         _input: &str,
+    ///         result: Option<&Result<ParsedDateTime, DateTimeError>>,
+    ///     ) {
+    ///         let outcome = result.map_or("rejected", |r| if r.is_ok() { "ok" } else { "no" });
+    ///         self.0.push(format!("{}: {}", detector.kind(), outcome));
+    ///     }
+    /// }
+    ///
+    /// let mut kinds = Kinds(Vec::new());
+    /// Detectors::default().parse_traced("1676550896789", &mut kinds).unwrap();
+    /// assert_eq!(
+    ///     kinds.0,
+    ///     ["rfc3339: rejected", "rfc2822: rejected", "epoch-seconds: no", "epoch-millis: ok"]
+    /// );
+    /// ```
+    pub fn parse_traced(
+        &self,
+        input: &str,
+        trace: &mut impl Trace,
+    ) -> Result<ParsedDateTime, DateTimeError> {
+        let trimmed = input.trim();
+        if let Some(parsed) = self.detect_traced(trimmed, trace) {
+            return Ok(parsed);
+        }
+        if let Some(cleaned) = self.cleaned(trimmed) {
+            trace.clean(cleaned);
+            if let Some(parsed) = self.detect_traced(cleaned, trace) {
+                return Ok(parsed);
+            }
+        }
+        self.fail(trimmed)
+    }
+
+    fn detect_traced(&self, input: &str, trace: &mut impl Trace) -> Option<ParsedDateTime> {
+        for detector in &self.detectors {
+            trace.start(detector.as_ref());
+            if !detector.looks_like(input) {
+                trace.finish(detector.as_ref(), input, None);
+                continue;
+            }
+            let result = detector.parse(input);
+            trace.finish(detector.as_ref(), input, Some(&result));
+            if result.is_ok() {
+                return result.ok();
+            }
+        }
+        None
+    }
+
+    /// `input` without surrounding punctuation to try again, `None` if it
+    /// has none or if only the kinds of [DetectorsBuilder::strict] are
+    /// allowed.
+    fn cleaned<'a>(&self, input: &'a str) -> Option<&'a str> {
+        if self.strict {
+            return None;
+        }
+        let cleaned = input
+            .strip_suffix(['.', ',', ';', ':', '!', '?'])
+            .unwrap_or(input);
+        let cleaned = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')]
+            .into_iter()
+            .find_map(|(open, close)| cleaned.strip_prefix(open)?.strip_suffix(close))
+            .unwrap_or(cleaned)
+            .trim();
+        (cleaned.len() < input.len() && !cleaned.is_empty()).then_some(cleaned)
+    }
+
+    /// Parses `inputs` on the threads of the global rayon pool. The results
+    /// are in the order of the inputs.
+    #[cfg(feature = "rayon")]
+    pub fn parse_many_parallel<'a>(
+        &self,
+        inputs: impl IntoParallelIterator<Item = &'a str>,
+    ) -> Vec<Result<ParsedDateTime, DateTimeError>> {
+        inputs
+            .into_par_iter()
+            .map(|input| self.parse(input))
+            .collect()
+    }
+
+    /// Collects the errors of all detectors for the message. The errors are
+    /// not kept while detecting, as most inputs are detected.
+    fn fail(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let mut attempts = Vec::with_capacity(self.detectors.len());
+        for detector in &self.detectors {
+            match detector.parse(input) {
+                Ok(parsed) => return Ok(parsed),
+                Err(e) => attempts.push((detector.kind(), e)),
+            }
+        }
+        let error = if attempts.len() == 1 {
+            attempts.remove(0).1
+        } else {
+            Unparsable(ParseFailure {
+                input: input.to_string(),
+                attempts,
+            })
+        };
+        if !self.strict {
+            return Err(error);
+        }
+        Err(StrictMismatch {
+            allowed: self
+                .kinds()
+                .iter()
+                .map(|kind| kind.name())
+                .collect::<Vec<_>>()
+                .join(", "),
+            source: Box::new(error),
+        })
+    }
+}
+
+/// The default detectors of [ParseOptions](crate::ParseOptions).
+impl Default for Detectors {
+    fn default() -> Self {
+        Detectors::builder().defaults().build()
+    }
+}
+
+impl Debug for Detectors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Detectors")
+            .field("kinds", &self.kinds())
+            .field("strict", &self.strict)
+            .finish()
+    }
+}
+
+/// Builds [Detectors] by adding, removing and reordering detectors.
+pub struct DetectorsBuilder {
+    detectors: Vec<Arc<dyn Detector>>,
+    strict: bool,
+}
+
+impl DetectorsBuilder {
+    /// Appends the detectors of [InputKind::DEFAULT].
+    pub fn defaults(self) -> Self {
+        self.push(Rfc3339Detector)
+            .push(Rfc2822Detector)
+            .push(EpochDetector::seconds_before(
+                crate::DEFAULT_EPOCH_CUTOFF_YEAR,
+            ))
+            .push(EpochDetector::new(EpochUnit::Ms))
+            .push(IcalDetector::default())
+            .push(ProtobufDetector)
+    }
+
+    /// Appends a detector.
+    pub fn push(mut self, detector: impl Detector + 'static) -> Self {
+        self.detectors.push(Arc::new(detector));
+        self
+    }
+
+    /// Inserts a detector at `index`, which is clamped to the number of
+    /// detectors.
+    pub fn insert(mut self, index: usize, detector: impl Detector + 'static) -> Self {
+        let index = index.min(self.detectors.len());
+        self.detectors.insert(index, Arc::new(detector));
+        self
+    }
+
+    /// Removes the detectors of `kind`.
+    pub fn remove(mut self, kind: InputKind) -> Self {
+        self.detectors.retain(|detector| detector.kind() != kind);
+        self
+    }
+
+    /// Makes errors name the kinds of the detectors, like with `--strict`.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Keeps only the detectors of `kinds` in the order of `kinds`, like
+    /// `--strict`. Errors then name the allowed kinds.
+    pub fn only(mut self, kinds: &[InputKind]) -> Result<Self, DateTimeError> {
+        let mut detectors = Vec::with_capacity(kinds.len());
+        for kind in kinds {
+            match self.detectors.iter().find(|d| d.kind() == *kind) {
+                Some(detector) => detectors.push(detector.clone()),
+                None => return Err(DateTimeError::UnsupportedKind(kind.to_string())),
+            }
+        }
+        self.detectors = detectors;
+        Ok(self.strict())
+    }
+
+    /// The detectors in their order.
+    pub fn build(self) -> Detectors {
+        Detectors {
+            detectors: self.detectors,
+            strict: self.strict,
+        }
+    }
+}
+
+/// Epoch seconds are only accepted if they are less than `cutoff`, larger
+/// values are left to the detector of epoch milliseconds.
+fn epoch_seconds_to_datetime(input: &str, cutoff: i64) -> Result<DateTime<Utc>, DateTimeError> {
+    let epoch_time = parse_epoch_time(input, EpochUnit::S)?;
+    if epoch_time >= cutoff {
+        return Err(DateTimeError::InvalidEpochTime(epoch_time));
+    }
+    epoch_to_datetime(input, EpochUnit::S)
+}
+
+fn epoch_to_datetime(input: &str, unit: EpochUnit) -> Result<DateTime<Utc>, DateTimeError> {
+    let epoch_time = parse_epoch_time(input, unit)?;
+    let datetime = match unit {
+        EpochUnit::S => Utc.timestamp_opt(epoch_time, 0).single(),
+        EpochUnit::Ms => Utc.timestamp_millis_opt(epoch_time).single(),
+        EpochUnit::Us => Utc.timestamp_micros(epoch_time).single(),
+        EpochUnit::Ns => Some(Utc.timestamp_nanos(epoch_time)),
+    };
+    datetime.ok_or_else(|| out_of_range(input, unit))
+}
+
+/// Parses an epoch time within [epoch_range].
+pub(crate) fn parse_epoch_time(input: &str, unit: EpochUnit) -> Result<i64, DateTimeError> {
+    let epoch_time = input
+        .parse()
+        .map_err(|e: std::num::ParseIntError| match e.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => out_of_range(input, unit),
+            _ => e.into(),
+        })?;
+    match epoch_range(unit).contains(&epoch_time) {
+        true => Ok(epoch_time),
+        false => Err(out_of_range(input, unit)),
+    }
+}
+
+fn out_of_range(input: &str, unit: EpochUnit) -> DateTimeError {
+    DateTimeError::OutOfRange {
+        value: input.to_string(),
+        unit: Some(unit),
+    }
+}
+
+/// The epoch times in `unit` whose local times can be represented in all
+/// time zones, which fails within a day of the range of chrono.
+pub(crate) fn epoch_range(unit: EpochUnit) -> RangeInclusive<i64> {
+    const DAY: i64 = 86_400;
+    let first = i128::from(DateTime::<Utc>::MIN_UTC.timestamp() + DAY);
+    let last = i128::from(DateTime::<Utc>::MAX_UTC.timestamp() - DAY);
+    let per_second = i128::from(unit.per_second());
+    let clamp = |epoch_time: i128| epoch_time.clamp(i64::MIN.into(), i64::MAX.into()) as i64;
+    clamp(first * per_second)..=clamp((last + 1) * per_second - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use chrono::{DateTime, Utc};
+
+    use crate::detect::epoch_range;
+    use crate::{
+        DateTimeError, Detector, Detectors, EpochDetector, EpochUnit, FloatSecondsDetector,
+        InputKind, ParseOptions, ParsedDateTime, Precision, Rfc2822Detector, Rfc3339Detector,
+    };
+
+    /// Accepts `now`, counting its calls of [Detector::parse].
+    struct Now(Arc<AtomicUsize>);
+
+    impl Detector for Now {
+        fn kind(&self) -> InputKind {
+            InputKind::Custom("now")
+        }
+
+        fn looks_like(&self, input: &str) -> bool {
+            input.starts_with('n')
+        }
+
+        fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            match input {
+                "now" => Ok(ParsedDateTime::new(
+                    input,
+                    self.kind(),
+                    DateTime::<Utc>::UNIX_EPOCH.into(),
+                )),
+                _ => Err(DateTimeError::InvalidFormat(input.to_string())),
+            }
+        }
+    }
+
+    fn now() -> Now {
+        Now(Arc::new(AtomicUsize::new(0)))
+    }
+
+    #[test]
+    fn defaults_detect_like_before() {
+        let detectors = Detectors::default();
+        assert_eq!(detectors.kinds(), InputKind::DEFAULT);
+        let parsed = detectors.parse(" 1676550896 ").unwrap();
+        assert_eq!(parsed.format, InputKind::EpochSeconds);
+        let parsed = detectors.parse("64060588800").unwrap();
+        assert_eq!(parsed.format, InputKind::EpochMillis);
+        let parsed = detectors.parse("-1676550896").unwrap();
+        assert_eq!(parsed.epoch_seconds(), -1676550896);
+    }
+
+    #[test]
+    fn rfc2822_names_in_any_case() {
+        for input in [
+            "Thu, 16 Feb 2023 12:34:56 +0000",
+            "thu, 16 feb 2023 12:34:56 +0000",
+            "THU, 16 FEB 2023 12:34:56 +0000",
+            "Thursday, 16 February 2023 12:34:56 +0000",
+            "16 FEBRUARY 2023 12:34:56 +0000",
+            "Wed, 16 Feb 2023 12:34:56 +0000",
+        ] {
+            let parsed = Rfc2822Detector.parse(input).unwrap();
+            assert_eq!(parsed.epoch_seconds(), 1676550896, "{input}");
+            assert_eq!(parsed.input, input);
+        }
+        for input in [
+            "Foo, 16 Feb 2023 12:34:56 +0000",
+            "16 Febr 2023 12:34:56 +0000",
+        ] {
+            assert!(Rfc2822Detector.parse(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn epoch_times_at_the_end_of_the_range_are_rejected() {
+        let detectors = Detectors::builder()
+            .push(EpochDetector::new(EpochUnit::Ms))
+            .build();
+        let last = DateTime::<Utc>::MAX_UTC.timestamp_millis();
+        assert!(detectors.parse(&last.to_string()).is_err());
+        assert!(detectors.parse(&(last - 86_400_000).to_string()).is_ok());
+        let first = DateTime::<Utc>::MIN_UTC.timestamp_millis();
+        assert!(detectors.parse(&first.to_string()).is_err());
+    }
+
+    #[test]
+    fn float_seconds_with_a_fraction() {
+        let parsed = FloatSecondsDetector.parse("1676550896.789").unwrap();
+        assert_eq!(parsed.epoch_millis(), 1676550896789);
+        assert_eq!(parsed.precision, Precision::Millis);
+        let parsed = FloatSecondsDetector.parse("1676550896.1234567891").unwrap();
+        assert_eq!(parsed.datetime.timestamp_subsec_nanos(), 123456789);
+        assert_eq!(
+            FloatSecondsDetector.parse("-1.5").unwrap().epoch_millis(),
+            -1500
+        );
+        assert_eq!(
+            FloatSecondsDetector.parse("-0.25").unwrap().epoch_millis(),
+            -250
+        );
+        for input in ["1676550896.", ".5", "1.5e3", "1.-5", "1..5"] {
+            assert!(!FloatSecondsDetector.looks_like(input), "{input}");
+            assert!(FloatSecondsDetector.parse(input).is_err(), "{input}");
+        }
+        let options = ParseOptions {
+            float_seconds: true,
+            ..Default::default()
+        };
+        let detectors = options.detectors().unwrap();
+        assert_eq!(
+            detectors.parse("1676550896").unwrap().format,
+            InputKind::EpochSeconds
+        );
+        assert_eq!(
+            detectors.parse("1676550896.789").unwrap().format,
+            InputKind::FloatSeconds
+        );
+        assert!(Detectors::default().parse("1676550896.789").is_err());
+    }
+
+    /// The unit of the error of parsing `input` as epoch time in `unit`, if
+    /// it is out of range.
+    fn out_of_range_unit(input: &str, unit: EpochUnit) -> Option<EpochUnit> {
+        let detector = EpochDetector::new(unit);
+        match detector.parse(input).unwrap_err() {
+            DateTimeError::OutOfRange { value, unit } if value == input => unit,
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn epoch_times_out_of_range() {
+        let too_large = "123456789012345678901234567890";
+        for unit in [EpochUnit::S, EpochUnit::Ms, EpochUnit::Us, EpochUnit::Ns] {
+            assert_eq!(out_of_range_unit(too_large, unit), Some(unit));
+            let range = epoch_range(unit);
+            let detector = EpochDetector::new(unit);
+            assert!(detector.parse(&range.start().to_string()).is_ok());
+            assert!(detector.parse(&range.end().to_string()).is_ok());
+            if unit != EpochUnit::Ns {
+                let before = (range.start() - 1).to_string();
+                let after = (range.end() + 1).to_string();
+                assert_eq!(out_of_range_unit(&before, unit), Some(unit));
+                assert_eq!(out_of_range_unit(&after, unit), Some(unit));
+                assert_eq!(out_of_range_unit(&i64::MAX.to_string(), unit), Some(unit));
+                assert_eq!(out_of_range_unit(&i64::MIN.to_string(), unit), Some(unit));
+            }
+        }
+        let detector = EpochDetector::seconds_before(3000);
+        let error = detector.parse(&i64::MAX.to_string()).unwrap_err();
+        assert!(matches!(error, DateTimeError::OutOfRange { .. }), "{error}");
+        let error = detector.parse("64060588800").unwrap_err();
+        assert!(matches!(
+            error,
+            DateTimeError::InvalidEpochTime(64060588800)
+        ));
+    }
+
+    #[test]
+    fn surrounding_punctuation_is_removed_after_failing() {
+        let detectors = Detectors::default();
+        let parsed = detectors.parse("1676550896.").unwrap();
+        assert_eq!(parsed.input, "1676550896");
+        assert_eq!(parsed.format, InputKind::EpochSeconds);
+        assert_eq!(parsed.precision, crate::Precision::Seconds);
+        for input in [
+            "[2023-02-16T12:34:56Z]",
+            "2023-02-16T12:34:56Z,",
+            "(1676550896).",
+        ] {
+            let parsed = detectors.parse(input).unwrap();
+            assert_eq!(parsed.epoch_seconds(), 1676550896, "{input}");
+        }
+        for input in ["1676550896..", "((1676550896))", "(1676550896", "()", "."] {
+            assert!(detectors.parse(input).is_err(), "{input}");
+        }
+        let error = detectors.parse("foo.").unwrap_err();
+        assert!(error.to_string().starts_with("Cannot parse \"foo.\""));
+    }
+
+    #[test]
+    fn punctuation_is_kept_in_strict_mode() {
+        let detectors = Detectors::builder()
+            .defaults()
+            .only(&[InputKind::EpochSeconds])
+            .unwrap()
+            .build();
+        assert!(detectors.parse("1676550896").is_ok());
+        assert!(detectors.parse("1676550896.").is_err());
+    }
+
+    #[test]
+    fn custom_detector_is_tried_in_order() {
+        let detectors = Detectors::builder()
+            .defaults()
+            .insert(0, now())
+            .remove(InputKind::Rfc2822)
+            .build();
+        assert_eq!(
+            detectors.kinds(),
+            [
+                InputKind::Custom("now"),
+                InputKind::Rfc3339,
+                InputKind::EpochSeconds,
+                InputKind::EpochMillis,
+                InputKind::Ical,
+                InputKind::Protobuf
+            ]
+        );
+        let parsed = detectors.parse("now").unwrap();
+        assert_eq!(
+            (parsed.format, parsed.epoch_seconds()),
+            (InputKind::Custom("now"), 0)
+        );
+        let error = detectors.parse("never").unwrap_err().to_string();
+        assert!(
+            error.starts_with("Cannot parse \"never\" as now: Invalid format"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn pre_filter_skips_parsing_until_all_fail() {
+        let now = now();
+        let calls = now.0.clone();
+        let detectors = Detectors::builder().push(now).push(Rfc3339Detector).build();
+        assert!(detectors.parse("2023-02-16T12:34:56Z").is_ok());
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+        let error = detectors.parse("xyz").unwrap_err().to_string();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(
+            error.starts_with("Cannot parse \"xyz\" as now: Invalid format"),
+            "{error}"
+        );
+        assert!(error.contains("\n  - rfc3339: "), "{error}");
+    }
+
+    #[test]
+    fn only_reorders_and_restricts() {
+        let detectors = Detectors::builder()
+            .defaults()
+            .push(EpochDetector::new(EpochUnit::Ns))
+            .only(&[InputKind::EpochNanos, InputKind::Rfc3339])
+            .unwrap()
+            .build();
+        let parsed = detectors.parse("1676550896").unwrap();
+        assert_eq!(parsed.format, InputKind::EpochNanos);
+        let error = detectors
+            .parse("Thu, 16 Feb 2023 12:34:56 +0000")
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Input is none of the allowed formats epoch-nanos, rfc3339:"));
+        let error = Detectors::builder()
+            .defaults()
+            .only(&[InputKind::EpochMicros])
+            .err()
+            .unwrap();
+        assert!(matches!(error, DateTimeError::UnsupportedKind(kind) if kind == "epoch-micros"));
+    }
+}
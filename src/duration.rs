@@ -0,0 +1,600 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDateTime, Utc};
+
+use crate::DateTimeError;
+
+/// A duration which may contain calendar months besides an exact duration.
+///
+/// Months are added before the exact duration. If the resulting day does not
+/// exist in the target month, it is clamped to the last day of that month, so
+/// 2023-01-31 plus one month is 2023-02-28 and 2024-02-29 plus one year is
+/// 2025-02-28.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalendarDuration {
+    pub months: i64,
+    pub exact: Duration,
+}
+
+impl CalendarDuration {
+    /// Adds this duration to `datetime`, `None` if the result is out of range.
+    pub fn add_to(&self, datetime: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let months = Months::new(u32::try_from(self.months.unsigned_abs()).ok()?);
+        let shifted = if self.months < 0 {
+            datetime.checked_sub_months(months)?
+        } else {
+            datetime.checked_add_months(months)?
+        };
+        shifted.checked_add_signed(self.exact)
+    }
+}
+
+impl std::ops::Neg for CalendarDuration {
+    type Output = CalendarDuration;
+
+    fn neg(self) -> CalendarDuration {
+        CalendarDuration {
+            months: -self.months,
+            exact: -self.exact,
+        }
+    }
+}
+
+impl From<Duration> for CalendarDuration {
+    fn from(exact: Duration) -> Self {
+        CalendarDuration { months: 0, exact }
+    }
+}
+
+impl std::fmt::Display for CalendarDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.months, self.exact.is_zero()) {
+            (0, _) => write!(f, "{}", format_iso(self.exact)),
+            (months, true) => write!(f, "{} months", months),
+            (months, false) => write!(f, "{} months + {}", months, format_iso(self.exact)),
+        }
+    }
+}
+
+/// The calendar time elapsed between two local date times: whole years and
+/// months, added like the months of a [CalendarDuration], then whole days and
+/// the time in whole seconds. So someone born on 2000-02-29 is 23 years old
+/// on 2023-02-28, as 2000-02-29 plus 23 years is 2023-02-28.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Age {
+    pub years: i64,
+    pub months: i64,
+    pub days: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub seconds: i64,
+    /// Whether the start is after the end, so the components count from the
+    /// end to the start.
+    pub future: bool,
+}
+
+impl Age {
+    /// The age of `from` at `to`, `None` if it is out of range.
+    pub fn between(from: NaiveDateTime, to: NaiveDateTime) -> Option<Age> {
+        if from > to {
+            let age = Age::between(to, from)?;
+            return Some(Age {
+                future: true,
+                ..age
+            });
+        }
+        let shifted =
+            |months: i64| from.checked_add_months(Months::new(u32::try_from(months).ok()?));
+        let mut months = i64::from(to.year() - from.year()) * 12 + i64::from(to.month())
+            - i64::from(from.month());
+        let mut anniversary = shifted(months)?;
+        if anniversary > to {
+            months -= 1;
+            anniversary = shifted(months)?;
+        }
+        let seconds = (to - anniversary).num_seconds();
+        Some(Age {
+            years: months / 12,
+            months: months % 12,
+            days: seconds / 86_400,
+            hours: seconds / 3_600 % 24,
+            minutes: seconds / 60 % 60,
+            seconds: seconds % 60,
+            future: false,
+        })
+    }
+
+    /// Formats the age in words, e.g. `35 years, 8 months and 1 day` or
+    /// `2 days and 3 hours in the future`.
+    pub fn humanize(&self) -> String {
+        let plural = |value: i64, unit: &str| match value {
+            1 => format!("1 {}", unit),
+            _ => format!("{} {}s", value, unit),
+        };
+        let mut parts: Vec<String> = [
+            (self.years, "year"),
+            (self.months, "month"),
+            (self.days, "day"),
+            (self.hours, "hour"),
+            (self.minutes, "minute"),
+            (self.seconds, "second"),
+        ]
+        .into_iter()
+        .filter(|(value, _)| *value != 0)
+        .map(|(value, unit)| plural(value, unit))
+        .collect();
+        let last = parts.pop().unwrap_or_else(|| plural(0, "day"));
+        let words = match parts.is_empty() {
+            true => last,
+            false => format!("{} and {}", parts.join(", "), last),
+        };
+        match self.future {
+            true => format!("{} in the future", words),
+            false => words,
+        }
+    }
+}
+
+/// Parses a duration either in ISO 8601 notation like `PT90M` or
+/// `P1Y2M3DT4H5M6.5S` or in shorthand notation like `90s`, `15m` or `1d2h30m`.
+///
+/// A shorthand duration is a sequence of numbers, each followed by one of the
+/// units `y`, `mo`, `w`, `d`, `h`, `m`, `s`, `ms`, `us` and `ns` or their long
+/// names like `month` or `days`. Both notations may start with a sign.
+pub fn parse_duration(input: &str) -> Result<CalendarDuration, DateTimeError> {
+    let invalid = || DateTimeError::InvalidDuration(input.to_string());
+    let (negative, rest) = match input.trim() {
+        s if s.starts_with('-') => (true, &s[1..]),
+        s if s.starts_with('+') => (false, &s[1..]),
+        s => (false, s),
+    };
+    let (months, nanos) = match rest.strip_prefix('P') {
+        Some(iso) => parse_iso(iso),
+        None => parse_shorthand(rest),
+    }
+    .ok_or_else(invalid)?;
+    let duration = CalendarDuration {
+        months,
+        exact: nanos_to_duration(nanos).ok_or_else(invalid)?,
+    };
+    Ok(if negative { -duration } else { duration })
+}
+
+/// Parses the shorthand notation to months and nanoseconds.
+fn parse_shorthand(mut rest: &str) -> Option<(i64, i128)> {
+    if rest.is_empty() {
+        return None;
+    }
+    let mut months: i64 = 0;
+    let mut nanos: i128 = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let letters = rest[digits..]
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .map_or(rest.len(), |i| digits + i);
+        let value: i128 = rest[..digits].parse().ok()?;
+        match shorthand_unit(&rest[digits..letters])? {
+            Unit::Months(per_unit) => {
+                months = i64::try_from(value.checked_mul(per_unit)?)
+                    .ok()?
+                    .checked_add(months)?
+            }
+            Unit::Nanos(per_unit) => nanos = value.checked_mul(per_unit)?.checked_add(nanos)?,
+        }
+        rest = &rest[letters..];
+    }
+    Some((months, nanos))
+}
+
+/// Parses the ISO 8601 notation without the leading `P` to months and nanoseconds.
+fn parse_iso(iso: &str) -> Option<(i64, i128)> {
+    let (date, time) = match iso.split_once('T') {
+        Some((_, "")) => return None,
+        Some((date, time)) => (date, Some(time)),
+        None => (iso, None),
+    };
+    if date.is_empty() && time.is_none() {
+        return None;
+    }
+    let mut months: i64 = 0;
+    let mut nanos: i128 = 0;
+    for (value, designator) in iso_components(date)? {
+        match designator {
+            'Y' => months = months.checked_add(i64::try_from(value?.checked_mul(12)?).ok()?)?,
+            'M' => months = months.checked_add(i64::try_from(value?).ok()?)?,
+            'W' => nanos = nanos.checked_add(value?.checked_mul(7 * NANOS_PER_DAY)?)?,
+            'D' => nanos = nanos.checked_add(value?.checked_mul(NANOS_PER_DAY)?)?,
+            _ => return None,
+        }
+    }
+    for (value, designator) in iso_components(time.unwrap_or(""))? {
+        match designator {
+            'H' => nanos = nanos.checked_add(value?.checked_mul(3_600_000_000_000)?)?,
+            'M' => nanos = nanos.checked_add(value?.checked_mul(60_000_000_000)?)?,
+            'S' => nanos = nanos.checked_add(value?)?,
+            _ => return None,
+        }
+    }
+    Some((months, nanos))
+}
+
+/// Splits `part` into values and their designators. Values are integers,
+/// only seconds may have a fraction. Seconds are returned in nanoseconds.
+fn iso_components(part: &str) -> Option<Vec<(Option<i128>, char)>> {
+    let mut components = Vec::new();
+    let mut rest = part;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.' && c != ',')?;
+        let designator = rest[end..].chars().next()?;
+        let number = rest[..end].replace(',', ".");
+        let value = match (designator, number.split_once('.')) {
+            (_, None) if number.is_empty() => return None,
+            ('S', None) => number.parse::<i128>().ok()?.checked_mul(1_000_000_000),
+            ('S', Some((seconds, fraction))) if !seconds.is_empty() && !fraction.is_empty() => {
+                let fraction = format!("{:0<9}", fraction);
+                let subsec: i128 = fraction.get(..9)?.parse().ok()?;
+                seconds
+                    .parse::<i128>()
+                    .ok()?
+                    .checked_mul(1_000_000_000)?
+                    .checked_add(subsec)
+            }
+            (_, None) => number.parse().ok(),
+            _ => return None,
+        };
+        components.push((value, designator));
+        rest = &rest[end + designator.len_utf8()..];
+    }
+    Some(components)
+}
+
+const NANOS_PER_DAY: i128 = 86_400_000_000_000;
+
+enum Unit {
+    Months(i128),
+    Nanos(i128),
+}
+
+fn shorthand_unit(unit: &str) -> Option<Unit> {
+    let unit = match unit {
+        "y" | "year" | "years" => Unit::Months(12),
+        "mo" | "month" | "months" => Unit::Months(1),
+        "w" | "week" | "weeks" => Unit::Nanos(7 * NANOS_PER_DAY),
+        "d" | "day" | "days" => Unit::Nanos(NANOS_PER_DAY),
+        "h" | "hour" | "hours" => Unit::Nanos(3_600_000_000_000),
+        "m" | "min" | "minute" | "minutes" => Unit::Nanos(60_000_000_000),
+        "s" | "sec" | "second" | "seconds" => Unit::Nanos(1_000_000_000),
+        "ms" => Unit::Nanos(1_000_000),
+        "us" => Unit::Nanos(1_000),
+        "ns" => Unit::Nanos(1),
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// Converts nanoseconds to a [Duration], `None` if they exceed its range.
+fn nanos_to_duration(nanos: i128) -> Option<Duration> {
+    let seconds = i64::try_from(nanos.div_euclid(1_000_000_000)).ok()?;
+    let subsec_nanos = nanos.rem_euclid(1_000_000_000) as i64;
+    if seconds.checked_abs()? > i64::MAX / 1_000 {
+        return None;
+    }
+    Some(Duration::seconds(seconds) + Duration::nanoseconds(subsec_nanos))
+}
+
+/// Splits the absolute value of `duration` into days, hours, minutes,
+/// seconds and milliseconds.
+fn components(duration: Duration) -> (i64, i64, i64, i64, i64) {
+    let millis = duration.num_milliseconds().unsigned_abs() as i64;
+    (
+        millis / 86_400_000,
+        millis / 3_600_000 % 24,
+        millis / 60_000 % 60,
+        millis / 1_000 % 60,
+        millis % 1_000,
+    )
+}
+
+fn sign(duration: Duration) -> &'static str {
+    if duration < Duration::zero() {
+        "-"
+    } else {
+        ""
+    }
+}
+
+/// Formats `duration` as `Dd HH:MM:SS.mmm`, e.g. `12d 11:25:04.789`.
+pub fn format_breakdown(duration: Duration) -> String {
+    let (days, hours, minutes, seconds, millis) = components(duration);
+    format!(
+        "{}{}d {:02}:{:02}:{:02}.{:03}",
+        sign(duration),
+        days,
+        hours,
+        minutes,
+        seconds,
+        millis
+    )
+}
+
+/// Formats `duration` as ISO 8601 duration, e.g. `P12DT11H25M4.789S`.
+pub fn format_iso(duration: Duration) -> String {
+    let (days, hours, minutes, seconds, millis) = components(duration);
+    let mut iso = format!("{}P", sign(duration));
+    if days > 0 {
+        iso += &format!("{}D", days);
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || millis > 0 || days == 0 {
+        iso += "T";
+    }
+    if hours > 0 {
+        iso += &format!("{}H", hours);
+    }
+    if minutes > 0 {
+        iso += &format!("{}M", minutes);
+    }
+    if millis > 0 {
+        iso += &format!("{}.{:03}S", seconds, millis);
+    } else if seconds > 0 || iso.ends_with('T') {
+        iso += &format!("{}S", seconds);
+    }
+    iso
+}
+
+/// Formats `duration` in words, e.g. `12 days, 11 hours and 4.789 seconds`.
+pub fn humanize(duration: Duration) -> String {
+    let (days, hours, minutes, seconds, millis) = components(duration);
+    let plural = |value: i64, unit: &str| match value {
+        1 => format!("1 {}", unit),
+        _ => format!("{} {}s", value, unit),
+    };
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(plural(days, "day"));
+    }
+    if hours > 0 {
+        parts.push(plural(hours, "hour"));
+    }
+    if minutes > 0 {
+        parts.push(plural(minutes, "minute"));
+    }
+    if millis > 0 {
+        parts.push(format!("{}.{:03} seconds", seconds, millis));
+    } else if seconds > 0 || parts.is_empty() {
+        parts.push(plural(seconds, "second"));
+    }
+    let last = parts.pop().unwrap_or_default();
+    let words = match parts.is_empty() {
+        true => last,
+        false => format!("{} and {}", parts.join(", "), last),
+    };
+    match sign(duration) {
+        "-" => format!("minus {}", words),
+        _ => words,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use crate::duration::{
+        format_breakdown, format_iso, humanize, parse_duration, Age, CalendarDuration,
+    };
+
+    fn exact(duration: Duration) -> CalendarDuration {
+        CalendarDuration::from(duration)
+    }
+
+    fn datetime(input: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(input).unwrap().into()
+    }
+
+    fn age(from: &str, to: &str) -> String {
+        let local = |input: &str| chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S");
+        let age = Age::between(local(from).unwrap(), local(to).unwrap()).unwrap();
+        age.humanize()
+    }
+
+    #[test]
+    fn age_around_birthdays() {
+        let born = "1987-06-15 00:00:00";
+        assert_eq!(
+            age(born, "2023-06-14 00:00:00"),
+            "35 years, 11 months and 30 days"
+        );
+        assert_eq!(age(born, "2023-06-15 00:00:00"), "36 years");
+        assert_eq!(
+            age(born, "2023-07-16 00:00:00"),
+            "36 years, 1 month and 1 day"
+        );
+        assert_eq!(age(born, born), "0 days");
+        // Months which are too short for the day are clamped.
+        let leap = "2000-02-29 00:00:00";
+        assert_eq!(
+            age(leap, "2023-02-27 00:00:00"),
+            "22 years, 11 months and 29 days"
+        );
+        assert_eq!(age(leap, "2023-02-28 00:00:00"), "23 years");
+        assert_eq!(
+            age(leap, "2024-02-28 00:00:00"),
+            "23 years, 11 months and 30 days"
+        );
+        assert_eq!(age(leap, "2024-02-29 00:00:00"), "24 years");
+        assert_eq!(
+            age("2023-01-31 00:00:00", "2023-03-01 00:00:00"),
+            "1 month and 1 day"
+        );
+    }
+
+    #[test]
+    fn age_with_time_and_in_the_future() {
+        assert_eq!(
+            age("2023-01-10 12:00:00", "2023-02-10 08:30:15"),
+            "30 days, 20 hours, 30 minutes and 15 seconds"
+        );
+        assert_eq!(
+            age("2030-03-01 00:00:00", "2023-02-28 00:00:00"),
+            "7 years and 1 day in the future"
+        );
+    }
+
+    fn add(input: &str, duration: &str) -> String {
+        let duration = parse_duration(duration).unwrap();
+        let result = duration.add_to(datetime(input)).unwrap();
+        result.to_rfc3339()
+    }
+
+    #[test]
+    fn single_unit() {
+        assert_eq!(parse_duration("15m").unwrap(), exact(Duration::minutes(15)));
+        assert_eq!(parse_duration("90s").unwrap(), exact(Duration::seconds(90)));
+        assert_eq!(parse_duration("2w").unwrap(), exact(Duration::weeks(2)));
+        assert_eq!(
+            parse_duration("250ms").unwrap(),
+            exact(Duration::milliseconds(250))
+        );
+        assert_eq!(
+            parse_duration("7ns").unwrap(),
+            exact(Duration::nanoseconds(7))
+        );
+    }
+
+    #[test]
+    fn combined_units() {
+        let expected = Duration::days(1) + Duration::hours(2) + Duration::minutes(30);
+        assert_eq!(parse_duration("1d2h30m").unwrap(), exact(expected));
+    }
+
+    #[test]
+    fn signed() {
+        assert_eq!(
+            parse_duration("-2h30m").unwrap(),
+            exact(-Duration::minutes(150))
+        );
+        assert_eq!(parse_duration("+1h").unwrap(), exact(Duration::hours(1)));
+    }
+
+    #[test]
+    fn invalid() {
+        for input in [
+            "", "-", "h", "15", "15x", "1.5h", "1h 30m", "P", "PT", "P1H", "PT1D", "P1.5D", "PT.5S",
+        ] {
+            assert!(parse_duration(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert!(parse_duration("9223372036854775807w").is_err());
+        assert!(parse_duration("99999999999999999999999999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn breakdown() {
+        let duration = parse_duration("12d11h25m4s789ms").unwrap().exact;
+        assert_eq!(format_breakdown(duration), "12d 11:25:04.789");
+        assert_eq!(format_breakdown(-duration), "-12d 11:25:04.789");
+        assert_eq!(format_breakdown(Duration::zero()), "0d 00:00:00.000");
+    }
+
+    #[test]
+    fn iso() {
+        let duration = parse_duration("12d11h25m4s789ms").unwrap().exact;
+        assert_eq!(format_iso(duration), "P12DT11H25M4.789S");
+        assert_eq!(format_iso(-duration), "-P12DT11H25M4.789S");
+        assert_eq!(format_iso(Duration::days(2)), "P2D");
+        assert_eq!(format_iso(Duration::minutes(90)), "PT1H30M");
+        assert_eq!(format_iso(Duration::zero()), "PT0S");
+    }
+
+    #[test]
+    fn humanized() {
+        let duration = parse_duration("1d11h1m4s789ms").unwrap().exact;
+        assert_eq!(
+            humanize(duration),
+            "1 day, 11 hours, 1 minute and 4.789 seconds"
+        );
+        assert_eq!(humanize(-Duration::hours(2)), "minus 2 hours");
+        assert_eq!(humanize(Duration::seconds(1)), "1 second");
+        assert_eq!(humanize(Duration::zero()), "0 seconds");
+    }
+
+    #[test]
+    fn calendar_units() {
+        let duration = parse_duration("1y2mo3d").unwrap();
+        assert_eq!(duration.months, 14);
+        assert_eq!(duration.exact, Duration::days(3));
+        assert_eq!(parse_duration("1month").unwrap().months, 1);
+        assert_eq!(parse_duration("-2years").unwrap().months, -24);
+    }
+
+    #[test]
+    fn iso_8601() {
+        assert_eq!(
+            parse_duration("PT90M").unwrap(),
+            exact(Duration::minutes(90))
+        );
+        assert_eq!(parse_duration("P2W").unwrap(), exact(Duration::weeks(2)));
+        assert_eq!(
+            parse_duration("PT6.5S").unwrap(),
+            exact(Duration::milliseconds(6500))
+        );
+        assert_eq!(
+            parse_duration("PT0,000001S").unwrap(),
+            exact(Duration::microseconds(1))
+        );
+        let duration = parse_duration("-P1Y2M3DT4H5M6S").unwrap();
+        assert_eq!(duration.months, -14);
+        let expected = Duration::days(3) + Duration::hours(4) + Duration::minutes(5);
+        assert_eq!(duration.exact, -(expected + Duration::seconds(6)));
+    }
+
+    #[test]
+    fn months_are_clamped_to_end_of_month() {
+        assert_eq!(
+            add("2023-01-31T10:00:00Z", "1month"),
+            "2023-02-28T10:00:00+00:00"
+        );
+        assert_eq!(
+            add("2024-01-31T10:00:00Z", "1mo"),
+            "2024-02-29T10:00:00+00:00"
+        );
+        assert_eq!(
+            add("2023-03-31T10:00:00Z", "-1mo"),
+            "2023-02-28T10:00:00+00:00"
+        );
+        assert_eq!(
+            add("2023-01-31T10:00:00Z", "P1M1D"),
+            "2023-03-01T10:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn years_across_leap_day() {
+        assert_eq!(
+            add("2024-02-29T00:00:00Z", "1y"),
+            "2025-02-28T00:00:00+00:00"
+        );
+        assert_eq!(
+            add("2024-02-29T00:00:00Z", "4y"),
+            "2028-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            add("2024-02-29T00:00:00Z", "-1y"),
+            "2023-02-28T00:00:00+00:00"
+        );
+        assert_eq!(
+            add("2023-02-28T00:00:00Z", "1y"),
+            "2024-02-28T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn out_of_range_result() {
+        let duration = parse_duration("300000y").unwrap();
+        assert!(duration.add_to(datetime("2023-01-01T00:00:00Z")).is_none());
+        let duration = parse_duration("-100000000w").unwrap();
+        assert!(duration.add_to(datetime("2023-01-01T00:00:00Z")).is_none());
+    }
+}
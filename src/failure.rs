@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::{DateTimeError, InputKind};
+
+/// The errors of all attempts to parse an input, in the order of the
+/// attempts.
+#[derive(Debug)]
+pub struct ParseFailure {
+    /// The input without surrounding whitespace.
+    pub input: String,
+    /// Each attempted kind with the error of parsing the input as this kind.
+    pub attempts: Vec<(InputKind, DateTimeError)>,
+}
+
+impl ParseFailure {
+    /// The attempt whose error most likely explains the failure: a local
+    /// time at a transition of its time zone, the first epoch time if the
+    /// input is a number, the first date format if the input contains `-`
+    /// and `:` and the first attempt otherwise.
+    pub fn headline(&self) -> Option<&(InputKind, DateTimeError)> {
+        let digits = self.input.strip_prefix('-').unwrap_or(&self.input);
+        let transition = self.attempts.iter().find(|(_, error)| {
+            matches!(
+                error,
+                DateTimeError::NonexistentLocalTime { .. }
+                    | DateTimeError::AmbiguousLocalTime { .. }
+            )
+        });
+        let preferred = if transition.is_some() {
+            transition
+        } else if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            self.attempts.iter().find(|(kind, _)| kind.unit().is_some())
+        } else if self.input.contains('-') && self.input.contains(':') {
+            self.attempts.iter().find(|(kind, _)| kind.unit().is_none())
+        } else {
+            None
+        };
+        preferred.or(self.attempts.first())
+    }
+}
+
+/// Prints the headline followed by one bullet per other attempt, like
+///
+/// ```text
+/// Cannot parse "2023-02-30T12:34:56Z" as rfc3339: day 30 is out of range for February 2023 (max 28)
+///   - rfc2822: input contains invalid characters
+///   - epoch-seconds: invalid digit found in string
+/// ```
+impl Display for ParseFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(headline) = self.headline() else {
+            return write!(f, "Cannot parse \"{}\"", self.input);
+        };
+        write!(
+            f,
+            "Cannot parse \"{}\" as {}: {}",
+            self.input, headline.0, headline.1
+        )?;
+        for (kind, error) in &self.attempts {
+            if !std::ptr::eq(kind, &headline.0) {
+                write!(f, "\n  - {}: {}", kind, error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.headline()
+            .map(|(_, error)| error as &(dyn Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{parse, parse_with, DateTimeError, InputKind, ParseFailure};
+
+    fn failure(input: &str) -> ParseFailure {
+        match parse(input) {
+            Err(DateTimeError::Unparsable(failure)) => failure,
+            result => panic!("unexpected result {result:?}"),
+        }
+    }
+
+    #[test]
+    fn all_attempts_are_kept_in_order() {
+        let failure = failure(" foo ");
+        assert_eq!(failure.input, "foo");
+        let kinds: Vec<InputKind> = failure.attempts.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, InputKind::DEFAULT);
+    }
+
+    #[test]
+    fn numbers_lead_with_the_numeric_error() {
+        let failure = failure("99999999999999999999");
+        let (kind, error) = failure.headline().unwrap();
+        assert_eq!(*kind, InputKind::EpochSeconds);
+        assert!(matches!(error, DateTimeError::OutOfRange { .. }));
+        assert!(failure.to_string().starts_with(
+            "Cannot parse \"99999999999999999999\" as epoch-seconds: Epoch time out of range"
+        ));
+    }
+
+    #[test]
+    fn dates_lead_with_the_date_format_error() {
+        let failure = failure("2023-02-30T12:34:56Z");
+        let (kind, error) = failure.headline().unwrap();
+        assert_eq!(*kind, InputKind::Rfc3339);
+        assert_eq!(
+            error.to_string(),
+            "day 30 is out of range for February 2023 (max 28)"
+        );
+        let kinds = [InputKind::EpochMillis, InputKind::Rfc2822];
+        let Err(DateTimeError::StrictMismatch { source, .. }) = parse_with(&kinds, "12:34 16-02")
+        else {
+            panic!("expected a strict mismatch");
+        };
+        let DateTimeError::Unparsable(failure) = *source else {
+            panic!("expected all attempts");
+        };
+        assert_eq!(failure.headline().unwrap().0, InputKind::Rfc2822);
+    }
+
+    #[test]
+    fn other_inputs_lead_with_the_first_attempt() {
+        let failure = failure("16 Feb 2023");
+        assert_eq!(failure.headline().unwrap().0, InputKind::Rfc3339);
+    }
+
+    #[test]
+    fn display_lists_the_other_attempts() {
+        let failure = failure("abc");
+        assert_eq!(
+            failure.to_string(),
+            "Cannot parse \"abc\" as rfc3339: premature end of input\n  \
+             - rfc2822: input contains invalid characters\n  \
+             - epoch-seconds: invalid digit found in string\n  \
+             - epoch-millis: invalid digit found in string\n  \
+             - ical: Invalid iCalendar property \"abc\": not a property line\n  \
+             - protobuf: Invalid protobuf timestamp \"abc\": \"abc\" is not a field"
+        );
+    }
+
+    #[test]
+    fn source_is_the_headline_error() {
+        let error = DateTimeError::Unparsable(failure("1676550896x"));
+        let source = error.source().unwrap();
+        assert_eq!(source.to_string(), "premature end of input");
+        let failure = failure("-1e3");
+        let source = failure.source().unwrap();
+        assert_eq!(
+            source.to_string(),
+            failure.headline().unwrap().1.to_string()
+        );
+    }
+}
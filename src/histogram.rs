@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::duration::parse_duration;
+use crate::DateTimeError;
+
+/// The maximum number of rows of a histogram including empty buckets.
+const MAX_BUCKETS: usize = 100_000;
+
+/// The size of the buckets of a histogram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Bucket {
+    /// A number of calendar months starting on the first day of a month.
+    Months(i64),
+    /// An exact number of nanoseconds.
+    Nanos(i128),
+}
+
+/// Parses a bucket size, which is either a duration like `15m` or `1h` or a
+/// unit like `hour` or `month`. Months cannot be combined with an exact
+/// duration.
+pub(crate) fn parse_bucket(input: &str) -> Result<Bucket, DateTimeError> {
+    let duration = match parse_duration(input) {
+        Err(_) if input.chars().all(|c| c.is_ascii_alphabetic()) => {
+            parse_duration(&format!("1{}", input))
+        }
+        result => result,
+    }?;
+    let nanos = i128::from(duration.exact.num_seconds()) * 1_000_000_000
+        + i128::from(duration.exact.subsec_nanos());
+    match (duration.months, nanos) {
+        (months, 0) if months > 0 => Ok(Bucket::Months(months)),
+        (0, nanos) if nanos > 0 => Ok(Bucket::Nanos(nanos)),
+        _ => Err(DateTimeError::InvalidBucket(input.to_string())),
+    }
+}
+
+/// Counts date times per bucket. Buckets are aligned to the local time of
+/// the time zone: days start at midnight, months on the first day and other
+/// durations at multiples of the duration since 1970-01-01T00:00:00.
+pub(crate) struct Histogram {
+    bucket: Bucket,
+    timezone: Tz,
+    counts: BTreeMap<i128, u64>,
+}
+
+impl Histogram {
+    pub(crate) fn new(bucket: Bucket, timezone: Option<Tz>) -> Self {
+        Histogram {
+            bucket,
+            timezone: timezone.unwrap_or(Tz::UTC),
+            counts: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, datetime: DateTime<Utc>) {
+        let index = self.index(datetime.with_timezone(&self.timezone).naive_local());
+        *self.counts.entry(index).or_default() += 1;
+    }
+
+    fn index(&self, local: NaiveDateTime) -> i128 {
+        match self.bucket {
+            Bucket::Months(months) => {
+                let month = i64::from(local.year()) * 12 + i64::from(local.month0());
+                i128::from(month.div_euclid(months))
+            }
+            Bucket::Nanos(nanos) => {
+                let local_nanos = i128::from(local.and_utc().timestamp()) * 1_000_000_000
+                    + i128::from(local.nanosecond());
+                local_nanos.div_euclid(nanos)
+            }
+        }
+    }
+
+    /// The start of the bucket with the given index, `None` if it does not
+    /// exist in the time zone, e.g. because of a daylight saving time gap.
+    fn start(&self, index: i128) -> Option<DateTime<Utc>> {
+        let local = match self.bucket {
+            Bucket::Months(months) => {
+                let month = i64::try_from(index).ok()?.checked_mul(months)?;
+                let year = i32::try_from(month.div_euclid(12)).ok()?;
+                let month = u32::try_from(month.rem_euclid(12)).ok()? + 1;
+                NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?
+            }
+            Bucket::Nanos(nanos) => {
+                let start = index.checked_mul(nanos)?;
+                let seconds = i64::try_from(start.div_euclid(1_000_000_000)).ok()?;
+                let nanos = start.rem_euclid(1_000_000_000) as u32;
+                DateTime::from_timestamp(seconds, nanos)?.naive_utc()
+            }
+        };
+        Some(
+            self.timezone
+                .from_local_datetime(&local)
+                .earliest()?
+                .with_timezone(&Utc),
+        )
+    }
+
+    /// The start and count of each bucket from the earliest to the latest
+    /// date time, including empty buckets.
+    pub(crate) fn rows(&self) -> Result<Vec<(DateTime<Utc>, u64)>, DateTimeError> {
+        let (Some((&first, _)), Some((&last, _))) =
+            (self.counts.first_key_value(), self.counts.last_key_value())
+        else {
+            return Ok(Vec::new());
+        };
+        if last - first >= MAX_BUCKETS as i128 {
+            return Err(DateTimeError::TooManyBuckets(MAX_BUCKETS));
+        }
+        Ok((first..=last)
+            .filter_map(|index| {
+                let count = self.counts.get(&index).copied().unwrap_or_default();
+                Some((self.start(index)?, count))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use crate::histogram::{parse_bucket, Bucket, Histogram};
+    use datetime_helper::parse_timezone;
+
+    fn at(iso: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(iso).unwrap().into()
+    }
+
+    #[test]
+    fn buckets() {
+        assert_eq!(
+            parse_bucket("1h").ok(),
+            Some(Bucket::Nanos(3_600_000_000_000))
+        );
+        assert_eq!(
+            parse_bucket("day").ok(),
+            Some(Bucket::Nanos(86_400_000_000_000))
+        );
+        assert_eq!(parse_bucket("month").ok(), Some(Bucket::Months(1)));
+        assert_eq!(parse_bucket("P1Y").ok(), Some(Bucket::Months(12)));
+        assert!(parse_bucket("1mo1d").is_err());
+        assert!(parse_bucket("0s").is_err());
+        assert!(parse_bucket("-1h").is_err());
+        assert!(parse_bucket("fortnight").is_err());
+    }
+
+    #[test]
+    fn hourly_buckets_of_a_day() {
+        let mut histogram = Histogram::new(Bucket::Nanos(3_600_000_000_000), None);
+        let events = [
+            "2023-02-16T00:00:00Z",
+            "2023-02-16T00:59:59.999Z",
+            "2023-02-16T03:15:00Z",
+            "2023-02-16T03:45:00Z",
+            "2023-02-16T03:30:00Z",
+            "2023-02-16T12:00:00Z",
+            "2023-02-16T23:59:59Z",
+        ];
+        for event in events {
+            histogram.add(at(event));
+        }
+        let rows = histogram.rows().unwrap();
+        assert_eq!(rows.len(), 24);
+        assert_eq!(rows[0], (at("2023-02-16T00:00:00Z"), 2));
+        assert_eq!(rows[1], (at("2023-02-16T01:00:00Z"), 0));
+        assert_eq!(rows[3], (at("2023-02-16T03:00:00Z"), 3));
+        assert_eq!(rows[12], (at("2023-02-16T12:00:00Z"), 1));
+        assert_eq!(rows[23], (at("2023-02-16T23:00:00Z"), 1));
+        assert_eq!(rows.iter().map(|(_, count)| count).sum::<u64>(), 7);
+    }
+
+    #[test]
+    fn days_start_at_local_midnight() {
+        let timezone = parse_timezone("Europe/Berlin").unwrap();
+        let mut histogram = Histogram::new(parse_bucket("day").unwrap(), Some(timezone));
+        histogram.add(at("2023-02-15T23:30:00Z"));
+        histogram.add(at("2023-02-16T23:30:00Z"));
+        let rows = histogram.rows().unwrap();
+        assert_eq!(
+            rows,
+            [
+                (at("2023-02-15T23:00:00Z"), 1),
+                (at("2023-02-16T23:00:00Z"), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_buckets() {
+        let mut histogram = Histogram::new(Bucket::Months(1), None);
+        histogram.add(at("2022-12-31T23:59:59Z"));
+        histogram.add(at("2023-02-16T12:34:56Z"));
+        assert_eq!(
+            histogram.rows().unwrap(),
+            [
+                (at("2022-12-01T00:00:00Z"), 1),
+                (at("2023-01-01T00:00:00Z"), 0),
+                (at("2023-02-01T00:00:00Z"), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_and_too_many_buckets() {
+        let mut histogram = Histogram::new(Bucket::Nanos(1_000_000_000), None);
+        assert!(histogram.rows().unwrap().is_empty());
+        histogram.add(Utc.timestamp_opt(0, 0).unwrap());
+        histogram.add(Utc.timestamp_opt(100_000, 0).unwrap());
+        assert!(histogram.rows().is_err());
+    }
+}
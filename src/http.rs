@@ -0,0 +1,184 @@
+use std::io::Write;
+use std::time::Duration as Timeout;
+
+use chrono::{DateTime, Duration, Utc};
+use datetime_helper::duration::format_breakdown;
+use datetime_helper::{DateTimeError, Detectors, Rfc2822Detector};
+
+use crate::output::{format_output, OutputOptions};
+
+/// The headers of --url, printed in this order.
+const HEADERS: [&str; 4] = ["Date", "Last-Modified", "Expires", "Retry-After"];
+
+/// The time headers of a response and when it was received.
+pub(crate) struct Response {
+    /// The values of [HEADERS], `None` for headers which were not sent.
+    headers: Vec<(&'static str, Option<String>)>,
+    received: DateTime<Utc>,
+}
+
+/// Sends a HEAD request to `url`. Responses with an error status are
+/// responses, too, as their headers are of interest all the same.
+pub(crate) fn fetch(url: &str) -> std::io::Result<Response> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .timeout_global(Some(Timeout::from_secs(30)))
+        .build()
+        .into();
+    let response = agent
+        .head(url)
+        .call()
+        .map_err(|e| std::io::Error::other(format!("Cannot fetch {}: {}", url, e)))?;
+    let received = Utc::now();
+    let headers = HEADERS
+        .iter()
+        .map(|&name| {
+            let value = response.headers().get(name);
+            let value = value.map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+            (name, value)
+        })
+        .collect();
+    Ok(Response { headers, received })
+}
+
+/// Writes one block per header of `response`, each its name and value
+/// followed by the converted date time, then the receive time and the skew
+/// between it and the `Date` header. Headers which were not sent or are not
+/// HTTP dates are reported to `errors` unless `quiet` is set. A
+/// `Retry-After` in seconds is relative to `Date`, or to the receive time
+/// without `Date`. Returns whether all sent headers could be parsed.
+pub(crate) fn write_response(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    response: &Response,
+    output: &OutputOptions,
+    quiet: bool,
+) -> std::io::Result<bool> {
+    // HTTP dates are RFC 2822 dates in GMT.
+    let detectors = Detectors::builder().push(Rfc2822Detector).build();
+    let mut valid = true;
+    let mut date = None;
+    for (name, value) in &response.headers {
+        let Some(value) = value else {
+            if !quiet {
+                writeln!(errors, "{}: not sent", name)?;
+            }
+            continue;
+        };
+        let parsed = match (*name, value.trim().parse::<i64>()) {
+            ("Retry-After", Ok(seconds)) => after(date.unwrap_or(response.received), seconds),
+            _ => detectors.parse(value).map(|parsed| parsed.datetime),
+        };
+        match parsed {
+            Ok(datetime) => {
+                if *name == "Date" {
+                    date = Some(datetime);
+                }
+                writeln!(out, "{}: {}", name, value)?;
+                out.write_all(format_output(&datetime.into(), output).as_bytes())?;
+            }
+            Err(e) => {
+                valid = false;
+                if !quiet {
+                    writeln!(errors, "{}: \"{}\" is not an HTTP date: {}", name, value, e)?;
+                }
+            }
+        }
+    }
+    writeln!(out, "Received")?;
+    out.write_all(format_output(&response.received.into(), output).as_bytes())?;
+    if let Some(date) = date {
+        writeln!(out, "{}", skew(response.received - date))?;
+    }
+    Ok(valid)
+}
+
+/// The date time `seconds` after `base`.
+fn after(base: DateTime<Utc>, seconds: i64) -> Result<DateTime<Utc>, DateTimeError> {
+    Duration::try_seconds(seconds)
+        .and_then(|duration| base.checked_add_signed(duration))
+        .ok_or_else(|| DateTimeError::OutOfRange {
+            value: format!("{} + {} seconds", base.to_rfc3339(), seconds),
+            unit: None,
+        })
+}
+
+/// Describes the skew of the local clock, which is ahead of the server by
+/// up to a second even if both agree, as `Date` has whole seconds.
+fn skew(skew: Duration) -> String {
+    let direction = match skew < Duration::zero() {
+        true => "behind",
+        false => "ahead of",
+    };
+    format!(
+        "Skew: {}, the local clock is {} Date",
+        format_breakdown(skew.abs()),
+        direction
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::http::{skew, write_response, Response};
+    use crate::output::{OutputField, OutputOptions};
+
+    #[test]
+    fn headers_are_converted_in_blocks() {
+        let response = Response {
+            headers: vec![
+                ("Date", Some("Thu, 16 Feb 2023 12:34:56 GMT".to_string())),
+                (
+                    "Last-Modified",
+                    Some("Wed, 15 Feb 2023 08:00:00 GMT".to_string()),
+                ),
+                ("Expires", None),
+                ("Retry-After", Some("120".to_string())),
+            ],
+            received: Utc.timestamp_millis_opt(1676550897250).unwrap(),
+        };
+        let output = OutputOptions {
+            only: Some(OutputField::Iso),
+            ..Default::default()
+        };
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let valid = write_response(&mut out, &mut errors, &response, &output, false).unwrap();
+        assert!(valid);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Date: Thu, 16 Feb 2023 12:34:56 GMT\n2023-02-16T12:34:56.000Z\n\
+             Last-Modified: Wed, 15 Feb 2023 08:00:00 GMT\n2023-02-15T08:00:00.000Z\n\
+             Retry-After: 120\n2023-02-16T12:36:56.000Z\n\
+             Received\n2023-02-16T12:34:57.250Z\n\
+             Skew: 0d 00:00:01.250, the local clock is ahead of Date\n"
+        );
+        assert_eq!(String::from_utf8(errors).unwrap(), "Expires: not sent\n");
+    }
+
+    #[test]
+    fn unparsable_headers_are_reported() {
+        let response = Response {
+            headers: vec![("Date", None), ("Expires", Some("0".to_string()))],
+            received: Utc.timestamp_millis_opt(1676550897250).unwrap(),
+        };
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let output = OutputOptions::default();
+        let valid = write_response(&mut out, &mut errors, &response, &output, false).unwrap();
+        assert!(!valid);
+        let errors = String::from_utf8(errors).unwrap();
+        assert!(
+            errors.starts_with("Date: not sent\nExpires: \"0\" is not an HTTP date: "),
+            "{errors}"
+        );
+        assert!(!String::from_utf8(out).unwrap().contains("Skew"));
+    }
+
+    #[test]
+    fn skew_names_the_direction() {
+        assert_eq!(
+            skew(Duration::milliseconds(-2500)),
+            "Skew: 0d 00:00:02.500, the local clock is behind Date"
+        );
+    }
+}
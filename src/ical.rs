@@ -0,0 +1,237 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+#[cfg(feature = "tz")]
+use chrono_tz::Tz;
+
+use crate::{DateTimeError, Detector, InputKind, ParsedDateTime};
+#[cfg(feature = "tz")]
+use crate::{DstPolicy, LocalDetector};
+
+/// Property lines of iCalendar files (RFC 5545) with a date time value like
+/// `DTEND:20230216T113456Z`, `DTSTART;TZID=Europe/Berlin:20230216T123456`
+/// or the all-day form `DTSTART;VALUE=DATE:20230216`. Floating values
+/// without `Z` and dates are local times of the `TZID` parameter, otherwise
+/// of the time zone given to [IcalDetector::with_timezone] and otherwise of
+/// UTC. Folded lines are not supported.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IcalDetector {
+    #[cfg(feature = "tz")]
+    timezone: Option<Tz>,
+    #[cfg(feature = "tz")]
+    dst: DstPolicy,
+}
+
+#[cfg(feature = "tz")]
+impl IcalDetector {
+    /// Reads floating values without `TZID` as local times of `timezone`.
+    /// Ambiguous and nonexistent local times, with or without `TZID`, are
+    /// resolved with `dst`.
+    pub fn with_timezone(timezone: Option<Tz>, dst: DstPolicy) -> Self {
+        IcalDetector { timezone, dst }
+    }
+}
+
+/// The parts of a property line.
+struct Property<'a> {
+    tzid: Option<&'a str>,
+    date: bool,
+    value: &'a str,
+}
+
+impl Detector for IcalDetector {
+    fn kind(&self) -> InputKind {
+        InputKind::Ical
+    }
+
+    /// Property names consist of letters, digits and `-`, followed by
+    /// parameters or the value.
+    fn looks_like(&self, input: &str) -> bool {
+        let end = input.find([';', ':']).unwrap_or(0);
+        end > 0
+            && input.as_bytes()[0].is_ascii_alphabetic()
+            && input[..end]
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let invalid = |reason: &str| DateTimeError::InvalidIcal {
+            value: input.to_string(),
+            reason: reason.to_string(),
+        };
+        let property = split(input).ok_or_else(|| invalid("not a property line"))?;
+        let utc = property.value.strip_suffix('Z');
+        let local = match (property.date, utc) {
+            (true, _) => {
+                NaiveDate::parse_from_str(property.value, "%Y%m%d")?.and_time(NaiveTime::MIN)
+            }
+            (false, Some(value)) => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")?,
+            (false, None) => NaiveDateTime::parse_from_str(property.value, "%Y%m%dT%H%M%S")?,
+        };
+        let datetime = match (utc, property.tzid) {
+            (Some(_), Some(_)) => return Err(invalid("UTC times must not have a TZID")),
+            (Some(_), None) => local.and_utc().fixed_offset(),
+            (None, tzid) => self.resolve(local, tzid)?,
+        };
+        Ok(ParsedDateTime::new(input, self.kind(), datetime))
+    }
+}
+
+impl IcalDetector {
+    /// The instant of the floating `local` time in the time zone `tzid`.
+    #[cfg(feature = "tz")]
+    fn resolve(
+        &self,
+        local: NaiveDateTime,
+        tzid: Option<&str>,
+    ) -> Result<DateTime<FixedOffset>, DateTimeError> {
+        let timezone = match tzid {
+            Some(tzid) => Some(crate::parse_timezone(tzid)?),
+            None => self.timezone,
+        };
+        match timezone {
+            Some(timezone) => LocalDetector::new(timezone, self.dst).resolve(local),
+            None => Ok(local.and_utc().fixed_offset()),
+        }
+    }
+
+    /// Without time zones, only floating times without `TZID` are supported.
+    #[cfg(not(feature = "tz"))]
+    fn resolve(
+        &self,
+        local: NaiveDateTime,
+        tzid: Option<&str>,
+    ) -> Result<DateTime<FixedOffset>, DateTimeError> {
+        match tzid {
+            Some(tzid) => Err(DateTimeError::UnknownTimeZone(tzid.to_string())),
+            None => Ok(local.and_utc().fixed_offset()),
+        }
+    }
+}
+
+/// Splits a property line into its `TZID` and `VALUE` parameters and its
+/// value. Parameter values may be quoted and names are case-insensitive.
+fn split(input: &str) -> Option<Property<'_>> {
+    let mut rest = &input[input.find([';', ':'])?..];
+    let mut property = Property {
+        tzid: None,
+        date: false,
+        value: "",
+    };
+    while let Some(parameter) = rest.strip_prefix(';') {
+        let (name, value) = parameter.split_once('=')?;
+        let (value, after) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let (value, after) = quoted.split_once('"')?;
+                (value, after)
+            }
+            None => value.split_at(value.find([';', ':'])?),
+        };
+        if name.eq_ignore_ascii_case("TZID") {
+            property.tzid = Some(value);
+        } else if name.eq_ignore_ascii_case("VALUE") {
+            property.date = value.eq_ignore_ascii_case("DATE");
+        }
+        rest = after;
+    }
+    property.value = rest.strip_prefix(':')?;
+    Some(property)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DateTimeError, Detector, IcalDetector, InputKind};
+
+    fn parse(detector: IcalDetector, input: &str) -> Result<String, DateTimeError> {
+        let parsed = detector.parse(input)?;
+        assert_eq!(parsed.format, InputKind::Ical);
+        let offset = parsed.offset.unwrap();
+        Ok(parsed.datetime.with_timezone(&offset).to_rfc3339())
+    }
+
+    #[test]
+    fn utc_floating_and_date_values() {
+        let detector = IcalDetector::default();
+        for (input, expected) in [
+            ("DTEND:20230216T113456Z", "2023-02-16T11:34:56+00:00"),
+            ("dtstamp:20230216T113456Z", "2023-02-16T11:34:56+00:00"),
+            ("DTSTART:20230216T123456", "2023-02-16T12:34:56+00:00"),
+            ("DTSTART;VALUE=DATE:20230216", "2023-02-16T00:00:00+00:00"),
+            (
+                "X-ALARM;FOO=\"a;b:c\";value=date:20230216",
+                "2023-02-16T00:00:00+00:00",
+            ),
+        ] {
+            assert!(detector.looks_like(input), "{input}");
+            assert_eq!(parse(detector, input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn other_lines_are_rejected() {
+        let detector = IcalDetector::default();
+        for input in [
+            "Thu, 16 Feb 2023 12:34:56 +0000",
+            "2023-02-16T12:34:56Z",
+            ":x",
+        ] {
+            assert!(!detector.looks_like(input), "{input}");
+        }
+        for input in [
+            "DTSTART",
+            "DTSTART;TZID:20230216T123456",
+            "DTSTART;TZID=\"Europe/Berlin:20230216T123456",
+            "DTSTART:20230216",
+            "DTSTART;VALUE=DATE:20230216T123456",
+            "DTSTART:20230230T123456Z",
+        ] {
+            assert!(detector.parse(input).is_err(), "{input}");
+        }
+    }
+
+    #[cfg(feature = "tz")]
+    #[test]
+    fn tzid_parameters_are_local_times() {
+        use chrono_tz::Tz;
+
+        use crate::DstPolicy;
+
+        let detector = IcalDetector::default();
+        for (input, expected) in [
+            (
+                "DTSTART;TZID=Europe/Berlin:20230216T123456",
+                "2023-02-16T12:34:56+01:00",
+            ),
+            (
+                "DTSTART;TZID=Europe/Berlin:20230716T123456",
+                "2023-07-16T12:34:56+02:00",
+            ),
+            (
+                "DTSTART;VALUE=DATE;TZID=\"America/New_York\":20230716",
+                "2023-07-16T00:00:00-04:00",
+            ),
+        ] {
+            assert_eq!(parse(detector, input).unwrap(), expected, "{input}");
+        }
+        let error = parse(detector, "DTSTART;TZID=Europe/Berlin:20230326T023000").unwrap_err();
+        assert!(
+            matches!(error, DateTimeError::NonexistentLocalTime { .. }),
+            "{error}"
+        );
+        let shifted = IcalDetector::with_timezone(None, DstPolicy::Shift);
+        assert_eq!(
+            parse(shifted, "DTSTART;TZID=Europe/Berlin:20230326T023000").unwrap(),
+            "2023-03-26T03:30:00+02:00"
+        );
+        assert!(parse(detector, "DTEND;TZID=Europe/Berlin:20230216T113456Z").is_err());
+        assert!(parse(detector, "DTEND;TZID=Europe/Springfield:20230216T113456").is_err());
+        let assumed = IcalDetector::with_timezone(Some(Tz::Europe__Berlin), DstPolicy::Reject);
+        assert_eq!(
+            parse(assumed, "DTSTART:20230216T123456").unwrap(),
+            "2023-02-16T12:34:56+01:00"
+        );
+        assert_eq!(
+            parse(assumed, "DTEND:20230216T113456Z").unwrap(),
+            "2023-02-16T11:34:56+00:00"
+        );
+    }
+}
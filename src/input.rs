@@ -0,0 +1,239 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Stderr, StdinLock};
+
+use datetime_helper::DateTimeError;
+
+use crate::progress::Progress;
+
+/// Where the inputs are read from.
+pub(crate) enum Source {
+    /// The date time given as argument.
+    Argument(Cursor<String>),
+    /// Standard input.
+    Stdin(StdinLock<'static>),
+    /// The file given by --file.
+    File(BufReader<File>),
+    /// Another source whose progress is shown on standard error.
+    Progress(Box<Progress<Source, Stderr>>),
+}
+
+impl Source {
+    fn reader(&mut self) -> &mut dyn BufRead {
+        match self {
+            Source::Argument(argument) => argument,
+            Source::Stdin(stdin) => stdin,
+            Source::File(file) => file,
+            Source::Progress(progress) => progress.as_mut(),
+        }
+    }
+}
+
+impl Read for Source {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader().read(buf)
+    }
+}
+
+impl BufRead for Source {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.reader().fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader().consume(amount)
+    }
+}
+
+/// The byte order mark which files saved by Windows tools start with.
+const BOM: &[u8] = "\u{FEFF}".as_bytes();
+
+/// Reads the lines selected by --skip and --limit into a buffer which is
+/// reused for all lines. Lines after the limit are not read. A byte order
+/// mark at the start of the input is removed.
+pub(crate) struct Lines<R> {
+    reader: R,
+    line: Vec<u8>,
+    number: usize,
+    skip: usize,
+    remaining: usize,
+    skip_blank: bool,
+    blank: u64,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub(crate) fn new(reader: R, skip: usize, limit: Option<usize>) -> Self {
+        Lines {
+            reader,
+            line: Vec::new(),
+            number: 0,
+            skip,
+            remaining: limit.unwrap_or(usize::MAX),
+            skip_blank: false,
+            blank: 0,
+        }
+    }
+
+    /// Makes [Lines::next_line] skip lines which are empty or only contain
+    /// whitespace. They still count for --skip and --limit.
+    pub(crate) fn skip_blank(mut self) -> Self {
+        self.skip_blank = true;
+        self
+    }
+
+    /// The number of lines skipped as blank.
+    pub(crate) fn blank(&self) -> u64 {
+        self.blank
+    }
+
+    /// The next line with its number starting at 1, including the line
+    /// break if there is one.
+    pub(crate) fn next_bytes(&mut self) -> std::io::Result<Option<(usize, &[u8])>> {
+        while self.remaining > 0 {
+            self.line.clear();
+            if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.number += 1;
+            if self.number == 1 && self.line.starts_with(BOM) {
+                self.line.drain(..BOM.len());
+            }
+            if self.number > self.skip {
+                self.remaining -= 1;
+                return Ok(Some((self.number, &self.line)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The next line with its number starting at 1, without the line break,
+    /// which may be `\n` or `\r\n`. Lines which are not valid UTF-8 are
+    /// returned as [DateTimeError::InvalidUtf8] to fail like unparsable
+    /// lines.
+    pub(crate) fn next_line(
+        &mut self,
+    ) -> std::io::Result<Option<(usize, Result<&str, DateTimeError>)>> {
+        let number = loop {
+            let Some((number, _)) = self.next_bytes()? else {
+                return Ok(None);
+            };
+            if !self.skip_blank || !self.line.iter().all(u8::is_ascii_whitespace) {
+                break number;
+            }
+            self.blank += 1;
+        };
+        let line = &self.line[..];
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = std::str::from_utf8(line).map_err(|_| DateTimeError::InvalidUtf8);
+        Ok(Some((number, line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor, Read};
+
+    use datetime_helper::DateTimeError;
+
+    use crate::input::Lines;
+
+    fn read_all<R: std::io::BufRead>(lines: &mut Lines<R>) -> Vec<(usize, String)> {
+        let mut read = Vec::new();
+        while let Some((number, line)) = lines.next_line().unwrap() {
+            read.push((number, line.unwrap().to_string()));
+        }
+        read
+    }
+
+    /// Fails after the given bytes, like a file on a broken disk.
+    struct Failing(Cursor<&'static str>);
+
+    impl Read for Failing {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(std::io::Error::other("device failed")),
+                length => Ok(length),
+            }
+        }
+    }
+
+    #[test]
+    fn lines_without_line_breaks() {
+        let mut lines = Lines::new(Cursor::new("a\nb\r\n\nc"), 0, None);
+        let expected = [(1, "a"), (2, "b"), (3, ""), (4, "c")];
+        assert_eq!(
+            read_all(&mut lines),
+            expected.map(|(n, l)| (n, l.to_string()))
+        );
+        assert!(lines.next_line().unwrap().is_none());
+    }
+
+    #[test]
+    fn skip_and_limit_keep_line_numbers() {
+        let mut lines = Lines::new(Cursor::new("a\nb\nc\nd\n"), 1, Some(2));
+        let expected = [(2, "b"), (3, "c")];
+        assert_eq!(
+            read_all(&mut lines),
+            expected.map(|(n, l)| (n, l.to_string()))
+        );
+    }
+
+    #[test]
+    fn blank_lines_are_counted_if_skipped() {
+        let input = "a\n\n \t\r\nb\n";
+        let mut lines = Lines::new(Cursor::new(input), 0, None);
+        assert_eq!(read_all(&mut lines).len(), 4);
+        let mut lines = Lines::new(Cursor::new(input), 0, Some(3)).skip_blank();
+        let expected = [(1, "a")];
+        assert_eq!(
+            read_all(&mut lines),
+            expected.map(|(n, l)| (n, l.to_string()))
+        );
+        assert_eq!(lines.blank(), 2);
+    }
+
+    #[test]
+    fn byte_order_mark_is_removed_from_the_first_line() {
+        let input = "\u{FEFF}1676550896\r\n\u{FEFF}2023-02-16T12:34:56Z\r\n";
+        let mut lines = Lines::new(Cursor::new(input), 0, None);
+        let expected = [(1, "1676550896"), (2, "\u{FEFF}2023-02-16T12:34:56Z")];
+        assert_eq!(
+            read_all(&mut lines),
+            expected.map(|(n, l)| (n, l.to_string()))
+        );
+        let mut lines = Lines::new(Cursor::new("\u{FEFF}a\r\nb"), 0, None);
+        assert_eq!(lines.next_bytes().unwrap(), Some((1, &b"a\r\n"[..])));
+        let mut lines = Lines::new(Cursor::new("\u{FEFF}a\nb"), 1, None);
+        assert_eq!(lines.next_bytes().unwrap(), Some((2, &b"b"[..])));
+    }
+
+    #[test]
+    fn bytes_keep_line_breaks() {
+        let mut lines = Lines::new(Cursor::new("a\r\nb"), 0, None);
+        assert_eq!(lines.next_bytes().unwrap(), Some((1, &b"a\r\n"[..])));
+        assert_eq!(lines.next_bytes().unwrap(), Some((2, &b"b"[..])));
+        assert_eq!(lines.next_bytes().unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_utf8_fails_the_line() {
+        let mut lines = Lines::new(Cursor::new(&b"a\n\xff\nb\n"[..]), 0, None);
+        assert!(matches!(lines.next_line().unwrap(), Some((1, Ok("a")))));
+        assert!(matches!(
+            lines.next_line().unwrap(),
+            Some((2, Err(DateTimeError::InvalidUtf8)))
+        ));
+        assert!(matches!(lines.next_line().unwrap(), Some((3, Ok("b")))));
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        let reader = BufReader::with_capacity(4, Failing(Cursor::new("1676550896\n16765")));
+        let mut lines = Lines::new(reader, 0, None);
+        assert!(matches!(
+            lines.next_line().unwrap(),
+            Some((1, Ok("1676550896")))
+        ));
+        assert_eq!(lines.next_line().unwrap_err().to_string(), "device failed");
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::{BufRead, Write};
+
+use crate::output::{write_time, OutputOptions};
+use datetime_helper::{Detectors, InputKind};
+
+const BANNER: &str =
+    "Interactive mode. Enter a date time, 'help' for accepted formats or 'quit' to exit.";
+const PROMPT: &str = "dt> ";
+const STDIN_HINT: &str = "reading from stdin; type a timestamp and press Enter, Ctrl-D to finish";
+
+/// The interactive mode is used if it is requested or if there is no
+/// argument and standard input is a terminal.
+pub(crate) fn is_interactive(requested: bool, has_argument: bool, stdin_is_terminal: bool) -> bool {
+    requested || (!has_argument && stdin_is_terminal)
+}
+
+/// The hint to print before reading standard input without prompts, which
+/// is only needed if there is no argument and standard input is a terminal.
+pub(crate) fn stdin_hint(has_argument: bool, stdin_is_terminal: bool) -> Option<&'static str> {
+    (!has_argument && stdin_is_terminal).then_some(STDIN_HINT)
+}
+
+/// Reads date times from `input` until EOF or `quit` and writes them to
+/// `out`, which is flushed after each result. Banner, prompt and messages
+/// are written to `messages`. Returns whether parsing at least one input
+/// failed.
+pub(crate) fn run(
+    input: impl BufRead,
+    out: &mut impl Write,
+    messages: &mut impl Write,
+    detectors: &Detectors,
+    output: &OutputOptions,
+    quiet: bool,
+) -> std::io::Result<bool> {
+    let mut failed = false;
+    writeln!(messages, "{}", BANNER)?;
+    let mut lines = input.lines();
+    loop {
+        write!(messages, "{}", PROMPT)?;
+        messages.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" => break,
+            "help" => write_help(messages)?,
+            input => match detectors.parse(input) {
+                Ok(parsed) => {
+                    write_time(out, &parsed, output)?;
+                    out.flush()?;
+                }
+                Err(e) => {
+                    failed = true;
+                    if !quiet {
+                        writeln!(messages, "{}", e)?;
+                    }
+                }
+            },
+        }
+    }
+    writeln!(messages)?;
+    Ok(failed)
+}
+
+fn write_help(out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "Accepted formats:")?;
+    for kind in InputKind::DEFAULT {
+        writeln!(out, "  {:14} {}", kind, kind.example().unwrap_or_default())?;
+    }
+    writeln!(out, "Commands: help, quit")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interactive::{is_interactive, run, stdin_hint};
+    use crate::output::{OutputField, OutputOptions};
+    use datetime_helper::Detectors;
+
+    #[test]
+    fn interactive_if_requested() {
+        assert!(is_interactive(true, false, false));
+        assert!(is_interactive(true, true, false));
+    }
+
+    #[test]
+    fn interactive_on_terminal_without_argument() {
+        assert!(is_interactive(false, false, true));
+    }
+
+    #[test]
+    fn not_interactive_on_pipe() {
+        assert!(!is_interactive(false, false, false));
+    }
+
+    #[test]
+    fn not_interactive_with_argument() {
+        assert!(!is_interactive(false, true, true));
+    }
+
+    #[test]
+    fn hint_on_terminal_without_argument() {
+        assert!(stdin_hint(false, true).unwrap().contains("Ctrl-D"));
+    }
+
+    #[test]
+    fn no_hint_on_pipe_or_with_argument() {
+        assert_eq!(stdin_hint(false, false), None);
+        assert_eq!(stdin_hint(true, true), None);
+    }
+
+    #[test]
+    fn stops_at_quit() {
+        let mut messages = Vec::new();
+        let input = "help\nquit\nfoo\n".as_bytes();
+        let failed = run(
+            input,
+            &mut Vec::new(),
+            &mut messages,
+            &Detectors::default(),
+            &OutputOptions::default(),
+            false,
+        )
+        .unwrap();
+        let messages = String::from_utf8(messages).unwrap();
+        assert!(!failed);
+        assert!(messages.starts_with("Interactive mode."));
+        assert!(messages.contains("epoch-millis"));
+        assert_eq!(messages.matches("dt> ").count(), 2);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut messages = Vec::new();
+        let input = "foo\n".as_bytes();
+        let failed = run(
+            input,
+            &mut Vec::new(),
+            &mut messages,
+            &Detectors::default(),
+            &OutputOptions::default(),
+            false,
+        )
+        .unwrap();
+        let messages = String::from_utf8(messages).unwrap();
+        assert!(failed);
+        assert!(messages.contains("dt> Cannot parse \"foo\" as rfc3339:"));
+    }
+
+    #[test]
+    fn writes_results_to_output() {
+        let mut out = Vec::new();
+        let mut messages = Vec::new();
+        let output = OutputOptions {
+            only: Some(OutputField::Millis),
+            ..Default::default()
+        };
+        let input = "1676550896\nfoo\n2023-02-16T12:34:56.789Z\n".as_bytes();
+        let failed = run(
+            input,
+            &mut out,
+            &mut messages,
+            &Detectors::default(),
+            &output,
+            false,
+        )
+        .unwrap();
+        assert!(failed);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1676550896000\n1676550896789\n"
+        );
+        let messages = String::from_utf8(messages).unwrap();
+        assert!(!messages.contains("1676550896000"));
+        assert_eq!(messages.matches("dt> ").count(), 4);
+    }
+}
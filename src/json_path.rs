@@ -0,0 +1,233 @@
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::input::Lines;
+use crate::stats::Stats;
+use crate::{get_datetime, DateTimeError, Detectors};
+
+/// The path of --json-key like `meta.created_at` or `events[0].time`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JsonPath(Vec<Step>);
+
+/// One step of a [JsonPath].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    /// The member of an object with this key.
+    Key(String),
+    /// The element of an array at this index, starting at 0.
+    Index(usize),
+}
+
+/// Parses a path of keys separated by `.`, each followed by any number of
+/// array indices like `[0]`. The path may start with an index.
+pub(crate) fn parse_json_path(input: &str) -> Result<JsonPath, DateTimeError> {
+    let invalid = || DateTimeError::InvalidJsonPath(input.to_string());
+    let mut steps = Vec::new();
+    for (position, segment) in input.split('.').enumerate() {
+        let (key, mut indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        match key {
+            "" if position > 0 || indices.is_empty() => return Err(invalid()),
+            "" => {}
+            key => steps.push(Step::Key(key.to_string())),
+        }
+        while !indices.is_empty() {
+            let (index, rest) = indices
+                .strip_prefix('[')
+                .and_then(|indices| indices.split_once(']'))
+                .ok_or_else(invalid)?;
+            steps.push(Step::Index(index.parse().map_err(|_| invalid())?));
+            indices = rest;
+        }
+    }
+    Ok(JsonPath(steps))
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (position, step) in self.0.iter().enumerate() {
+            match step {
+                Step::Key(key) if position > 0 => write!(f, ".{}", key)?,
+                Step::Key(key) => write!(f, "{}", key)?,
+                Step::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl JsonPath {
+    /// The value at this path within `value`, if there is one.
+    fn find<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value> {
+        self.0.iter().try_fold(value, |value, step| match step {
+            Step::Key(key) => value.get_mut(key),
+            Step::Index(index) => value.get_mut(index),
+        })
+    }
+}
+
+/// Copies each JSON line of `lines` to `out` with the date time at `path`
+/// replaced by the result of `render`. Changed lines are printed
+/// compactly with their keys in the original order. Lines which are not
+/// JSON, do not have the path or whose value cannot be parsed are copied
+/// unchanged, reported to `errors` unless `quiet` is set, and counted as
+/// failures. Blank lines are copied and counted as blank. Returns the
+/// statistics of the parsed values.
+pub(crate) fn rewrite_lines(
+    lines: &mut Lines<impl BufRead>,
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    path: &JsonPath,
+    detectors: &Detectors,
+    render: impl Fn(DateTime<Utc>) -> Value,
+    quiet: bool,
+) -> std::io::Result<Stats> {
+    let mut stats = Stats::default();
+    while let Some((number, line)) = lines.next_bytes()? {
+        let content = line.strip_suffix(b"\n").unwrap_or(line);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        if content.iter().all(u8::is_ascii_whitespace) {
+            stats.blank += 1;
+            out.write_all(line)?;
+            continue;
+        }
+        match rewrite(content, path, detectors, &render) {
+            Ok((datetime, rewritten)) => {
+                stats.add(datetime);
+                out.write_all(rewritten.as_bytes())?;
+                out.write_all(&line[content.len()..])?;
+            }
+            Err(e) => {
+                stats.add_failure(number);
+                if !quiet {
+                    writeln!(errors, "line {}: {}", number, e)?;
+                }
+                out.write_all(line)?;
+            }
+        }
+    }
+    Ok(stats)
+}
+
+/// Replaces the date time at `path` within the JSON `line`, returning the
+/// date time and the changed line.
+fn rewrite(
+    line: &[u8],
+    path: &JsonPath,
+    detectors: &Detectors,
+    render: impl Fn(DateTime<Utc>) -> Value,
+) -> Result<(DateTime<Utc>, String), String> {
+    let mut json: Value =
+        serde_json::from_slice(line).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let value = path
+        .find(&mut json)
+        .ok_or_else(|| format!("No value at {}", path))?;
+    let input = match value {
+        Value::String(input) => input.clone(),
+        Value::Number(number) => number.to_string(),
+        _ => {
+            return Err(format!(
+                "The value at {} is neither a string nor a number",
+                path
+            ))
+        }
+    };
+    let datetime = get_datetime(&input, detectors).map_err(|e| e.to_string())?;
+    *value = render(datetime);
+    Ok((datetime, json.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use chrono::{DateTime, Utc};
+    use datetime_helper::Detectors;
+
+    use crate::input::Lines;
+    use crate::json_path::{parse_json_path, rewrite_lines};
+
+    fn rewrite(input: &str, path: &str, numeric: bool) -> (String, String, u64) {
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let render = |datetime: DateTime<Utc>| match numeric {
+            true => datetime.timestamp().into(),
+            false => datetime.to_rfc3339().into(),
+        };
+        let stats = rewrite_lines(
+            &mut Lines::new(Cursor::new(input), 0, None),
+            &mut out,
+            &mut errors,
+            &parse_json_path(path).unwrap(),
+            &Detectors::default(),
+            render,
+            false,
+        )
+        .unwrap();
+        let string = |bytes| String::from_utf8(bytes).unwrap();
+        (string(out), string(errors), stats.failed)
+    }
+
+    #[test]
+    fn paths_of_keys_and_indices() {
+        for path in [
+            "time",
+            "meta.created_at",
+            "events[0].time",
+            "[1][2].a",
+            "a.b[10]",
+        ] {
+            assert_eq!(parse_json_path(path).unwrap().to_string(), path);
+        }
+        for path in [
+            "", ".a", "a.", "a..b", "a[", "a[x]", "a[0]b", "a.[0]", "a[-1]",
+        ] {
+            assert!(parse_json_path(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn nested_values_are_replaced_in_place() {
+        let input = "{\"z\":1,\"meta\":{\"created_at\":1676550896789,\"by\":\"me\"},\"a\":[]}\n";
+        let (out, errors, failed) = rewrite(input, "meta.created_at", false);
+        assert_eq!(
+            out,
+            "{\"z\":1,\"meta\":{\"created_at\":\"2023-02-16T12:34:56.789+00:00\",\"by\":\"me\"},\"a\":[]}\n"
+        );
+        assert_eq!((errors.as_str(), failed), ("", 0));
+    }
+
+    #[test]
+    fn values_within_arrays() {
+        let input = "{\"events\": [{\"time\": \"2023-02-16T13:34:56+01:00\"}]}\r\n[1, 1676550896]";
+        let (out, _, failed) = rewrite(input, "events[0].time", true);
+        assert_eq!(
+            out,
+            "{\"events\":[{\"time\":1676550896}]}\r\n[1, 1676550896]"
+        );
+        assert_eq!(failed, 1);
+        let (out, _, failed) = rewrite("[1, \"1676550896\"]\n", "[1]", true);
+        assert_eq!((out.as_str(), failed), ("[1,1676550896]\n", 0));
+    }
+
+    #[test]
+    fn other_lines_are_copied_with_a_warning() {
+        let input = "not json\n{\"time\":true}\n{\"other\":1}\n\n{\"time\":\"soon\"}\n";
+        let (out, errors, failed) = rewrite(input, "time", false);
+        assert_eq!(out, input);
+        assert_eq!(failed, 4);
+        let errors: Vec<&str> = errors.lines().collect();
+        assert!(
+            errors[0].starts_with("line 1: Invalid JSON: "),
+            "{}",
+            errors[0]
+        );
+        assert_eq!(
+            errors[1],
+            "line 2: The value at time is neither a string nor a number"
+        );
+        assert_eq!(errors[2], "line 3: No value at time");
+        assert!(errors[3].starts_with("line 5: Cannot parse \"soon\""));
+    }
+}
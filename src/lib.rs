@@ -0,0 +1,917 @@
+//! Parsing of date times given in various formats like RFC 3339 timestamps
+//! and epoch times, which is the core of the `datetime` tool.
+//!
+//! [parse] tries all formats and guesses the unit of epoch times, while
+//! [parse_with] only tries the given [InputKind]s and [ParseOptions] also
+//! allows fixing the unit.
+
+use std::num::ParseIntError;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, ParseError, SecondsFormat, Utc};
+#[cfg(feature = "cli")]
+use clap::builder::PossibleValue;
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+#[cfg(feature = "rayon")]
+use rayon::iter::IntoParallelIterator;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+pub use crate::detect::{
+    Detector, Detectors, DetectorsBuilder, EpochDetector, FloatSecondsDetector, Rfc2822Detector,
+    Rfc3339Detector, Trace,
+};
+pub use crate::failure::ParseFailure;
+pub use crate::ical::IcalDetector;
+#[cfg(feature = "tz")]
+pub use crate::local::{DstPolicy, LocalDetector};
+pub use crate::parsed::{ParsedDateTime, Precision};
+pub use crate::protobuf::ProtobufDetector;
+#[cfg(feature = "wasm")]
+pub use crate::wasm::parse_to_json;
+#[cfg(feature = "tz")]
+pub use chrono_tz::Tz;
+
+mod calendar;
+mod detect;
+/// Durations, both exact ones and calendar durations counting months.
+pub mod duration;
+mod failure;
+mod ical;
+#[cfg(feature = "tz")]
+mod local;
+mod names;
+/// Rendering of parsed date times, durations and statistics as printed by
+/// the `datetime` tool.
+#[cfg(feature = "cli")]
+pub mod output;
+mod parsed;
+mod protobuf;
+/// Statistics over the inputs of a run.
+pub mod stats;
+#[cfg(feature = "wasm")]
+mod wasm;
+/// Counting and adding workdays, which skip weekends and holidays.
+pub mod workdays;
+/// Offsets and offset transitions of time zones.
+#[cfg(feature = "tz")]
+pub mod zone;
+
+/// The year from which on numeric values are handled as epoch milliseconds.
+pub const DEFAULT_EPOCH_CUTOFF_YEAR: i32 = 3000;
+
+/// Options controlling how inputs are parsed.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    /// The kinds an input may have. [InputKind::DEFAULT] is tried if empty.
+    pub kinds: Vec<InputKind>,
+    /// The unit of numeric inputs. It is guessed if not given.
+    pub unit: Option<EpochUnit>,
+    /// Guessed epoch seconds must result in a year before this year.
+    pub epoch_cutoff_year: i32,
+    /// Whether seconds with a fraction like `1676550896.789` are detected as
+    /// [InputKind::FloatSeconds] after the kinds of [ParseOptions::kinds].
+    pub float_seconds: bool,
+    /// The time zone of date times without offset. If given, they are
+    /// detected as [InputKind::Local] after the kinds of
+    /// [ParseOptions::kinds]. Floating iCalendar values are in this time
+    /// zone, too.
+    #[cfg(feature = "tz")]
+    pub assume_tz: Option<Tz>,
+    /// How ambiguous and nonexistent local times are resolved.
+    #[cfg(feature = "tz")]
+    pub dst: DstPolicy,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            kinds: Vec::new(),
+            unit: None,
+            epoch_cutoff_year: DEFAULT_EPOCH_CUTOFF_YEAR,
+            float_seconds: false,
+            #[cfg(feature = "tz")]
+            assume_tz: None,
+            #[cfg(feature = "tz")]
+            dst: DstPolicy::default(),
+        }
+    }
+}
+
+/// The kind of an input, i.e. the format of a date time or the unit of an
+/// epoch time. [Display](std::fmt::Display) prints the names used by the
+/// `--strict` option of the `datetime` tool like `epoch-seconds`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InputKind {
+    /// RFC 3339 like 2023-02-16T12:34:56.789Z
+    Rfc3339,
+    /// RFC 2822 like Thu, 16 Feb 2023 12:34:56 +0000
+    Rfc2822,
+    /// Seconds since 1970-01-01T00:00:00Z, only accepted before the epoch
+    /// cutoff year when guessing
+    EpochSeconds,
+    /// Milliseconds since 1970-01-01T00:00:00Z
+    EpochMillis,
+    /// Microseconds since 1970-01-01T00:00:00Z
+    EpochMicros,
+    /// Nanoseconds since 1970-01-01T00:00:00Z
+    EpochNanos,
+    /// Seconds since 1970-01-01T00:00:00Z with a fraction like
+    /// 1676550896.789, as used by the HTTP API of Prometheus
+    FloatSeconds,
+    /// The value of an iCalendar property line like
+    /// `DTSTART;TZID=Europe/Berlin:20230216T133456`
+    Ical,
+    /// The fields of a protobuf timestamp like
+    /// `seconds: 1676550896 nanos: 789000000`
+    Protobuf,
+    /// Date time without offset like 2023-02-16 13:34:56, which is a local
+    /// time of the time zone of `ParseOptions::assume_tz`
+    Local,
+    /// A format with the given name which is not built in
+    Custom(&'static str),
+}
+
+impl InputKind {
+    /// The kinds tried in this order when no kinds are given.
+    pub const DEFAULT: [InputKind; 6] = [
+        InputKind::Rfc3339,
+        InputKind::Rfc2822,
+        InputKind::EpochSeconds,
+        InputKind::EpochMillis,
+        InputKind::Ical,
+        InputKind::Protobuf,
+    ];
+
+    /// All built-in kinds.
+    pub const BUILT_IN: [InputKind; 10] = [
+        InputKind::Rfc3339,
+        InputKind::Rfc2822,
+        InputKind::EpochSeconds,
+        InputKind::EpochMillis,
+        InputKind::EpochMicros,
+        InputKind::EpochNanos,
+        InputKind::FloatSeconds,
+        InputKind::Ical,
+        InputKind::Protobuf,
+        InputKind::Local,
+    ];
+
+    /// The name of the kind as used by `--strict`.
+    pub fn name(self) -> &'static str {
+        match self {
+            InputKind::Rfc3339 => "rfc3339",
+            InputKind::Rfc2822 => "rfc2822",
+            InputKind::EpochSeconds => "epoch-seconds",
+            InputKind::EpochMillis => "epoch-millis",
+            InputKind::EpochMicros => "epoch-micros",
+            InputKind::EpochNanos => "epoch-nanos",
+            InputKind::FloatSeconds => "float-seconds",
+            InputKind::Ical => "ical",
+            InputKind::Protobuf => "protobuf",
+            InputKind::Local => "local",
+            InputKind::Custom(name) => name,
+        }
+    }
+
+    /// An example input of a built-in kind.
+    pub fn example(self) -> Option<&'static str> {
+        match self {
+            InputKind::Rfc3339 => Some("2023-02-16T12:34:56.789Z"),
+            InputKind::Rfc2822 => Some("Thu, 16 Feb 2023 12:34:56 +0000"),
+            InputKind::EpochSeconds => Some("1676550896"),
+            InputKind::EpochMillis => Some("1676550896789"),
+            InputKind::EpochMicros => Some("1676550896789123"),
+            InputKind::EpochNanos => Some("1676550896789123456"),
+            InputKind::FloatSeconds => Some("1676550896.789"),
+            InputKind::Ical => Some("DTSTART;TZID=Europe/Berlin:20230216T133456"),
+            InputKind::Protobuf => Some("seconds: 1676550896 nanos: 789000000"),
+            InputKind::Local => Some("2023-02-16 13:34:56"),
+            InputKind::Custom(_) => None,
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    fn description(self) -> Option<&'static str> {
+        match self {
+            InputKind::Rfc3339 => Some("RFC 3339 like 2023-02-16T12:34:56.789Z"),
+            InputKind::Rfc2822 => Some("RFC 2822 like Thu, 16 Feb 2023 12:34:56 +0000"),
+            InputKind::EpochSeconds => Some("Seconds since 1970-01-01T00:00:00Z"),
+            InputKind::EpochMillis => Some("Milliseconds since 1970-01-01T00:00:00Z"),
+            InputKind::EpochMicros => Some("Microseconds since 1970-01-01T00:00:00Z"),
+            InputKind::EpochNanos => Some("Nanoseconds since 1970-01-01T00:00:00Z"),
+            InputKind::FloatSeconds => Some("Seconds with a fraction like 1676550896.789"),
+            InputKind::Ical => Some("iCalendar property like DTEND:20230216T123456Z"),
+            InputKind::Protobuf => {
+                Some("Protobuf timestamp fields like 'seconds: 1676550896 nanos: 789000000'")
+            }
+            InputKind::Local => Some("Date time without offset like 2023-02-16 13:34:56"),
+            InputKind::Custom(_) => None,
+        }
+    }
+
+    /// The unit of epoch times of this kind, `None` for other kinds.
+    pub fn unit(self) -> Option<EpochUnit> {
+        match self {
+            InputKind::EpochSeconds | InputKind::FloatSeconds => Some(EpochUnit::S),
+            InputKind::EpochMillis => Some(EpochUnit::Ms),
+            InputKind::EpochMicros => Some(EpochUnit::Us),
+            InputKind::EpochNanos => Some(EpochUnit::Ns),
+            InputKind::Rfc3339
+            | InputKind::Rfc2822
+            | InputKind::Ical
+            | InputKind::Protobuf
+            | InputKind::Local
+            | InputKind::Custom(_) => None,
+        }
+    }
+
+    /// The kind of epoch times in `unit`.
+    pub(crate) fn of_unit(unit: EpochUnit) -> Self {
+        match unit {
+            EpochUnit::S => InputKind::EpochSeconds,
+            EpochUnit::Ms => InputKind::EpochMillis,
+            EpochUnit::Us => InputKind::EpochMicros,
+            EpochUnit::Ns => InputKind::EpochNanos,
+        }
+    }
+}
+
+impl std::fmt::Display for InputKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The built-in kinds are the values of `--strict`.
+#[cfg(feature = "cli")]
+impl ValueEnum for InputKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &InputKind::BUILT_IN
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()).help(self.description()?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for InputKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// The unit of an epoch time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "lowercase"))]
+pub enum EpochUnit {
+    /// Seconds
+    S,
+    /// Milliseconds
+    Ms,
+    /// Microseconds
+    Us,
+    /// Nanoseconds
+    Ns,
+}
+
+impl EpochUnit {
+    /// The symbol like `ms`.
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            EpochUnit::S => "s",
+            EpochUnit::Ms => "ms",
+            EpochUnit::Us => "us",
+            EpochUnit::Ns => "ns",
+        }
+    }
+
+    pub(crate) fn per_second(self) -> i64 {
+        match self {
+            EpochUnit::S => 1,
+            EpochUnit::Ms => 1_000,
+            EpochUnit::Us => 1_000_000,
+            EpochUnit::Ns => 1_000_000_000,
+        }
+    }
+}
+
+/// The errors of parsing inputs and of the other operations of the
+/// `datetime` tool.
+#[derive(Error, Debug)]
+pub enum DateTimeError {
+    /// A numeric input is not an integer.
+    #[error("{0}")]
+    NumberFormatError(#[from] ParseIntError),
+    /// An input does not match a date format.
+    #[error("{0}")]
+    DateFormatError(#[from] ParseError),
+    /// A component of a date time like the day or the hour is out of range,
+    /// like the day of `2023-02-30`.
+    #[error("{0}")]
+    InvalidComponent(String),
+    /// An epoch time cannot be represented as a date time.
+    #[error("Invalid epoch time: {0}")]
+    InvalidEpochTime(i64),
+    /// A duration like `1h30m` cannot be parsed.
+    #[error("Invalid duration: \"{0}\"")]
+    InvalidDuration(String),
+    /// A strftime format is invalid.
+    #[error("Invalid format: \"{0}\"")]
+    InvalidFormat(String),
+    /// An input kind has no detector.
+    #[error("Unsupported input kind: \"{0}\"")]
+    UnsupportedKind(String),
+    /// A time zone name is not in the time zone database.
+    #[error("Unknown time zone: \"{0}\"")]
+    UnknownTimeZone(String),
+    /// A value cannot be copied to the clipboard.
+    #[error("Cannot copy {0} to the clipboard: {1}")]
+    Clipboard(String, String),
+    /// An epoch time or the result of date time arithmetic is outside the
+    /// range of date times.
+    #[error("{}", describe_out_of_range(.value, .unit))]
+    OutOfRange {
+        /// The epoch time as given or the arithmetic, like `2023-02-16T12:34:56Z + 1 months`.
+        value: String,
+        /// The unit of an epoch time, `None` for date times.
+        unit: Option<EpochUnit>,
+    },
+    /// The step of a range is not positive.
+    #[error("Step must be positive: {0}")]
+    InvalidStep(String),
+    /// A range contains more date times than allowed.
+    #[error("Range contains more than {0} date times, see --limit")]
+    RangeLimitExceeded(usize),
+    /// A local time does not exist because of a transition of its time
+    /// zone, like the start of daylight saving time.
+    #[error("{gap} does not exist on {date} in {timezone}")]
+    NonexistentLocalTime {
+        /// The skipped local times like `02:00–03:00`.
+        gap: String,
+        /// The date of the local time.
+        date: String,
+        /// The name of the time zone.
+        timezone: String,
+    },
+    /// A local time exists twice because of a transition of its time zone,
+    /// like the end of daylight saving time.
+    #[error("{fold} exists twice on {date} in {timezone}, see --dst")]
+    AmbiguousLocalTime {
+        /// The repeated local times like `02:00–03:00`.
+        fold: String,
+        /// The date of the local time.
+        date: String,
+        /// The name of the time zone.
+        timezone: String,
+    },
+    /// A histogram bucket is neither a number of months nor an exact duration.
+    #[error("Bucket must be a positive number of months or a positive exact duration: \"{0}\"")]
+    InvalidBucket(String),
+    /// A histogram contains more buckets than allowed.
+    #[error("Histogram contains more than {0} buckets")]
+    TooManyBuckets(usize),
+    /// A CSV column is neither a number starting at 1 nor the name of a header.
+    #[error("Column must be a number starting at 1 or the name of a header: \"{0}\"")]
+    InvalidColumn(String),
+    /// A JSON path is not a sequence of keys and array indices.
+    #[error("Invalid JSON path, expected keys separated by '.' and indices like [0]: \"{0}\"")]
+    InvalidJsonPath(String),
+    /// The fraction of seconds like `789` of `1676550896.789` is not a
+    /// number.
+    #[error("Invalid fraction of seconds: \"{0}\"")]
+    InvalidFraction(String),
+    /// An iCalendar property line like `DTSTART:20230216T123456Z` is
+    /// malformed.
+    #[error("Invalid iCalendar property \"{value}\": {reason}")]
+    InvalidIcal {
+        /// The input.
+        value: String,
+        /// What is wrong with the line, like `not a property line`.
+        reason: String,
+    },
+    /// The fields of a protobuf timestamp like `seconds: 1676550896` are
+    /// malformed.
+    #[error("Invalid protobuf timestamp \"{value}\": {reason}")]
+    InvalidProtobuf {
+        /// The input.
+        value: String,
+        /// What is wrong with its fields, like `unknown field "millis"`.
+        reason: String,
+    },
+    /// An input failed to parse as all tried kinds.
+    #[error(transparent)]
+    Unparsable(ParseFailure),
+    /// An input is none of the kinds of [ParseOptions::kinds].
+    #[error("Input is none of the allowed formats {allowed}: {source}")]
+    StrictMismatch {
+        /// The names of the allowed kinds.
+        allowed: String,
+        /// The error of parsing the input as the allowed kinds.
+        source: Box<DateTimeError>,
+    },
+    /// [InputKind::Local] is selected without a time zone of the local
+    /// times.
+    #[error("Input kind \"local\" requires a time zone, see --assume-tz")]
+    LocalWithoutTimeZone,
+    /// A line of the input is not valid UTF-8.
+    #[error("invalid UTF-8")]
+    InvalidUtf8,
+}
+
+/// The message of [DateTimeError::OutOfRange] naming the limits.
+fn describe_out_of_range(value: &str, unit: &Option<EpochUnit>) -> String {
+    match unit {
+        Some(unit) => {
+            let range = detect::epoch_range(*unit);
+            format!(
+                "Epoch time out of range: {} {} is not between {} and {}",
+                value,
+                unit.symbol(),
+                range.start(),
+                range.end()
+            )
+        }
+        None => {
+            let iso =
+                |datetime: DateTime<Utc>| datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+            format!(
+                "Date time out of range: {} is not between {} and {}",
+                value,
+                iso(DateTime::<Utc>::MIN_UTC),
+                iso(DateTime::<Utc>::MAX_UTC)
+            )
+        }
+    }
+}
+
+/// Parses `input` trying all formats with the default options.
+///
+/// ```
+/// let parsed = datetime_helper::parse("1676550896").unwrap();
+/// assert_eq!(parsed.datetime.to_rfc3339(), "2023-02-16T12:34:56+00:00");
+/// assert_eq!(parsed.format, datetime_helper::InputKind::EpochSeconds);
+/// ```
+pub fn parse(input: &str) -> Result<ParsedDateTime, DateTimeError> {
+    default_detectors().parse(input)
+}
+
+/// Parses `inputs` like [parse] on the threads of the global rayon pool.
+/// The results are in the order of the inputs.
+///
+/// ```
+/// let results = datetime_helper::parse_many_parallel(vec!["1676550896", "foo"]);
+/// assert_eq!(results[0].as_ref().unwrap().epoch_seconds(), 1676550896);
+/// assert!(results[1].is_err());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn parse_many_parallel<'a>(
+    inputs: impl IntoParallelIterator<Item = &'a str>,
+) -> Vec<Result<ParsedDateTime, DateTimeError>> {
+    default_detectors().parse_many_parallel(inputs)
+}
+
+/// Parses the name of a time zone of the IANA database like `Europe/Berlin`.
+///
+/// ```
+/// let timezone = datetime_helper::parse_timezone("Europe/Berlin").unwrap();
+/// assert_eq!(timezone, datetime_helper::Tz::Europe__Berlin);
+/// assert!(datetime_helper::parse_timezone("Europe/Springfield").is_err());
+/// ```
+#[cfg(feature = "tz")]
+pub fn parse_timezone(input: &str) -> Result<Tz, DateTimeError> {
+    input
+        .parse()
+        .map_err(|_| DateTimeError::UnknownTimeZone(input.to_string()))
+}
+
+fn default_detectors() -> &'static Detectors {
+    static DETECTORS: OnceLock<Detectors> = OnceLock::new();
+    DETECTORS.get_or_init(Detectors::default)
+}
+
+/// Parses `input` only trying the given kinds in their order, like the
+/// `--strict` option of the `datetime` tool.
+///
+/// ```
+/// use datetime_helper::{parse_with, InputKind};
+///
+/// let kinds = [InputKind::Rfc3339, InputKind::EpochMicros];
+/// let parsed = parse_with(&kinds, "1676550896789123").unwrap();
+/// assert_eq!(parsed.format, InputKind::EpochMicros);
+/// assert!(parse_with(&kinds, "Thu, 16 Feb 2023 12:34:56 +0000").is_err());
+/// ```
+pub fn parse_with(kinds: &[InputKind], input: &str) -> Result<ParsedDateTime, DateTimeError> {
+    ParseOptions {
+        kinds: kinds.to_vec(),
+        ..Default::default()
+    }
+    .parse(input)
+}
+
+impl ParseOptions {
+    /// Parses `input`, which may be surrounded by whitespace, as the kinds
+    /// of these options. With [ParseOptions::unit], numbers are epoch times
+    /// in this unit if any epoch kind is allowed.
+    ///
+    /// ```
+    /// use datetime_helper::{EpochUnit, InputKind, ParseOptions};
+    ///
+    /// let options = ParseOptions {
+    ///     kinds: vec![InputKind::EpochSeconds, InputKind::EpochMillis],
+    ///     unit: Some(EpochUnit::Us),
+    ///     ..Default::default()
+    /// };
+    /// let parsed = options.parse(" 1676550896789123 ").unwrap();
+    /// assert_eq!(parsed.datetime.timestamp_micros(), 1676550896789123);
+    /// assert_eq!(parsed.format, InputKind::EpochMicros);
+    /// assert!(options.parse("2023-02-16T12:34:56Z").is_err());
+    /// ```
+    pub fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        self.detectors()?.parse(input)
+    }
+
+    /// The detectors of these options, which should be reused for parsing
+    /// many inputs. With [ParseOptions::unit], the first epoch kind is
+    /// replaced by epoch times in this unit and the other epoch kinds are
+    /// dropped. Custom kinds have no detector, and [InputKind::Local] only
+    /// has one with [ParseOptions::assume_tz].
+    pub fn detectors(&self) -> Result<Detectors, DateTimeError> {
+        let kinds = if self.kinds.is_empty() {
+            &InputKind::DEFAULT[..]
+        } else {
+            &self.kinds[..]
+        };
+        let mut builder = Detectors::builder();
+        let mut unit_used = false;
+        for kind in kinds {
+            builder = match (kind, kind.unit(), self.unit) {
+                (InputKind::FloatSeconds, _, _) => builder.push(FloatSecondsDetector),
+                (_, Some(_), Some(_)) if unit_used => builder,
+                (_, Some(_), Some(unit)) => {
+                    unit_used = true;
+                    builder.push(EpochDetector::new(unit))
+                }
+                (_, Some(EpochUnit::S), None) => {
+                    builder.push(EpochDetector::seconds_before(self.epoch_cutoff_year))
+                }
+                (_, Some(unit), None) => builder.push(EpochDetector::new(unit)),
+                (InputKind::Rfc3339, None, _) => builder.push(Rfc3339Detector),
+                (InputKind::Rfc2822, None, _) => builder.push(Rfc2822Detector),
+                (InputKind::Ical, None, _) => builder.push(self.ical_detector()),
+                (InputKind::Protobuf, None, _) => builder.push(ProtobufDetector),
+                #[cfg(feature = "tz")]
+                (InputKind::Local, None, _) => builder.push(self.local_detector()?),
+                (kind, None, _) => return Err(DateTimeError::UnsupportedKind(kind.to_string())),
+            };
+        }
+        if self.float_seconds && !kinds.contains(&InputKind::FloatSeconds) {
+            builder = builder.push(FloatSecondsDetector);
+        }
+        #[cfg(feature = "tz")]
+        if let (Some(timezone), false) = (self.assume_tz, kinds.contains(&InputKind::Local)) {
+            builder = builder.push(LocalDetector::new(timezone, self.dst));
+        }
+        if !self.kinds.is_empty() {
+            builder = builder.strict();
+        }
+        Ok(builder.build())
+    }
+
+    /// The detector of [InputKind::Local] in the time zone of
+    /// [ParseOptions::assume_tz], which is required.
+    #[cfg(feature = "tz")]
+    fn local_detector(&self) -> Result<LocalDetector, DateTimeError> {
+        match self.assume_tz {
+            Some(timezone) => Ok(LocalDetector::new(timezone, self.dst)),
+            None => Err(DateTimeError::LocalWithoutTimeZone),
+        }
+    }
+
+    /// The iCalendar detector reading floating values in the time zone of
+    /// [ParseOptions::assume_tz].
+    fn ical_detector(&self) -> IcalDetector {
+        #[cfg(feature = "tz")]
+        return IcalDetector::with_timezone(self.assume_tz, self.dst);
+        #[cfg(not(feature = "tz"))]
+        IcalDetector::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Datelike, Utc};
+
+    use crate::{parse, parse_with, DateTimeError, EpochUnit, InputKind, ParseOptions};
+
+    fn get_datetime(input: &str, options: &ParseOptions) -> Result<DateTime<Utc>, DateTimeError> {
+        options.parse(input).map(|parsed| parsed.datetime)
+    }
+
+    fn strict(kinds: &[InputKind]) -> ParseOptions {
+        ParseOptions {
+            kinds: kinds.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn cutoff(epoch_cutoff_year: i32) -> ParseOptions {
+        ParseOptions {
+            epoch_cutoff_year,
+            ..Default::default()
+        }
+    }
+
+    fn unit(unit: EpochUnit) -> ParseOptions {
+        ParseOptions {
+            unit: Some(unit),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn iso_8601_inclusive_milliseconds() {
+        let input = "2023-02-16T12:34:56.789Z";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn iso_8601_inclusive_milliseconds_starting_with_spaces() {
+        let input = " 2023-02-16T12:34:56.789Z";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn iso_8601_inclusive_milliseconds_ending_with_spaces() {
+        let input = "2023-02-16T12:34:56.789Z ";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn iso_8601() {
+        let input = "2023-02-16T12:34:56Z";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn iso_8601_starting_with_spaces() {
+        let input = " 2023-02-16T12:34:56Z";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn iso_8601_ending_with_spaces() {
+        let input = "2023-02-16T12:34:56Z ";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn epoch_seconds() {
+        let input = "1676550896";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn epoch_seconds_starting_with_spaces() {
+        let input = " 1676550896";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn epoch_seconds_ending_with_spaces() {
+        let input = "1676550896 ";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn epoch_milliseconds() {
+        let input = "1676550896789";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn epoch_milliseconds_starting_with_spaces() {
+        let input = " 1676550896789";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn epoch_milliseconds_ending_with_spaces() {
+        let input = "1676550896789 ";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn rfc2822() {
+        let input = "Thu, 16 Feb 2023 12:34:56 +0000";
+        let date_time = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    }
+
+    #[test]
+    fn strict_rfc3339_accepts_rfc3339() {
+        let input = "2023-02-16T12:34:56.789Z";
+        let date_time = get_datetime(input, &strict(&[InputKind::Rfc3339])).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn strict_rfc3339_rejects_epoch_seconds() {
+        let result = get_datetime("1676550896", &strict(&[InputKind::Rfc3339]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn strict_epoch_seconds_rejects_epoch_milliseconds() {
+        let result = get_datetime("1676550896789", &strict(&[InputKind::EpochSeconds]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn strict_epoch_millis_forces_milliseconds() {
+        let input = "1676550896";
+        let date_time = get_datetime(input, &strict(&[InputKind::EpochMillis])).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896);
+    }
+
+    #[test]
+    fn strict_error_names_allowed_formats() {
+        let formats = [InputKind::Rfc3339, InputKind::EpochSeconds];
+        let error = get_datetime("foo", &strict(&formats)).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Input is none of the allowed formats rfc3339, epoch-seconds:"));
+    }
+
+    #[test]
+    fn unit_seconds_beyond_year_3000() {
+        let input = "32503680000";
+        let guessed = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(guessed.year(), 1971);
+        let forced = get_datetime(input, &unit(EpochUnit::S)).unwrap();
+        assert_eq!(forced.year(), 3000);
+    }
+
+    #[test]
+    fn unit_milliseconds_for_small_values() {
+        let input = "1676550896";
+        let guessed = get_datetime(input, &ParseOptions::default()).unwrap();
+        assert_eq!(guessed.timestamp_millis(), 1676550896000);
+        let forced = get_datetime(input, &unit(EpochUnit::Ms)).unwrap();
+        assert_eq!(forced.timestamp_millis(), 1676550896);
+    }
+
+    #[test]
+    fn unit_microseconds() {
+        let input = "1676550896789123";
+        let date_time = get_datetime(input, &unit(EpochUnit::Us)).unwrap();
+        assert_eq!(date_time.timestamp_nanos_opt(), Some(1676550896789123000));
+    }
+
+    #[test]
+    fn unit_nanoseconds() {
+        let input = "1676550896789123456";
+        let date_time = get_datetime(input, &unit(EpochUnit::Ns)).unwrap();
+        assert_eq!(date_time.timestamp_nanos_opt(), Some(1676550896789123456));
+    }
+
+    #[test]
+    fn unit_nanoseconds_before_epoch() {
+        let input = "-1500000000";
+        let date_time = get_datetime(input, &unit(EpochUnit::Ns)).unwrap();
+        assert_eq!(date_time.timestamp_nanos_opt(), Some(-1500000000));
+    }
+
+    #[test]
+    fn unit_out_of_range() {
+        let result = get_datetime("9223372036854775807", &unit(EpochUnit::S));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unit_does_not_affect_non_numeric_inputs() {
+        let input = "2023-02-16T12:34:56.789Z";
+        let date_time = get_datetime(input, &unit(EpochUnit::Ns)).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    }
+
+    #[test]
+    fn epoch_cutoff_year_moves_interpretation() {
+        // 32503680000 seconds are 3000-01-01, as milliseconds they are in 1971
+        let input = "32503680000";
+        let below = get_datetime(input, &cutoff(3000)).unwrap();
+        assert_eq!(below.year(), 1971);
+        let above = get_datetime(input, &cutoff(3001)).unwrap();
+        assert_eq!(above.year(), 3000);
+    }
+
+    #[test]
+    fn epoch_cutoff_year_before_epoch_forces_milliseconds() {
+        let input = "1676550896";
+        let date_time = get_datetime(input, &cutoff(1970)).unwrap();
+        assert_eq!(date_time.timestamp_millis(), 1676550896);
+    }
+
+    #[test]
+    fn parse_reports_format_and_unit() {
+        let parsed = parse("1676550896789").unwrap();
+        assert_eq!(parsed.format, InputKind::EpochMillis);
+        assert_eq!(parsed.unit, Some(EpochUnit::Ms));
+        let parsed = parse("Thu, 16 Feb 2023 12:34:56 +0000").unwrap();
+        assert_eq!((parsed.format, parsed.unit), (InputKind::Rfc2822, None));
+        let parsed = unit(EpochUnit::Ns).parse("1676550896789123456").unwrap();
+        assert_eq!(parsed.unit, Some(EpochUnit::Ns));
+        assert_eq!(parsed.format, InputKind::EpochNanos);
+    }
+
+    #[test]
+    fn parse_with_only_tries_the_given_kinds() {
+        let input = "1676550896789123";
+        assert_eq!(parse(input).unwrap().format, InputKind::EpochMillis);
+        let parsed = parse_with(&[InputKind::EpochMicros], input).unwrap();
+        assert_eq!(parsed.datetime.timestamp_micros(), 1676550896789123);
+        assert_eq!(parsed.unit, Some(EpochUnit::Us));
+        let parsed = parse_with(&[InputKind::EpochNanos], input).unwrap();
+        assert_eq!(
+            parsed.datetime.timestamp_nanos_opt(),
+            Some(1676550896789123)
+        );
+        assert!(parse_with(&[InputKind::Rfc2822], "2023-02-16T12:34:56Z").is_err());
+    }
+
+    #[test]
+    fn custom_kinds_without_parser_are_errors() {
+        let kinds = [InputKind::Custom("syslog"), InputKind::Rfc3339];
+        let error = parse_with(&kinds, "2023-02-16T12:34:56Z").unwrap_err();
+        assert!(error.to_string().contains("syslog"), "{error}");
+    }
+
+    #[test]
+    fn kinds_display_their_cli_names() {
+        let names: Vec<String> = InputKind::BUILT_IN
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "rfc3339",
+                "rfc2822",
+                "epoch-seconds",
+                "epoch-millis",
+                "epoch-micros",
+                "epoch-nanos",
+                "float-seconds",
+                "ical",
+                "protobuf",
+                "local"
+            ]
+        );
+        assert_eq!(InputKind::Custom("syslog").to_string(), "syslog");
+    }
+
+    #[test]
+    fn out_of_range_names_the_limits() {
+        let error = crate::parse("9223372036854775807").unwrap_err();
+        assert!(error.to_string().contains(
+            "Epoch time out of range: 9223372036854775807 s is not between -8334601142400 and \
+             8210266790399"
+        ));
+        let error = DateTimeError::OutOfRange {
+            value: "2023-02-16T12:34:56Z + 3600000 months".to_string(),
+            unit: None,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Date time out of range: 2023-02-16T12:34:56Z + 3600000 months is not between \
+             -262143-01-01T00:00:00Z and +262142-12-31T23:59:59.999999999Z"
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn built_in_kinds_are_values_of_strict() {
+        use clap::ValueEnum;
+
+        for kind in InputKind::BUILT_IN {
+            assert_eq!(InputKind::from_str(kind.name(), false), Ok(kind));
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use chrono::{DateTime, Duration, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+
+use crate::calendar::invalid_component;
+use crate::{DateTimeError, Detector, InputKind, ParsedDateTime};
+
+/// The formats of local date times, tried in this order.
+const FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// How local times are resolved which are ambiguous or do not exist because
+/// of a transition of their time zone, like the end or the start of daylight
+/// saving time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DstPolicy {
+    /// Reject ambiguous and nonexistent local times
+    #[default]
+    Reject,
+    /// Take the earlier of two ambiguous instants
+    Earliest,
+    /// Take the later of two ambiguous instants
+    Latest,
+    /// Move nonexistent local times forward by the gap and take the earlier
+    /// of two ambiguous instants
+    Shift,
+}
+
+impl DstPolicy {
+    /// The name as used by `--dst`.
+    pub fn name(self) -> &'static str {
+        match self {
+            DstPolicy::Reject => "reject",
+            DstPolicy::Earliest => "earliest",
+            DstPolicy::Latest => "latest",
+            DstPolicy::Shift => "shift",
+        }
+    }
+}
+
+/// Date times without offset like `2023-02-16 13:34:56` or
+/// `2023-02-16T13:34`, which are local times of a time zone.
+#[derive(Clone, Copy, Debug)]
+pub struct LocalDetector {
+    timezone: Tz,
+    dst: DstPolicy,
+}
+
+impl LocalDetector {
+    /// Detects local times of `timezone`, resolving ambiguous and
+    /// nonexistent ones with `dst`.
+    pub fn new(timezone: Tz, dst: DstPolicy) -> Self {
+        LocalDetector { timezone, dst }
+    }
+
+    pub(crate) fn resolve(
+        &self,
+        local: NaiveDateTime,
+    ) -> Result<DateTime<FixedOffset>, DateTimeError> {
+        match (self.timezone.from_local_datetime(&local), self.dst) {
+            (LocalResult::Single(datetime), _) => Ok(datetime.fixed_offset()),
+            (LocalResult::Ambiguous(_, latest), DstPolicy::Latest) => Ok(latest.fixed_offset()),
+            (LocalResult::Ambiguous(earliest, _), DstPolicy::Earliest | DstPolicy::Shift) => {
+                Ok(earliest.fixed_offset())
+            }
+            (LocalResult::Ambiguous(..), DstPolicy::Reject) => {
+                Err(DateTimeError::AmbiguousLocalTime {
+                    fold: self.transition(local).to_string(),
+                    date: local.date().to_string(),
+                    timezone: self.timezone.name().to_string(),
+                })
+            }
+            (LocalResult::None, DstPolicy::Shift) => {
+                let transition = self.transition(local);
+                let utc = local - Duration::seconds(transition.before.local_minus_utc().into());
+                Ok(DateTime::from_naive_utc_and_offset(utc, transition.after))
+            }
+            (LocalResult::None, _) => Err(DateTimeError::NonexistentLocalTime {
+                gap: self.transition(local).to_string(),
+                date: local.date().to_string(),
+                timezone: self.timezone.name().to_string(),
+            }),
+        }
+    }
+
+    /// The transition of the time zone next to `local`, which has to be
+    /// within a day of it.
+    fn transition(&self, local: NaiveDateTime) -> Transition {
+        let offset = |utc: NaiveDateTime| self.timezone.offset_from_utc_datetime(&utc).fix();
+        let (mut first, mut last) = (local - Duration::days(1), local + Duration::days(1));
+        let before = offset(first);
+        // The first instant with the new offset, transitions are at full seconds.
+        while last - first > Duration::seconds(1) {
+            let middle = first + (last - first) / 2;
+            if offset(middle) == before {
+                first = middle;
+            } else {
+                last = middle;
+            }
+        }
+        Transition {
+            utc: last,
+            before,
+            after: offset(last),
+        }
+    }
+}
+
+/// A change of the offset of a time zone.
+struct Transition {
+    utc: NaiveDateTime,
+    before: FixedOffset,
+    after: FixedOffset,
+}
+
+/// The local times which are skipped or repeated, like `02:00–03:00`.
+impl std::fmt::Display for Transition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let local =
+            |offset: FixedOffset| self.utc + Duration::seconds(offset.local_minus_utc().into());
+        let (before, after) = (local(self.before), local(self.after));
+        let format = match before.and_utc().timestamp() % 60 {
+            0 => "%H:%M",
+            _ => "%H:%M:%S",
+        };
+        write!(
+            f,
+            "{}–{}",
+            before.min(after).format(format),
+            before.max(after).format(format)
+        )
+    }
+}
+
+impl Detector for LocalDetector {
+    fn kind(&self) -> InputKind {
+        InputKind::Local
+    }
+
+    /// Local date times start with a year of four digits followed by `-`.
+    fn looks_like(&self, input: &str) -> bool {
+        input.as_bytes().get(4) == Some(&b'-')
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let datetime = self.resolve(parse_local(input)?)?;
+        Ok(ParsedDateTime::new(input, self.kind(), datetime))
+    }
+
+    /// Names the time zone, whether the local time is ambiguous or does not
+    /// exist and the policy of `--dst`.
+    fn reason(&self, input: &str) -> Option<String> {
+        let local = parse_local(input).ok()?;
+        let resolution = match self.timezone.from_local_datetime(&local) {
+            LocalResult::Single(_) => "",
+            LocalResult::Ambiguous(..) => "ambiguous ",
+            LocalResult::None => "nonexistent ",
+        };
+        Some(format!(
+            "{}in {}, --dst {}",
+            resolution,
+            self.timezone.name(),
+            self.dst.name()
+        ))
+    }
+}
+
+fn parse_local(input: &str) -> Result<NaiveDateTime, DateTimeError> {
+    let mut result = NaiveDateTime::parse_from_str(input, FORMATS[0]);
+    for format in &FORMATS[1..] {
+        if result.is_ok() {
+            break;
+        }
+        result = NaiveDateTime::parse_from_str(input, format);
+    }
+    result.map_err(|e| invalid_component(input).map_or(e.into(), DateTimeError::InvalidComponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Tz;
+
+    use crate::{DateTimeError, Detector, DstPolicy, LocalDetector};
+
+    fn parse(input: &str, dst: DstPolicy) -> Result<String, DateTimeError> {
+        let detector = LocalDetector::new(Tz::Europe__Berlin, dst);
+        let parsed = detector.parse(input)?;
+        Ok(parsed.datetime.to_rfc3339())
+    }
+
+    #[test]
+    fn unique_local_times() {
+        for input in [
+            "2023-02-16 13:34:56",
+            "2023-02-16T13:34:56",
+            "2023-02-16T13:34:56.000",
+        ] {
+            assert_eq!(
+                parse(input, DstPolicy::Reject).unwrap(),
+                "2023-02-16T12:34:56+00:00"
+            );
+        }
+        let parsed = LocalDetector::new(Tz::Asia__Kolkata, DstPolicy::Reject)
+            .parse("2023-02-16 18:04")
+            .unwrap();
+        assert_eq!(parsed.datetime.to_rfc3339(), "2023-02-16T12:34:00+00:00");
+        assert!(parse("2023-02-16", DstPolicy::Reject).is_err());
+        let error = parse("2024-02-30 12:00", DstPolicy::Reject).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 30 is out of range for February 2024 (max 29)"
+        );
+    }
+
+    #[test]
+    fn nonexistent_local_times() {
+        let error = parse("2023-03-26 02:30", DstPolicy::Reject).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "02:00–03:00 does not exist on 2023-03-26 in Europe/Berlin"
+        );
+        for dst in [DstPolicy::Earliest, DstPolicy::Latest] {
+            assert!(parse("2023-03-26 02:30", dst).is_err());
+        }
+        assert_eq!(
+            parse("2023-03-26 02:30", DstPolicy::Shift).unwrap(),
+            "2023-03-26T01:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn ambiguous_local_times() {
+        let error = parse("2023-10-29 02:30", DstPolicy::Reject).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "02:00–03:00 exists twice on 2023-10-29 in Europe/Berlin, see --dst"
+        );
+        assert_eq!(
+            parse("2023-10-29 02:30", DstPolicy::Earliest).unwrap(),
+            "2023-10-29T00:30:00+00:00"
+        );
+        assert_eq!(
+            parse("2023-10-29 02:30", DstPolicy::Shift).unwrap(),
+            "2023-10-29T00:30:00+00:00"
+        );
+        assert_eq!(
+            parse("2023-10-29 02:30", DstPolicy::Latest).unwrap(),
+            "2023-10-29T01:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn transitions_of_the_southern_hemisphere() {
+        let detector = LocalDetector::new(Tz::Australia__Sydney, DstPolicy::Reject);
+        let error = detector.parse("2023-10-01 02:15").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "02:00–03:00 does not exist on 2023-10-01 in Australia/Sydney"
+        );
+        let error = detector.parse("2023-04-02 02:15").unwrap_err();
+        assert!(error.to_string().starts_with("02:00–03:00 exists twice"));
+    }
+
+    #[test]
+    fn reason_names_the_policy() {
+        let detector = LocalDetector::new(Tz::Europe__Berlin, DstPolicy::Latest);
+        assert_eq!(
+            detector.reason("2023-10-29 02:30").unwrap(),
+            "ambiguous in Europe/Berlin, --dst latest"
+        );
+        assert_eq!(
+            detector.reason("2023-02-16 13:34").unwrap(),
+            "in Europe/Berlin, --dst latest"
+        );
+    }
+}
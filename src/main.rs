@@ -1,182 +1,756 @@
-use std::io::stdin;
-use std::num::ParseIntError;
+use std::ffi::OsString;
+use std::io::{stderr, stdin, stdout, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-use chrono::prelude::*;
-use chrono::LocalResult::Single;
-use chrono::{DateTime, ParseError, Utc};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 
-use clap::Parser;
-use thiserror::Error;
+use clap::error::ErrorKind;
+use clap::{ArgAction, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use datetime_helper::workdays::Weekend;
+use datetime_helper::{
+    duration, output, stats, DateTimeError, Detectors, DstPolicy, EpochUnit, InputKind,
+    ParseOptions, DEFAULT_EPOCH_CUTOFF_YEAR,
+};
 
-use crate::DateTimeError::MultipleErrors;
+use crate::column::Column;
+use crate::config::ConfigError;
+use crate::duration::CalendarDuration;
+use crate::histogram::Bucket;
+use crate::json_path::JsonPath;
+use crate::output::{OutputField, OutputFormat, OutputOptions, Precision, WeekRule};
+use crate::stamps::Stamp;
+
+mod clipboard;
+mod column;
+mod commands;
+mod config;
+mod deltas;
+mod histogram;
+#[cfg(feature = "http")]
+mod http;
+mod input;
+mod interactive;
+mod json_path;
+mod modes;
+mod pair;
+mod parallel;
+mod progress;
+mod range;
+mod scan;
+mod sort;
+mod stamps;
+mod trace;
+mod watch;
 
 /// Tries to parse an input from standard input or from first parameter as
-/// ISO 8601 date or as epoch (milli)seconds and prints the parsed date as
+/// ISO 8601 date, RFC 2822 date or as epoch (milli)seconds and prints the parsed date as
 /// - ISO 8601 datetime,
 /// - epoch seconds and
 /// - epoch milliseconds.
+///
 /// NOTE:
-/// Numeric values will be handled as epoch seconds if the year of the result is less than 3000.
-/// Otherwise, they will be handled as epoch milliseconds.
+/// Numeric values will be handled as epoch seconds if the year of the result is less than 3000
+/// (see --epoch-cutoff-year). Otherwise, they will be handled as epoch milliseconds.
+/// Use --unit to interpret numeric values in a fixed unit instead.
 #[derive(Parser)]
-#[clap(verbatim_doc_comment)]
+#[clap(
+    name = "datetime",
+    verbatim_doc_comment,
+    override_usage = "datetime [OPTIONS] [DATE_TIME]\n       datetime [OPTIONS] <COMMAND>",
+    after_long_help = EXAMPLES
+)]
 struct CliParams {
+    #[clap(subcommand)]
+    command: Option<Command>,
     /// Input to be parsed. If omitted standard input is used.
     #[clap()]
     date_time: Option<String>,
+    /// Read the inputs from the given file instead of standard input.
+    #[clap(long, value_name = "PATH", conflicts_with_all = ["date_time", "interactive", "watch"])]
+    file: Option<PathBuf>,
+    /// Only check that all inputs can be parsed, exiting with 1 if any input is invalid.
+    ///
+    /// Nothing is printed to standard output. With --verbose, each failure is printed to standard
+    /// error with its line number.
+    #[clap(
+        long,
+        conflicts_with_all = ["interactive", "watch", "rewrite", "annotate", "annotate_eol", "sort"]
+    )]
+    check: bool,
+    /// Prompt for inputs interactively. This is the default if standard input is a terminal.
+    #[clap(short, long, conflicts_with = "date_time")]
+    interactive: bool,
+    /// Continuously display the current time until interrupted with Ctrl-C.
+    #[clap(short, long, conflicts_with_all = ["date_time", "interactive"])]
+    watch: bool,
+    /// Interval in milliseconds between two updates of --watch.
+    #[clap(long, value_name = "MS", default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+    /// Print the input with every date time found in it replaced by its --format or --only value.
+    #[clap(long, conflicts_with_all = ["interactive", "watch"])]
+    rewrite: bool,
+    /// Print the input with the --format or --only value appended to every date time found in it.
+    #[clap(long, conflicts_with_all = ["interactive", "watch", "rewrite"])]
+    annotate: bool,
+    /// Like --annotate, but append the values at the end of each line.
+    #[clap(long, conflicts_with_all = ["interactive", "watch", "rewrite", "annotate"])]
+    annotate_eol: bool,
+    /// Read the input as CSV and replace the date time in the --column of each record by its --format or --only
+    /// value. The first record is the header. All other cells, quotes and the header are kept.
+    #[clap(
+        long,
+        requires = "column",
+        conflicts_with_all = ["date_time", "interactive", "watch", "rewrite", "annotate", "annotate_eol", "check", "skip", "limit"]
+    )]
+    csv: bool,
+    /// The column of --csv, either its number starting at 1 or its name in the header.
+    #[clap(long, value_name = "COLUMN", requires = "csv", value_parser = column::parse_column)]
+    column: Option<Column>,
+    /// Read each input line as JSON and replace the date time at --json-key by its --format or --only value. Lines are
+    /// printed compactly with their keys in the original order.
+    #[clap(
+        long,
+        requires = "json_key",
+        conflicts_with_all = ["date_time", "interactive", "watch", "rewrite", "annotate", "annotate_eol", "check", "csv"]
+    )]
+    json_rewrite: bool,
+    /// Send a HEAD request to the URL and convert its Date, Last-Modified, Expires and Retry-After headers, followed
+    /// by the receive time and the skew between it and Date.
+    #[cfg(feature = "http")]
+    #[clap(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["date_time", "file", "interactive", "watch", "rewrite", "annotate", "annotate_eol", "csv", "json_rewrite", "sort", "check", "skip", "limit"]
+    )]
+    url: Option<String>,
+    /// Print the modification time of each of the files, and with --atime, --ctime and --created those stamps, too.
+    #[clap(
+        long,
+        value_name = "PATH",
+        num_args = 1..,
+        conflicts_with_all = ["date_time", "file", "interactive", "watch", "rewrite", "annotate", "annotate_eol", "csv", "json_rewrite", "sort", "check", "skip", "limit"]
+    )]
+    mtime: Vec<PathBuf>,
+    /// Print the access time of the files of --mtime, too.
+    #[clap(long, requires = "mtime")]
+    atime: bool,
+    /// Print the status change time of the files of --mtime, too, which is only supported on Unix.
+    #[clap(long, requires = "mtime")]
+    ctime: bool,
+    /// Print the creation time of the files of --mtime, too, where the platform and file system record it.
+    #[clap(long, requires = "mtime")]
+    created: bool,
+    /// Print the two date times of each input line, like a send and a receive time, and the drift from the first to
+    /// the second in milliseconds, followed by the smallest, mean and largest drift. Lines without exactly two date
+    /// times are reported and skipped.
+    #[clap(
+        long,
+        conflicts_with_all = ["interactive", "watch", "rewrite", "annotate", "annotate_eol", "csv", "json_rewrite", "sort", "check"]
+    )]
+    pair: bool,
+    /// Split the lines of --pair at DELIMITER into the two date times, which may then be in any format, instead of
+    /// searching them like --rewrite.
+    #[clap(long, value_name = "DELIMITER", requires = "pair")]
+    pair_delimiter: Option<String>,
+    /// Print the date time of each input line after the first with the delta from the previous line in milliseconds
+    /// and in words, followed by the smallest, mean and largest gap and the line of the largest one. Lines which
+    /// cannot be parsed are reported and skipped. The input is read line by line.
+    #[clap(
+        long,
+        conflicts_with_all = ["interactive", "watch", "rewrite", "annotate", "annotate_eol", "csv", "json_rewrite", "sort", "check", "pair"]
+    )]
+    deltas: bool,
+    /// Start anew after a line of --deltas which cannot be parsed instead of taking the delta of the next line from
+    /// the last parsed one.
+    #[clap(long, requires = "deltas")]
+    deltas_reset_on_error: bool,
+    /// The path of --json-rewrite, keys separated by '.' and array indices like 'meta.created_at' or 'events[0].time'.
+    #[clap(long, value_name = "PATH", requires = "json_rewrite", value_parser = json_path::parse_json_path)]
+    json_key: Option<JsonPath>,
+    /// Print the input lines sorted by the date time of each line or the first date time found in
+    /// it. Lines without a date time follow in their original order. All input is kept in memory.
+    #[clap(long, conflicts_with_all = ["interactive", "watch", "rewrite", "annotate", "annotate_eol", "csv", "json_rewrite"])]
+    sort: bool,
+    /// Sort in descending order with --sort.
+    #[clap(long)]
+    reverse: bool,
+    /// Leave out lines without a date time with --sort.
+    #[clap(long)]
+    sort_drop_unparsed: bool,
+    /// Print a summary of all inputs after their results: the number of parsed and failed inputs,
+    /// the earliest, latest and mean date time and the span between the earliest and the latest.
+    #[clap(long)]
+    stats: bool,
+    /// Like --stats, but only print the summary.
+    #[clap(long)]
+    stats_only: bool,
+    /// Print the number of inputs per bucket like 15m, 1h or day instead of the results, including
+    /// empty buckets between the earliest and the latest one.
+    #[clap(long, value_name = "BUCKET", value_parser = histogram::parse_bucket)]
+    histogram: Option<Bucket>,
+    /// Parse the inputs with N threads, 0 for one per CPU. The results keep the order of the inputs.
+    #[clap(long, value_name = "N", default_value_t = 1, conflicts_with_all = ["interactive", "watch", "rewrite", "annotate", "annotate_eol", "sort", "csv", "json_rewrite", "pair", "deltas"])]
+    jobs: usize,
+    /// Skip the first N inputs or lines.
+    #[clap(long, value_name = "N", default_value_t = 0, conflicts_with_all = ["interactive", "watch"])]
+    skip: usize,
+    /// Process at most N inputs or lines after the skipped ones and stop reading then.
+    #[clap(long, value_name = "N", conflicts_with_all = ["interactive", "watch"])]
+    limit: Option<usize>,
+    /// The text before and after each annotation of --annotate and --annotate-eol [default: ' [' and ']'].
+    #[clap(long, num_args = 2, value_names = ["OPEN", "CLOSE"], allow_hyphen_values = true, default_values = [" [", "]"], hide_default_value = true)]
+    delimiters: Vec<String>,
+    /// Do not print error messages for inputs that cannot be parsed.
+    #[clap(short, long, global = true)]
+    quiet: bool,
+    /// Like --quiet, but additionally suppresses any summary output.
+    #[clap(long, global = true)]
+    silent: bool,
+    /// Print additional information to standard error: how each input is detected, with -vv including the time each
+    /// detector took. Inputs are then parsed by a single thread.
+    #[clap(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Only accept inputs in the given formats instead of guessing.
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FORMAT"
+    )]
+    strict: Vec<InputKind>,
+    /// Interpret numeric inputs in the given unit instead of guessing.
+    #[clap(long, global = true, value_enum, env = "DATETIME_HELPER_UNIT")]
+    unit: Option<EpochUnit>,
+    /// Numeric values are epoch seconds if their year is below this year and epoch milliseconds otherwise.
+    #[clap(long, global = true, value_name = "YEAR", env = "DATETIME_HELPER_EPOCH_CUTOFF_YEAR", default_value_t = DEFAULT_EPOCH_CUTOFF_YEAR)]
+    epoch_cutoff_year: i32,
+    /// Additionally accept epoch seconds with a fraction like '1676550896.789' as used by Prometheus. Print them with
+    /// --only prom_s or prom_ms.
+    #[clap(long, global = true)]
+    prom: bool,
+    /// Read date times without offset like '2023-02-16 13:34:56' as local times of the given time zone.
+    #[clap(long, global = true, value_name = "TIME_ZONE", value_parser = datetime_helper::parse_timezone)]
+    assume_tz: Option<Tz>,
+    /// How local times of --assume-tz are resolved which are ambiguous or do not exist because of a daylight saving
+    /// time transition.
+    #[clap(long, global = true, value_enum, value_name = "POLICY", default_value_t = DstPolicy::Reject)]
+    dst: DstPolicy,
+    /// Only print the given value instead of the table.
+    #[clap(long, global = true, value_enum, value_name = "FIELD")]
+    only: Option<OutputField>,
+    /// Print date times in the given strftime format like '%H:%M:%S' instead of the table.
+    #[clap(long, global = true, value_name = "STRFTIME", conflicts_with = "only", value_parser = output::parse_time_format)]
+    format: Option<String>,
+    /// The format of the printed results.
+    #[clap(long, global = true, value_enum, value_name = "FORMAT", env = "DATETIME_HELPER_OUTPUT", default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+    /// Print date times in the given time zone like Europe/Berlin instead of UTC.
+    #[clap(long, global = true, value_name = "TIME_ZONE", env = "DATETIME_HELPER_TZ", value_parser = datetime_helper::parse_timezone)]
+    tz: Option<Tz>,
+    /// The fractional digits of printed ISO 8601 timestamps.
+    #[clap(long, global = true, value_enum, env = "DATETIME_HELPER_PRECISION", default_value_t = Precision::Ms)]
+    precision: Precision,
+    /// How the weeks of --only week are numbered.
+    #[clap(long, global = true, value_enum, value_name = "RULE", default_value_t = WeekRule::Iso)]
+    week_rule: WeekRule,
+    /// Additionally copy the given value of the result to the clipboard. If there are multiple
+    /// results, the value of the last one is copied.
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FIELD",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "iso"
+    )]
+    copy: Option<OutputField>,
+    /// Read default options from this config file instead of the default location.
+    #[clap(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Ignore the config file.
+    #[clap(long, global = true, conflicts_with = "config")]
+    no_config: bool,
+    /// Print the completion script for the given shell and exit.
+    #[clap(long, value_enum, value_name = "SHELL", hide = true)]
+    completions: Option<Shell>,
+    /// Print the man page in roff format and exit.
+    #[clap(long, hide = true)]
+    generate_man: bool,
 }
 
-#[derive(Error, Debug)]
-enum DateTimeError {
-    #[error("{0}")]
-    NumberFormatError(#[from] ParseIntError),
-    #[error("{0}")]
-    DateFormatError(#[from] ParseError),
-    #[error("Invalid epoch time: {0}")]
-    InvalidEpochTime(i64),
-    #[error("Multiple errors: \"{0}\" and \"{1}\"")]
-    MultipleErrors(Box<DateTimeError>, Box<DateTimeError>),
+const EXAMPLES: &str = "\
+Examples:
+  datetime 2023-02-16T12:34:56.789Z
+  datetime 'Thu, 16 Feb 2023 12:34:56 +0000'
+  datetime 1676550896
+  datetime 1676550896789
+  datetime --unit us 1676550896789000
+  datetime --prom 1676550896.789 --only prom_ms
+  datetime now --plus 1h --only seconds
+  datetime diff 1676550896 2023-03-01T00:00:00Z
+  datetime add 2023-01-31T12:00:00Z 1month
+  datetime range 2023-02-01T00:00:00Z 2023-02-05T00:00:00Z --step 6h
+  echo 1676550896 | datetime
+  cat app.log | datetime --rewrite --format '%H:%M:%S'
+  cat app.log | datetime --annotate-eol
+  cat app.ndjson | datetime --json-rewrite --json-key meta.created_at --only iso
+  datetime --csv --column created --format '%Y-%m-%d %H:%M:%S' --file in.csv";
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the current time, optionally shifted by a duration.
+    Now(NowArgs),
+    /// Print the duration between two date times.
+    Diff(DiffArgs),
+    /// Add a duration to a date time.
+    Add(ArithmeticArgs),
+    /// Subtract a duration from a date time.
+    Sub(ArithmeticArgs),
+    /// Print all date times from a start to an end in steps of a duration.
+    Range(RangeArgs),
+    /// Print the calendar time elapsed since a date or date time in years, months and days.
+    Age(AgeArgs),
+    /// Print the number of workdays from a date up to, but not including, another one, or with --add the date a
+    /// number of workdays after it.
+    Workdays(WorkdaysArgs),
+    /// Print the UTC offset, abbreviation and DST state of a time zone at a date time and its previous and next offset
+    /// transitions.
+    Tzinfo(ZoneArgs),
+    /// Print the next offset transition of a time zone after a date time with the jump of the wall clock.
+    DstNext(ZoneArgs),
+    /// Print the last offset transition of a time zone up to a date time with the jump of the wall clock.
+    DstPrev(ZoneArgs),
 }
 
-fn main() {
-    for line in input_iterator() {
-        match get_datetime(line.as_str()) {
-            Ok(datetime) => print_time(datetime),
-            Err(e) => eprintln!("{}", e),
-        }
-    }
+#[derive(Args)]
+struct NowArgs {
+    /// Add a duration like 15m, 1d2h30m or PT90M to the current time.
+    #[clap(long, value_name = "DURATION", value_parser = duration::parse_duration, allow_hyphen_values = true)]
+    plus: Option<CalendarDuration>,
+    /// Subtract a duration like 15m, 1d2h30m or PT90M from the current time.
+    #[clap(long, value_name = "DURATION", value_parser = duration::parse_duration, allow_hyphen_values = true)]
+    minus: Option<CalendarDuration>,
 }
 
-fn input_iterator() -> Box<dyn Iterator<Item = String>> {
-    match CliParams::parse().date_time {
-        Some(date_time) => Box::new([date_time].into_iter()),
-        _ => Box::new(stdin().lines().flatten()),
-    }
+#[derive(Args)]
+struct ArithmeticArgs {
+    /// The date time, "now" for the current time.
+    date_time: String,
+    /// A duration like 15m, 1d2h30m, 1month or PT90M. Adding months keeps the day of the month,
+    /// clamped to the last day of the resulting month.
+    #[clap(value_parser = duration::parse_duration, allow_hyphen_values = true)]
+    duration: CalendarDuration,
 }
 
-fn print_time(datetime: DateTime<Utc>) {
-    let iso_time = datetime.to_rfc3339_opts(SecondsFormat::Millis, true);
-    let epoch_seconds = datetime.timestamp();
-    let epoch_milliseconds = datetime.timestamp_millis();
-    println!("┌────────────────────┬──────────────────────────┐");
-    println!("│ ISO 8601 timestamp │ {:24} │", iso_time);
-    println!("├────────────────────┬──────────────────────────┤");
-    println!("│ Epoch seconds      │ {:24} │", epoch_seconds);
-    println!("├────────────────────┬──────────────────────────┤");
-    println!("│ Epoch milliseconds │ {:24} │", epoch_milliseconds);
-    println!("└────────────────────┴──────────────────────────┘");
+#[derive(Args)]
+struct RangeArgs {
+    /// The first date time, "now" for the current time.
+    start: String,
+    /// The last date time, "now" for the current time.
+    end: String,
+    /// The positive duration between two date times like 6h or 1month.
+    #[clap(long, value_name = "DURATION", value_parser = duration::parse_duration, allow_hyphen_values = true)]
+    step: CalendarDuration,
+    /// The maximum number of date times. Larger ranges are rejected.
+    #[clap(long, value_name = "COUNT", default_value_t = 100_000)]
+    limit: usize,
+    /// Exclude the end from the range.
+    #[clap(long)]
+    exclusive_end: bool,
 }
 
-fn get_datetime(input: &str) -> Result<DateTime<Utc>, DateTimeError> {
-    let trimmed = input.trim();
-    match (iso_to_datetime(trimmed), epoch_to_datetime(trimmed)) {
-        (Ok(datetime), _) => Ok(datetime),
-        (_, Ok(datetime)) => Ok(datetime),
-        (Err(e1), Err(e2)) => Err(MultipleErrors(Box::new(e1), Box::new(e2))),
-    }
+#[derive(Args)]
+struct AgeArgs {
+    /// The date like 1987-06-15 or the date time. Hours, minutes and seconds are only printed for date times.
+    date_time: String,
+    /// The date or date time at which the age is computed [default: now].
+    #[clap(long, value_name = "DATE_TIME")]
+    at: Option<String>,
 }
 
-fn iso_to_datetime(input: &str) -> Result<DateTime<Utc>, DateTimeError> {
-    Ok(DateTime::parse_from_rfc3339(input)?.into())
+#[derive(Args)]
+struct WorkdaysArgs {
+    /// The first date like 2023-02-16, or a date time whose date in the time zone of --tz is used.
+    start: String,
+    /// The date after the last counted one. Workdays before the start are counted negatively.
+    #[clap(required_unless_present = "add")]
+    end: Option<String>,
+    /// Print the date N workdays after the start, or before it for negative N.
+    #[clap(
+        long,
+        value_name = "N",
+        conflicts_with = "end",
+        allow_hyphen_values = true
+    )]
+    add: Option<i64>,
+    /// A file with a date like 2023-12-25 on each line which is no workday. Blank lines and lines starting with '#'
+    /// are ignored.
+    #[clap(long, value_name = "FILE")]
+    holidays: Option<PathBuf>,
+    /// The days of the weekend.
+    #[clap(long, value_enum, default_value_t = Weekend::SatSun)]
+    weekend: Weekend,
 }
 
-fn epoch_to_datetime(input: &str) -> Result<DateTime<Utc>, DateTimeError> {
-    let epoch_time = str::parse(input)?;
-    match (
-        Utc.timestamp_opt(epoch_time, 0),
-        Utc.timestamp_millis_opt(epoch_time),
-    ) {
-        (Single(datetime), _) if datetime.year() < 3000 => Ok(datetime),
-        (_, Single(datetime)) => Ok(datetime),
-        _ => Err(DateTimeError::InvalidEpochTime(epoch_time)),
-    }
+#[derive(Args)]
+struct ZoneArgs {
+    /// The IANA time zone like Europe/Berlin or Etc/GMT+5.
+    #[clap(value_parser = datetime_helper::parse_timezone)]
+    timezone: Tz,
+    /// The date time, "now" for the current time [default: now].
+    date_time: Option<String>,
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::get_datetime;
+#[derive(Args)]
+struct DiffArgs {
+    /// The start of the duration, "now" for the current time.
+    from: String,
+    /// The end of the duration, "now" for the current time [default: now].
+    to: Option<String>,
+}
 
-    #[test]
-    fn iso_8601_inclusive_milliseconds() {
-        let input = "2023-02-16T12:34:56.789Z";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+impl CliParams {
+    fn is_quiet(&self) -> bool {
+        self.quiet || self.silent
     }
 
-    #[test]
-    fn iso_8601_inclusive_milliseconds_starting_with_spaces() {
-        let input = " 2023-02-16T12:34:56.789Z";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    /// Whether the input is copied with date times rewritten or annotated or
+    /// is sorted.
+    fn is_filter(&self) -> bool {
+        self.rewrite || self.annotate || self.annotate_eol || self.sort
     }
 
-    #[test]
-    fn iso_8601_inclusive_milliseconds_ending_with_spaces() {
-        let input = "2023-02-16T12:34:56.789Z ";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    /// The inputs selected by --skip and --limit. Inputs after the limit are
+    /// not read.
+    fn window<I: Iterator>(&self, inputs: I) -> impl Iterator<Item = I::Item> {
+        inputs
+            .skip(self.skip)
+            .take(self.limit.unwrap_or(usize::MAX))
     }
 
-    #[test]
-    fn iso_8601() {
-        let input = "2023-02-16T12:34:56Z";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    /// The first given option selecting a mode other than converting the
+    /// inputs one by one.
+    fn mode_option(&self) -> Option<&'static str> {
+        [
+            ("--interactive", self.interactive),
+            ("--watch", self.watch),
+            ("--rewrite", self.rewrite),
+            ("--annotate", self.annotate),
+            ("--annotate-eol", self.annotate_eol),
+            ("--sort", self.sort),
+            ("--csv", self.csv),
+            ("--json-rewrite", self.json_rewrite),
+            ("--check", self.check),
+            ("--url", self.url().is_some()),
+            ("--mtime", !self.mtime.is_empty()),
+            ("--pair", self.pair),
+            ("--deltas", self.deltas),
+        ]
+        .into_iter()
+        .find_map(|(option, given)| given.then_some(option))
     }
 
-    #[test]
-    fn iso_8601_starting_with_spaces() {
-        let input = " 2023-02-16T12:34:56Z";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    /// The stamps of --mtime in the order they are printed.
+    fn stamps(&self) -> Vec<Stamp> {
+        [
+            (Stamp::Mtime, true),
+            (Stamp::Atime, self.atime),
+            (Stamp::Ctime, self.ctime),
+            (Stamp::Created, self.created),
+        ]
+        .into_iter()
+        .filter_map(|(stamp, given)| given.then_some(stamp))
+        .collect()
     }
 
-    #[test]
-    fn iso_8601_ending_with_spaces() {
-        let input = "2023-02-16T12:34:56Z ";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    /// The URL of --url, which needs the `http` feature.
+    fn url(&self) -> Option<&str> {
+        #[cfg(feature = "http")]
+        return self.url.as_deref();
+        #[cfg(not(feature = "http"))]
+        None
     }
 
-    #[test]
-    fn epoch_seconds() {
-        let input = "1676550896";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            kinds: self.strict.clone(),
+            unit: self.unit,
+            epoch_cutoff_year: self.epoch_cutoff_year,
+            float_seconds: self.prom,
+            assume_tz: self.assume_tz,
+            dst: self.dst,
+        }
     }
 
-    #[test]
-    fn epoch_seconds_starting_with_spaces() {
-        let input = " 1676550896";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    fn output_options(&self) -> OutputOptions {
+        OutputOptions {
+            only: self.only,
+            format: self.output,
+            time_format: self.format.clone(),
+            timezone: self.tz,
+            precision: self.precision,
+            week_rule: self.week_rule,
+        }
     }
+}
+
+fn main() -> ExitCode {
+    let params = match parse_params() {
+        Ok(params) => params,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    check_options(&params);
+    let mut out = BufWriter::new(stdout().lock());
+    let mut errors = stderr().lock();
+    let result = run(&params, &mut out, &mut errors);
+    let flushed = out.flush();
+    match result.and_then(|success| Ok(flushed.map(|_| success)?)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            let _ = writeln!(errors, "{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Exits with a usage error if options are combined which cannot be used
+/// together.
+fn check_options(params: &CliParams) {
+    let conflict = |message: String| {
+        CliParams::command()
+            .error(ErrorKind::ArgumentConflict, message)
+            .exit()
+    };
+    let mode = params.mode_option();
+    if params.command.is_some() {
+        let stats = (params.stats || params.stats_only).then_some("--stats");
+        let stats = stats.or(params.histogram.map(|_| "--histogram"));
+        let stats = stats.or((params.jobs != 1).then_some("--jobs"));
+        let date_time = params.date_time.as_ref().map(|_| "[DATE_TIME]");
+        let date_time = date_time.or(params.file.as_ref().map(|_| "--file"));
+        if let Some(option) = date_time.or(mode).or(stats) {
+            conflict(format!("{} cannot be used with a command", option));
+        }
+    }
+    if let (Some(mode), true) = (mode, params.copy.is_some()) {
+        conflict(format!("--copy cannot be used with {}", mode));
+    }
+    if let (Some(mode), true) = (mode, params.stats || params.stats_only) {
+        conflict(format!("--stats cannot be used with {}", mode));
+    }
+    if let (Some(mode), Some(_)) = (mode, params.histogram) {
+        conflict(format!("--histogram cannot be used with {}", mode));
+    }
+    if (params.reverse || params.sort_drop_unparsed) && !params.sort {
+        CliParams::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--reverse and --sort-drop-unparsed require --sort",
+            )
+            .exit();
+    }
+}
+
+/// Parses the command line. Options missing on the command line are taken
+/// from the config file if present there.
+fn parse_params() -> Result<CliParams, ConfigError> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let command = CliParams::command();
+    let matches = command
+        .clone()
+        .try_get_matches_from(&args)
+        .unwrap_or_else(|e| config::env_error(&command, &e).unwrap_or(e).exit());
+    let config_args = config::config_args(&command, &matches)?;
+    if config_args.is_empty() {
+        return Ok(CliParams::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()));
+    }
+    let (name, args) = args.split_at(1);
+    let config_args = config_args.into_iter().map(OsString::from);
+    Ok(CliParams::parse_from(
+        name.iter()
+            .cloned()
+            .chain(config_args)
+            .chain(args.iter().cloned()),
+    ))
+}
+/// Runs the command given by `params`. Returns whether all inputs could be
+/// parsed.
+fn run(
+    params: &CliParams,
+    out: &mut impl Write,
+    errors: &mut impl Write,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let output = params.output_options();
+    if let Some(shell) = params.completions {
+        write_completions(shell, out);
+        return Ok(true);
+    }
+    if params.generate_man {
+        write_man_page(out)?;
+        return Ok(true);
+    }
+    if params.watch {
+        let interval = std::time::Duration::from_millis(params.interval);
+        watch::run(out, interval, &output, stdout().is_terminal())?;
+        return Ok(true);
+    }
+    let detectors = params.parse_options().detectors()?;
+    if let Some(command) = &params.command {
+        return commands::run(command, params, out, errors, &detectors, &output);
+    }
+    if params.sort {
+        modes::sort_input(out, params, &detectors)?;
+        return Ok(true);
+    }
+    if params.is_filter() {
+        modes::filter_input(out, params, &detectors, &output)?;
+        return Ok(true);
+    }
+    if let (true, Some(column)) = (params.csv, &params.column) {
+        return Ok(modes::convert_csv(
+            out, errors, params, column, &detectors, &output,
+        )?);
+    }
+    if let (true, Some(path)) = (params.json_rewrite, &params.json_key) {
+        return Ok(modes::rewrite_json(
+            out, errors, params, path, &detectors, &output,
+        )?);
+    }
+    if params.check {
+        return Ok(modes::check_input(errors, params, &detectors)?);
+    }
+    if params.pair {
+        return Ok(modes::write_pairs(
+            out, errors, params, &detectors, &output,
+        )?);
+    }
+    if params.deltas {
+        return Ok(modes::write_deltas(
+            out, errors, params, &detectors, &output,
+        )?);
+    }
+    #[cfg(feature = "http")]
+    if let Some(url) = &params.url {
+        let response = http::fetch(url)?;
+        return Ok(http::write_response(
+            out,
+            errors,
+            &response,
+            &output,
+            params.is_quiet(),
+        )?);
+    }
+    if !params.mtime.is_empty() {
+        return Ok(stamps::write_stamps(
+            out,
+            errors,
+            &params.mtime,
+            &params.stamps(),
+            &output,
+            params.is_quiet(),
+        )?);
+    }
+    if interactive::is_interactive(
+        params.interactive,
+        params.date_time.is_some() || params.file.is_some(),
+        stdin().is_terminal(),
+    ) {
+        let failed = interactive::run(
+            stdin().lock(),
+            out,
+            errors,
+            &detectors,
+            &output,
+            params.is_quiet(),
+        )?;
+        return Ok(!failed);
+    }
+    modes::convert_inputs(out, errors, params, &detectors, &output)
+}
+
+fn write_completions(shell: Shell, out: &mut impl Write) {
+    clap_complete::generate(shell, &mut CliParams::command(), "datetime", out);
+}
+
+fn write_man_page(out: &mut impl Write) -> std::io::Result<()> {
+    let man = clap_mangen::Man::new(CliParams::command());
+    man.render_title(out)?;
+    man.render_name_section(out)?;
+    man.render_synopsis_section(out)?;
+    man.render_description_section(out)?;
+    man.render_options_section(out)?;
+    writeln!(out, ".SH EXAMPLES")?;
+    writeln!(out, ".nf")?;
+    for line in EXAMPLES.lines().skip(1) {
+        writeln!(
+            out,
+            "{}",
+            line.trim().replace('-', "\\-").replace('\'', "\\(aq")
+        )?;
+    }
+    writeln!(out, ".fi")
+}
+
+fn get_datetime(input: &str, detectors: &Detectors) -> Result<DateTime<Utc>, DateTimeError> {
+    detectors.parse(input).map(|parsed| parsed.datetime)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, ValueEnum};
+    use clap_complete::Shell;
+
+    use crate::{write_completions, write_man_page, CliParams};
 
     #[test]
-    fn epoch_seconds_ending_with_spaces() {
-        let input = "1676550896 ";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896000);
+    fn completions_for_all_shells() {
+        for shell in Shell::value_variants() {
+            let mut out = Vec::new();
+            write_completions(*shell, &mut out);
+            let script = String::from_utf8(out).unwrap();
+            assert!(script.contains("strict"), "{shell}");
+        }
     }
 
     #[test]
-    fn epoch_milliseconds() {
-        let input = "1676550896789";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    fn completions_enumerate_values() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let mut out = Vec::new();
+            write_completions(shell, &mut out);
+            let script = String::from_utf8(out).unwrap();
+            assert!(script.contains("epoch-millis"), "{shell}");
+        }
     }
 
     #[test]
-    fn epoch_milliseconds_starting_with_spaces() {
-        let input = " 1676550896789";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    fn man_page() {
+        let mut out = Vec::new();
+        write_man_page(&mut out).unwrap();
+        let man_page = String::from_utf8(out).unwrap();
+        assert!(man_page.starts_with(".ie \\n(.g .ds Aq \\(aq"));
+        assert!(man_page.contains(".TH datetime 1"));
+        assert!(man_page.contains("epoch\\-cutoff\\-year"));
+        assert!(man_page.contains(".SH EXAMPLES\n.nf\ndatetime 2023\\-02\\-16T12:34:56.789Z\n"));
     }
 
     #[test]
-    fn epoch_milliseconds_ending_with_spaces() {
-        let input = "1676550896789 ";
-        let date_time = get_datetime(input).unwrap();
-        assert_eq!(date_time.timestamp_millis(), 1676550896789);
+    fn all_arguments_have_help() {
+        let command = CliParams::command();
+        let commands = std::iter::once(&command).chain(command.get_subcommands());
+        for command in commands {
+            for arg in command.get_arguments() {
+                let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+                assert!(!help.trim().is_empty(), "{} has no help", arg.get_id());
+            }
+        }
     }
 }
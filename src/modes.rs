@@ -0,0 +1,338 @@
+use std::fs::File;
+use std::io::{stderr, stdin, stdout, BufRead, BufReader, Cursor, IsTerminal, Write};
+
+use chrono::{DateTime, Utc};
+use datetime_helper::{DateTimeError, Detectors};
+
+use crate::column::{self, Column};
+use crate::histogram::Histogram;
+use crate::input::{Lines, Source};
+use crate::json_path::{self, JsonPath};
+use crate::output::{
+    weekday_warning, write_header, write_histogram, write_record, write_stats, OutputField,
+    OutputOptions,
+};
+use crate::progress::Progress;
+use crate::stats::Stats;
+use crate::{clipboard, deltas, interactive, pair, parallel, scan, sort, trace, CliParams};
+
+/// Converts the inputs one by one and prints the results, the statistics or
+/// the histogram, followed by the summary unless a single date time is given
+/// or --silent. Returns whether all inputs could be parsed.
+pub(crate) fn convert_inputs(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    params: &CliParams,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut stats = Stats::default();
+    let mut histogram = params
+        .histogram
+        .map(|bucket| Histogram::new(bucket, output.timezone));
+    let print_results = !params.stats_only && histogram.is_none();
+    // Results are only flushed once the buffer is full, unless someone
+    // watches them appear.
+    let flush_records = stdout().is_terminal();
+    let mut last = None;
+    // Cut off digits are only noted once.
+    let mut noted = params.is_quiet();
+    if print_results {
+        write_header(out, output)?;
+    }
+    let mut lines = input_lines(params)?;
+    let plain = |line: &str| detectors.parse(line);
+    let traced = |line: &str| trace::parse(stderr(), detectors, line, params.verbose > 1);
+    // Traces of parallel threads would be interleaved.
+    let (parse, jobs): (parallel::Parse, usize) = match params.verbose {
+        0 => (&plain, parallel::thread_count(params.jobs)),
+        _ => (&traced, 1),
+    };
+    parallel::parse_lines(&mut lines, parse, jobs, |number, result| {
+        match result {
+            Ok(parsed) => {
+                if print_results {
+                    write_record(out, &parsed, output)?;
+                    if flush_records {
+                        out.flush()?;
+                    }
+                    if let Some(note) = output.precision_note(&parsed).filter(|_| !noted) {
+                        writeln!(errors, "{}", note)?;
+                        noted = true;
+                    }
+                }
+                if let Some(warning) = weekday_warning(&parsed).filter(|_| !params.is_quiet()) {
+                    writeln!(errors, "{}", warning)?;
+                }
+                if let Some(histogram) = &mut histogram {
+                    histogram.add(parsed.datetime);
+                }
+                stats.add(parsed.datetime);
+                last = Some(parsed);
+            }
+            Err(e) => {
+                stats.add_failure(number);
+                if !params.is_quiet() {
+                    match e {
+                        // Other errors quote the input, which cannot be printed.
+                        DateTimeError::InvalidUtf8 => writeln!(errors, "line {}: {}", number, e)?,
+                        _ => writeln!(errors, "{}", e)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    })?;
+    stats.blank = lines.blank();
+    if let Some(histogram) = &histogram {
+        let width = terminal_size::terminal_size().map_or(80, |(width, _)| width.0 as usize);
+        write_histogram(out, &histogram.rows()?, output, width)?;
+    }
+    if params.stats || params.stats_only {
+        write_stats(out, &stats, output)?;
+    }
+    if params.date_time.is_none() && !params.silent {
+        out.flush()?;
+        writeln!(errors, "{}", stats.summary())?;
+    }
+    if let Some(last) = last {
+        clipboard::copy_value(params, errors, |field| field.format(&last, output))?;
+    }
+    Ok(stats.failed == 0)
+}
+
+/// Copies the input to `out` with all date times replaced by or annotated
+/// with their formatted value. Everything else is copied byte by byte.
+pub(crate) fn filter_input(
+    out: &mut impl Write,
+    params: &CliParams,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    let render = |datetime| output.format_value(datetime);
+    let delimiters = match &params.delimiters[..] {
+        [open, close] => (open.as_str(), close.as_str()),
+        _ => unreachable!("clap requires two delimiters"),
+    };
+    let filter = |line: &[u8]| {
+        if params.rewrite {
+            scan::rewrite(line, detectors, render)
+        } else {
+            scan::annotate(line, detectors, render, delimiters, params.annotate_eol)
+        }
+    };
+    if let Some(line) = &params.date_time {
+        out.write_all(&filter(line.as_bytes()))?;
+        return writeln!(out);
+    }
+    let mut lines = Lines::new(input_reader(params)?, params.skip, params.limit);
+    while let Some((_, line)) = lines.next_bytes()? {
+        out.write_all(&filter(line))?;
+    }
+    Ok(())
+}
+
+/// Converts the --column of the CSV input and prints the summary unless
+/// --silent is given. Returns whether all cells could be parsed.
+pub(crate) fn convert_csv(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    params: &CliParams,
+    column: &Column,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> std::io::Result<bool> {
+    let render = |datetime| output.format_value(datetime);
+    let stats = column::convert_column(
+        input_reader(params)?,
+        out,
+        errors,
+        column,
+        detectors,
+        render,
+        params.is_quiet(),
+    )?;
+    if !params.silent {
+        out.flush()?;
+        writeln!(errors, "{}", stats.summary())?;
+    }
+    Ok(stats.failed == 0)
+}
+
+/// Replaces the date times at the --json-key of the JSON input lines and
+/// prints the summary unless --silent is given. Epoch seconds and
+/// milliseconds of --only are written as numbers, including prom_ms.
+/// Returns whether all values could be parsed.
+pub(crate) fn rewrite_json(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    params: &CliParams,
+    path: &JsonPath,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> std::io::Result<bool> {
+    let render = |datetime: DateTime<Utc>| match (&output.time_format, output.only) {
+        (None, Some(OutputField::Seconds)) => datetime.timestamp().into(),
+        (None, Some(OutputField::Millis | OutputField::PromMs)) => {
+            datetime.timestamp_millis().into()
+        }
+        _ => output.format_value(datetime).into(),
+    };
+    let mut lines = Lines::new(input_reader(params)?, params.skip, params.limit);
+    let stats = json_path::rewrite_lines(
+        &mut lines,
+        out,
+        errors,
+        path,
+        detectors,
+        render,
+        params.is_quiet(),
+    )?;
+    if !params.silent {
+        out.flush()?;
+        writeln!(errors, "{}", stats.summary())?;
+    }
+    Ok(stats.failed == 0)
+}
+
+/// Writes the date times and drifts of --pair and prints the summary unless
+/// --silent is given. Returns whether all lines had two date times.
+pub(crate) fn write_pairs(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    params: &CliParams,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> std::io::Result<bool> {
+    let stats = pair::write_pairs(
+        &mut input_lines(params)?,
+        out,
+        errors,
+        params.pair_delimiter.as_deref(),
+        detectors,
+        output,
+        params.is_quiet(),
+    )?;
+    if !params.silent {
+        out.flush()?;
+        writeln!(errors, "{}", stats.summary())?;
+    }
+    Ok(stats.failed == 0)
+}
+
+/// Writes the deltas of --deltas and prints the summary unless --silent is
+/// given. Returns whether all lines could be parsed.
+pub(crate) fn write_deltas(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    params: &CliParams,
+    detectors: &Detectors,
+    output: &OutputOptions,
+) -> std::io::Result<bool> {
+    let stats = deltas::write_deltas(
+        &mut input_lines(params)?,
+        out,
+        errors,
+        detectors,
+        output,
+        params.deltas_reset_on_error,
+        params.is_quiet(),
+    )?;
+    if !params.silent {
+        out.flush()?;
+        writeln!(errors, "{}", stats.summary())?;
+    }
+    Ok(stats.failed == 0)
+}
+
+/// Writes all input lines sorted by their date times. Each written line ends
+/// with a line break, even if the last input line did not.
+pub(crate) fn sort_input(
+    out: &mut impl Write,
+    params: &CliParams,
+    detectors: &Detectors,
+) -> std::io::Result<()> {
+    let lines = match &params.date_time {
+        Some(line) => params
+            .window([line.as_bytes().to_vec()].into_iter())
+            .collect(),
+        None => params
+            .window(input_reader(params)?.split(b'\n'))
+            .collect::<Result<_, _>>()?,
+    };
+    let sorted = sort::sort_lines(lines, detectors, params.reverse, params.sort_drop_unparsed);
+    for line in sorted {
+        out.write_all(&line)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Opens the file given by --file or standard input. Before reading from a
+/// terminal, a hint is printed unless --silent is given. If standard error is
+/// a terminal, the progress of reading is shown unless --quiet is given.
+fn input_reader(params: &CliParams) -> std::io::Result<Source> {
+    let show_progress = stderr().is_terminal() && !params.is_quiet();
+    if let Some(path) = &params.file {
+        let file = File::open(path).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let size = file.metadata().ok().map(|metadata| metadata.len());
+        let source = Source::File(BufReader::new(file));
+        return Ok(match show_progress {
+            true => Source::Progress(Box::new(Progress::new(source, size, stderr()))),
+            false => source,
+        });
+    }
+    let hint = interactive::stdin_hint(false, stdin().is_terminal());
+    if let (Some(hint), false) = (hint, params.silent) {
+        eprintln!("{}", hint);
+    }
+    let source = Source::Stdin(stdin().lock());
+    // Typed input is not worth a spinner.
+    Ok(match show_progress && !stdin().is_terminal() {
+        true => Source::Progress(Box::new(Progress::new(source, None, stderr()))),
+        false => source,
+    })
+}
+
+/// The lines to parse. Blank lines of standard input or a file are skipped,
+/// unless all inputs are validated with --check or --strict.
+fn input_lines(params: &CliParams) -> std::io::Result<Lines<Source>> {
+    let lines = match &params.date_time {
+        Some(date_time) => {
+            let source = Source::Argument(Cursor::new(date_time.clone()));
+            return Ok(Lines::new(source, params.skip, params.limit));
+        }
+        None => Lines::new(input_reader(params)?, params.skip, params.limit),
+    };
+    if params.check || !params.strict.is_empty() {
+        Ok(lines)
+    } else {
+        Ok(lines.skip_blank())
+    }
+}
+
+/// Parses all inputs without printing any results. With --verbose, each
+/// failure is reported to `errors` with its line number. Returns whether all
+/// inputs could be parsed.
+pub(crate) fn check_input(
+    errors: &mut impl Write,
+    params: &CliParams,
+    detectors: &Detectors,
+) -> std::io::Result<bool> {
+    let mut valid = true;
+    let jobs = parallel::thread_count(params.jobs);
+    let mut lines = input_lines(params)?;
+    let parse = |line: &str| detectors.parse(line);
+    parallel::parse_lines(&mut lines, &parse, jobs, |number, result| {
+        if let Err(e) = result {
+            valid = false;
+            if params.verbose > 0 {
+                writeln!(errors, "line {}: {}", number, e)?;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(valid)
+}
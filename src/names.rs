@@ -0,0 +1,126 @@
+use chrono::{Month, Weekday};
+
+/// The names of the months, starting with January.
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// The names of the weekdays, starting with Monday like [Weekday].
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The month named by `token`, which is its full name or the first three
+/// letters of it in any case, like `Feb`, `FEB` or `february`.
+pub(crate) fn month(token: &str) -> Option<Month> {
+    let index = find(&MONTHS, token)?;
+    Month::try_from(u8::try_from(index).ok()? + 1).ok()
+}
+
+/// The weekday named by `token`, which is its full name or the first three
+/// letters of it in any case, like `Thu`, `THU` or `thursday`.
+pub(crate) fn weekday(token: &str) -> Option<Weekday> {
+    let index = find(&WEEKDAYS, token)?;
+    Weekday::try_from(u8::try_from(index).ok()?).ok()
+}
+
+/// The abbreviation of `month` as written by RFC 2822, like `Feb`.
+pub(crate) fn month_abbreviation(month: Month) -> &'static str {
+    &MONTHS[month.number_from_month() as usize - 1][..3]
+}
+
+/// The full name of `weekday`, like `Thursday`.
+#[cfg(feature = "cli")]
+pub(crate) fn weekday_name(weekday: Weekday) -> &'static str {
+    WEEKDAYS[weekday.num_days_from_monday() as usize]
+}
+
+/// Splits a leading weekday like `Thu,` off `input`, returning it and the
+/// rest without surrounding whitespace.
+pub(crate) fn split_weekday(input: &str) -> Option<(Weekday, &str)> {
+    let (token, rest) = input.split_once(',')?;
+    Some((weekday(token.trim())?, rest.trim()))
+}
+
+fn find(names: &[&str], token: &str) -> Option<usize> {
+    names
+        .iter()
+        .position(|name| token.eq_ignore_ascii_case(name) || token.eq_ignore_ascii_case(&name[..3]))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Month, Weekday};
+
+    use crate::names::{month, month_abbreviation, split_weekday, weekday};
+
+    #[test]
+    fn months_in_any_case() {
+        for token in [
+            "Feb", "feb", "FEB", "fEb", "February", "february", "FEBRUARY",
+        ] {
+            assert_eq!(month(token), Some(Month::February), "{token}");
+        }
+        assert_eq!(month("Jan"), Some(Month::January));
+        assert_eq!(month("dec"), Some(Month::December));
+        assert_eq!(month("Sept"), None);
+        assert_eq!(month("Fe"), None);
+        assert_eq!(month("Febr"), None);
+        assert_eq!(month(""), None);
+        assert_eq!(month("Thu"), None);
+    }
+
+    #[test]
+    fn weekdays_in_any_case() {
+        for token in ["Thu", "thu", "THU", "Thursday", "thursday", "THURSDAY"] {
+            assert_eq!(weekday(token), Some(Weekday::Thu), "{token}");
+        }
+        assert_eq!(weekday("Mon"), Some(Weekday::Mon));
+        assert_eq!(weekday("sunday"), Some(Weekday::Sun));
+        assert_eq!(weekday("Thurs"), None);
+        assert_eq!(weekday("Feb"), None);
+    }
+
+    #[test]
+    fn names_of_months_and_weekdays() {
+        assert_eq!(month_abbreviation(Month::January), "Jan");
+        assert_eq!(month_abbreviation(Month::September), "Sep");
+        #[cfg(feature = "cli")]
+        {
+            use crate::names::weekday_name;
+            assert_eq!(weekday_name(Weekday::Mon), "Monday");
+            assert_eq!(weekday_name(Weekday::Sun), "Sunday");
+        }
+    }
+
+    #[test]
+    fn leading_weekdays_are_split_off() {
+        assert_eq!(
+            split_weekday("Thu, 16 Feb 2023 12:34:56 +0000"),
+            Some((Weekday::Thu, "16 Feb 2023 12:34:56 +0000"))
+        );
+        assert_eq!(
+            split_weekday("wednesday ,16 Feb 2023"),
+            Some((Weekday::Wed, "16 Feb 2023"))
+        );
+        assert_eq!(split_weekday("16 Feb 2023 12:34:56 +0000"), None);
+        assert_eq!(split_weekday("Foo, 16 Feb 2023"), None);
+    }
+}
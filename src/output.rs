@@ -0,0 +1,1785 @@
+use std::io::Write;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Offset, SecondsFormat, TimeZone,
+    Timelike, Utc,
+};
+use chrono_tz::Tz;
+use serde_json::json;
+
+use crate::{names, protobuf, DateTimeError, ParsedDateTime};
+
+use crate::duration::{format_breakdown, format_iso, humanize, Age};
+use crate::stats::{Durations, Stats};
+use crate::zone::{Transition, ZoneInfo, ZoneOffset};
+
+/// Options controlling how parsed date times are printed.
+#[derive(Clone, Default)]
+pub struct OutputOptions {
+    /// The single value to print instead of the table.
+    pub only: Option<OutputField>,
+    /// The format of the printed results.
+    pub format: OutputFormat,
+    /// A strftime format to print date times with instead of the table.
+    pub time_format: Option<String>,
+    /// The time zone of printed date times, UTC if not given.
+    pub timezone: Option<Tz>,
+    /// The fractional digits of printed ISO 8601 timestamps.
+    pub precision: Precision,
+    /// How the weeks of [OutputField::Week] are numbered.
+    pub week_rule: WeekRule,
+}
+
+impl OutputOptions {
+    /// Formats `datetime` as a single value: with the strftime format if
+    /// given, otherwise as the selected field or as ISO 8601 timestamp.
+    pub fn format_value(&self, datetime: DateTime<Utc>) -> String {
+        match (&self.time_format, self.only) {
+            (Some(time_format), _) => match local(datetime, self.timezone.unwrap_or(Tz::UTC)) {
+                Some(local) => local.format(time_format).to_string(),
+                None => OUT_OF_RANGE.to_string(),
+            },
+            (None, Some(field)) => field.format(&datetime.into(), self),
+            (None, None) => self.iso(datetime),
+        }
+    }
+
+    /// A note on the fractional digits of a parsed date time cut off by the
+    /// selected precision, `None` if they are printed or the ISO 8601
+    /// timestamp is not. JSON results never cut off digits.
+    pub fn precision_note(&self, parsed: &ParsedDateTime) -> Option<String> {
+        let iso_printed = match (&self.time_format, self.only) {
+            (Some(_), _) => false,
+            (None, Some(field)) => field == OutputField::Iso,
+            (None, None) => !matches!(self.format, OutputFormat::Json | OutputFormat::Protobuf),
+        };
+        let nanos = parsed.datetime.nanosecond();
+        if !iso_printed || self.precision.prints_all_digits(nanos) {
+            return None;
+        }
+        let unit = match nanos % 1_000 {
+            0 => "microsecond",
+            _ => "nanosecond",
+        };
+        Some(format!(
+            "note: \"{}\" has {} precision, which --precision {} cuts off, see --precision auto",
+            parsed.input,
+            unit,
+            self.precision.name()
+        ))
+    }
+
+    /// Formats `datetime` as ISO 8601 timestamp in the selected time zone.
+    fn iso(&self, datetime: DateTime<Utc>) -> String {
+        self.iso_with(datetime, self.precision)
+    }
+
+    fn iso_with(&self, datetime: DateTime<Utc>, precision: Precision) -> String {
+        let format = precision.seconds_format();
+        match self.timezone {
+            Some(timezone) => match local(datetime, timezone) {
+                Some(local) => local.to_rfc3339_opts(format, true),
+                None => OUT_OF_RANGE.to_string(),
+            },
+            None => datetime.to_rfc3339_opts(format, true),
+        }
+    }
+}
+
+/// Printed instead of a date time whose local time cannot be represented.
+const OUT_OF_RANGE: &str = "out of range";
+
+/// `datetime` in `timezone`, `None` if its local time is beyond the range of
+/// chrono, which happens within a day of its first and last date time.
+fn local(datetime: DateTime<Utc>, timezone: Tz) -> Option<DateTime<Tz>> {
+    let offset = timezone.offset_from_utc_datetime(&datetime.naive_utc());
+    datetime.naive_utc().checked_add_offset(offset.fix())?;
+    Some(datetime.with_timezone(&timezone))
+}
+
+/// Checks that `input` is a valid strftime format like `%H:%M:%S`.
+pub fn parse_time_format(input: &str) -> Result<String, DateTimeError> {
+    if StrftimeItems::new(input).any(|item| item == Item::Error) {
+        Err(DateTimeError::InvalidFormat(input.to_string()))
+    } else {
+        Ok(input.to_string())
+    }
+}
+
+/// A value which can be printed on its own with `--only`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputField {
+    /// ISO 8601 timestamp or duration
+    Iso,
+    /// Epoch seconds or seconds of a duration
+    Seconds,
+    /// Epoch milliseconds or milliseconds of a duration
+    Millis,
+    /// Epoch milliseconds as used by Prometheus, like `1676550896789`
+    #[cfg_attr(feature = "cli", value(name = "prom_ms"))]
+    PromMs,
+    /// Epoch seconds with three decimals as used by Prometheus, like
+    /// `1676550896.789`
+    #[cfg_attr(feature = "cli", value(name = "prom_s"))]
+    PromS,
+    /// Week of the year by --week-rule like `2023-W07`, or whole weeks of a duration
+    Week,
+    /// Local time for `touch -t` like `202302161234.56`, cut off to seconds, or ISO 8601 duration
+    Touch,
+    /// RFC 3339 timestamp with all digits which GNU `date -d` reads back, or ISO 8601 duration
+    #[cfg_attr(feature = "cli", value(name = "gnu-date"))]
+    GnuDate,
+}
+
+impl OutputField {
+    fn name(self) -> &'static str {
+        match self {
+            OutputField::Iso => "iso",
+            OutputField::Seconds => "epoch_seconds",
+            OutputField::Millis => "epoch_millis",
+            OutputField::PromMs => "prom_ms",
+            OutputField::PromS => "prom_s",
+            OutputField::Week => "week",
+            OutputField::Touch => "touch",
+            OutputField::GnuDate => "gnu_date",
+        }
+    }
+
+    pub fn format(self, parsed: &ParsedDateTime, output: &OutputOptions) -> String {
+        match self {
+            OutputField::Iso => output.iso(parsed.datetime),
+            OutputField::Seconds => parsed.epoch_seconds().to_string(),
+            OutputField::Millis | OutputField::PromMs => parsed.epoch_millis().to_string(),
+            OutputField::PromS => prom_seconds(parsed.epoch_millis()),
+            OutputField::Week => match local(parsed.datetime, output.timezone.unwrap_or(Tz::UTC)) {
+                Some(local) => output.week_rule.format(local.date_naive()),
+                None => OUT_OF_RANGE.to_string(),
+            },
+            OutputField::Touch => match shell_local(parsed.datetime, output.timezone) {
+                Some(local) => format_touch(local),
+                None => OUT_OF_RANGE.to_string(),
+            },
+            OutputField::GnuDate => match shell_local(parsed.datetime, output.timezone) {
+                Some(local) => format_gnu_date(local),
+                None => OUT_OF_RANGE.to_string(),
+            },
+        }
+    }
+
+    pub fn format_duration(self, duration: Duration) -> String {
+        match self {
+            OutputField::Iso => format_iso(duration),
+            OutputField::Seconds => duration.num_seconds().to_string(),
+            OutputField::Millis | OutputField::PromMs => duration.num_milliseconds().to_string(),
+            OutputField::PromS => prom_seconds(duration.num_milliseconds()),
+            OutputField::Week => duration.num_weeks().to_string(),
+            OutputField::Touch | OutputField::GnuDate => format_iso(duration),
+        }
+    }
+}
+
+/// `datetime` in `timezone` or UTC for the tools of the shell, `None` unless
+/// its year has four digits, as `touch` and `date` do not read others.
+fn shell_local(datetime: DateTime<Utc>, timezone: Option<Tz>) -> Option<DateTime<Tz>> {
+    local(datetime, timezone.unwrap_or(Tz::UTC)).filter(|local| (0..=9999).contains(&local.year()))
+}
+
+/// Formats `local` for `touch -t`, cut off to seconds.
+fn format_touch(local: DateTime<Tz>) -> String {
+    local.format("%Y%m%d%H%M.%S").to_string()
+}
+
+/// Formats `local` as RFC 3339 timestamp with all digits for GNU `date -d`.
+fn format_gnu_date(local: DateTime<Tz>) -> String {
+    local.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Formats `millis` as seconds with exactly three decimals, never in
+/// scientific notation like a float might be.
+fn prom_seconds(millis: i64) -> String {
+    let sign = if millis < 0 { "-" } else { "" };
+    let millis = millis.unsigned_abs();
+    format!("{}{}.{:03}", sign, millis / 1000, millis % 1000)
+}
+
+/// How the weeks of a year are numbered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum WeekRule {
+    /// ISO 8601 weeks starting on Monday, week 1 has the first Thursday, so days around January 1 may belong to the
+    /// adjacent year
+    #[default]
+    Iso,
+    /// Weeks starting on Sunday, week 1 has January 1, as in the US
+    Sunday,
+    /// Seven days each from January 1 on, whatever the weekday
+    MondaySimple,
+}
+
+impl WeekRule {
+    /// The year and the number of the week of `date`.
+    fn week(self, date: NaiveDate) -> (i32, u32) {
+        match self {
+            WeekRule::Iso => (date.iso_week().year(), date.iso_week().week()),
+            WeekRule::Sunday => {
+                let january_1 = date.with_ordinal(1).unwrap();
+                let offset = january_1.weekday().num_days_from_sunday();
+                (date.year(), (date.ordinal0() + offset) / 7 + 1)
+            }
+            WeekRule::MondaySimple => (date.year(), date.ordinal0() / 7 + 1),
+        }
+    }
+
+    /// Formats the week of `date` like `2023-W07`.
+    fn format(self, date: NaiveDate) -> String {
+        let (year, week) = self.week(date);
+        format!("{}-W{:02}", year, week)
+    }
+}
+
+/// The fractional digits of printed ISO 8601 timestamps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Precision {
+    /// As many groups of three digits as needed, none for full seconds
+    Auto,
+    /// No fractional digits
+    S,
+    /// Milliseconds
+    #[default]
+    Ms,
+    /// Microseconds
+    Us,
+    /// Nanoseconds
+    Ns,
+}
+
+impl Precision {
+    fn name(self) -> &'static str {
+        match self {
+            Precision::Auto => "auto",
+            Precision::S => "s",
+            Precision::Ms => "ms",
+            Precision::Us => "us",
+            Precision::Ns => "ns",
+        }
+    }
+
+    /// Whether the fractional digits of this precision include all digits of
+    /// `nanos`.
+    fn prints_all_digits(self, nanos: u32) -> bool {
+        let per_digit = match self {
+            Precision::Auto | Precision::Ns => 1,
+            Precision::S => 1_000_000_000,
+            Precision::Ms => 1_000_000,
+            Precision::Us => 1_000,
+        };
+        nanos.is_multiple_of(per_digit)
+    }
+
+    fn seconds_format(self) -> SecondsFormat {
+        match self {
+            Precision::Auto => SecondsFormat::AutoSi,
+            Precision::S => SecondsFormat::Secs,
+            Precision::Ms => SecondsFormat::Millis,
+            Precision::Us => SecondsFormat::Micros,
+            Precision::Ns => SecondsFormat::Nanos,
+        }
+    }
+}
+
+/// The format of the printed results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// A table for humans
+    #[default]
+    Table,
+    /// One JSON object per result
+    Json,
+    /// Comma separated values with a header line
+    Csv,
+    /// The JSON mapping of a protobuf timestamp or duration followed by its
+    /// seconds and nanos fields
+    Protobuf,
+}
+
+/// A value in a table row. Numbers are aligned to the right.
+enum Cell {
+    Text(String),
+    Number(i64),
+}
+
+/// Writes a single date time. CSV output starts with a header line.
+pub fn write_time(
+    out: &mut impl Write,
+    parsed: &ParsedDateTime,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    write_header(out, output)?;
+    write_record(out, parsed, output)
+}
+
+/// Writes the CSV header line if CSV output is selected. It has to be
+/// written once before all records.
+pub fn write_header(out: &mut impl Write, output: &OutputOptions) -> std::io::Result<()> {
+    if output.format == OutputFormat::Csv {
+        writeln!(out, "{}", csv_header(output))?;
+    }
+    Ok(())
+}
+
+/// Writes one of possibly multiple date times.
+pub fn write_record(
+    out: &mut impl Write,
+    parsed: &ParsedDateTime,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_output(parsed, output).as_bytes())
+}
+
+/// Writes the CSV header line of [write_labeled] records, whose first
+/// columns are `names`, if CSV output is selected.
+pub fn write_labeled_header(
+    out: &mut impl Write,
+    names: &[&str],
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    if output.format == OutputFormat::Csv {
+        writeln!(out, "{},{}", names.join(","), csv_header(output))?;
+    }
+    Ok(())
+}
+
+/// Writes the date time of a source named by `labels`, like the path and the
+/// kind of a file timestamp, or the error why there is none. Tables follow
+/// a line of the labels, while JSON objects and CSV records start with them.
+/// Single values of `--only` and `--format` follow the labels on the same
+/// line.
+pub fn write_labeled(
+    out: &mut impl Write,
+    labels: &[(&str, &str)],
+    result: Result<&ParsedDateTime, &str>,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    let single = output.only.is_some() || output.time_format.is_some();
+    match (output.format, result) {
+        (OutputFormat::Csv, result) => {
+            let mut fields: Vec<String> =
+                labels.iter().map(|(_, value)| csv_field(value)).collect();
+            match result {
+                Ok(parsed) => fields.push(format_output(parsed, output).trim_end().to_string()),
+                Err(_) => fields.push(",".repeat(csv_header(output).matches(',').count())),
+            }
+            writeln!(out, "{}", fields.join(","))
+        }
+        (_, result) if single => {
+            let labels: Vec<&str> = labels.iter().map(|(_, value)| *value).collect();
+            match result {
+                Ok(parsed) => write!(
+                    out,
+                    "{}: {}",
+                    labels.join(" "),
+                    format_output(parsed, output)
+                ),
+                Err(e) => writeln!(out, "{}: {}", labels.join(" "), e),
+            }
+        }
+        (OutputFormat::Json, result) => {
+            let mut json = serde_json::Map::new();
+            for (name, value) in labels {
+                json.insert(name.to_string(), (*value).into());
+            }
+            match result {
+                Ok(parsed) => {
+                    if let serde_json::Value::Object(fields) = format_json(parsed, output) {
+                        json.extend(fields);
+                    }
+                }
+                Err(e) => {
+                    json.insert("error".to_string(), e.into());
+                }
+            }
+            writeln!(out, "{}", serde_json::Value::Object(json))
+        }
+        (_, result) => {
+            let labels: Vec<&str> = labels.iter().map(|(_, value)| *value).collect();
+            writeln!(out, "{}:", labels.join(" "))?;
+            match result {
+                Ok(parsed) => out.write_all(format_output(parsed, output).as_bytes()),
+                Err(e) => writeln!(out, "{}", e),
+            }
+        }
+    }
+}
+
+/// Quotes `value` for CSV if needed.
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// A warning on a weekday in the input which does not match its date, which
+/// was parsed ignoring the weekday.
+pub fn weekday_warning(parsed: &ParsedDateTime) -> Option<String> {
+    let (given, date) = parsed.weekday_mismatch()?;
+    Some(format!(
+        "warning: \"{}\" says {}, but {} is a {}",
+        parsed.input,
+        names::weekday_name(given),
+        date,
+        names::weekday_name(date.weekday())
+    ))
+}
+
+fn csv_header(output: &OutputOptions) -> String {
+    match (&output.time_format, output.only) {
+        (Some(_), _) => "formatted".to_string(),
+        (None, Some(field)) => field.name().to_string(),
+        (None, None) => FIELDS.map(OutputField::name).join(","),
+    }
+}
+
+/// The fields of CSV records, which are also those of JSON objects.
+const FIELDS: [OutputField; 5] = [
+    OutputField::Iso,
+    OutputField::Seconds,
+    OutputField::Millis,
+    OutputField::Touch,
+    OutputField::GnuDate,
+];
+
+pub fn format_output(parsed: &ParsedDateTime, output: &OutputOptions) -> String {
+    if output.time_format.is_some() {
+        return output.format_value(parsed.datetime) + "\n";
+    }
+    match (output.only, output.format) {
+        (Some(field), _) => field.format(parsed, output) + "\n",
+        (None, OutputFormat::Table) => format_time(parsed, output),
+        (None, OutputFormat::Json) => format_json(parsed, output).to_string() + "\n",
+        (None, OutputFormat::Csv) => {
+            // Left empty like in JSON for years which the shell tools do not read.
+            let shell = shell_local(parsed.datetime, output.timezone).is_some();
+            let format = |field| match field {
+                OutputField::Touch | OutputField::GnuDate if !shell => String::new(),
+                field => OutputField::format(field, parsed, output),
+            };
+            FIELDS.map(format).join(",") + "\n"
+        }
+        (None, OutputFormat::Protobuf) => format!(
+            "{} {}\n",
+            protobuf::timestamp_json(parsed.datetime),
+            protobuf::timestamp_fields(parsed.datetime)
+        ),
+    }
+}
+
+pub fn format_time(parsed: &ParsedDateTime, output: &OutputOptions) -> String {
+    let mut rows = vec![
+        (
+            "ISO 8601 timestamp",
+            Cell::Text(output.iso(parsed.datetime)),
+        ),
+        ("Epoch seconds", Cell::Number(parsed.epoch_seconds())),
+        ("Epoch milliseconds", Cell::Number(parsed.epoch_millis())),
+    ];
+    if let Some(local) = original_local(parsed, output) {
+        rows.push(("Original local time", Cell::Text(local)));
+    }
+    format_table(&rows)
+}
+
+/// The local time of an input with an offset other than UTC.
+fn original_local(parsed: &ParsedDateTime, output: &OutputOptions) -> Option<String> {
+    let local = parsed.original_local()?;
+    Some(local.to_rfc3339_opts(output.precision.seconds_format(), true))
+}
+
+/// The ISO 8601 timestamp is printed with as many digits as needed if the
+/// selected precision would cut some off.
+fn format_json(parsed: &ParsedDateTime, output: &OutputOptions) -> serde_json::Value {
+    let precision = match output
+        .precision
+        .prints_all_digits(parsed.datetime.nanosecond())
+    {
+        true => output.precision,
+        false => Precision::Auto,
+    };
+    let mut json = json!({
+        "iso": output.iso_with(parsed.datetime, precision),
+        "epoch_seconds": parsed.epoch_seconds(),
+        "epoch_millis": parsed.epoch_millis(),
+    });
+    // Left out for years which the tools of the shell do not read.
+    if let Some(local) = shell_local(parsed.datetime, output.timezone) {
+        json["touch"] = format_touch(local).into();
+        json["gnu_date"] = format_gnu_date(local).into();
+    }
+    if let Some(local) = parsed.original_local() {
+        json["original_offset"] = local.offset().to_string().into();
+        json["original_local"] = local
+            .to_rfc3339_opts(precision.seconds_format(), true)
+            .into();
+    }
+    json
+}
+
+/// Writes the duration between `from` and `to`.
+pub fn write_duration(
+    out: &mut impl Write,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_duration_output(from, to, output).as_bytes())
+}
+
+fn format_duration_output(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    output: &OutputOptions,
+) -> String {
+    let duration = to - from;
+    match (output.only, output.format) {
+        (Some(field), OutputFormat::Csv) => {
+            format!("{}\n{}\n", field.name(), field.format_duration(duration))
+        }
+        (Some(field), _) => field.format_duration(duration) + "\n",
+        (None, OutputFormat::Table) => format_table(&[
+            ("From", Cell::Text(output.iso(from))),
+            ("To", Cell::Text(output.iso(to))),
+            ("Seconds", Cell::Number(duration.num_seconds())),
+            ("Milliseconds", Cell::Number(duration.num_milliseconds())),
+            ("Duration", Cell::Text(format_breakdown(duration))),
+            ("Humanized", Cell::Text(humanize(duration))),
+        ]),
+        (None, OutputFormat::Csv) => {
+            let header = "from,to,seconds,millis,iso,duration,humanized";
+            let row = [
+                output.iso(from),
+                output.iso(to),
+                duration.num_seconds().to_string(),
+                duration.num_milliseconds().to_string(),
+                format_iso(duration),
+                format_breakdown(duration),
+                format!("\"{}\"", humanize(duration)),
+            ];
+            format!("{}\n{}\n", header, row.join(","))
+        }
+        (None, OutputFormat::Json) => {
+            let json = json!({
+                "from": format_json(&from.into(), output),
+                "to": format_json(&to.into(), output),
+                "seconds": duration.num_seconds(),
+                "millis": duration.num_milliseconds(),
+                "iso": format_iso(duration),
+                "duration": format_breakdown(duration),
+                "humanized": humanize(duration),
+            });
+            json.to_string() + "\n"
+        }
+        (None, OutputFormat::Protobuf) => format!(
+            "{} {}\n",
+            protobuf::duration_json(duration),
+            protobuf::duration_fields(duration)
+        ),
+    }
+}
+
+/// Writes the `age` of `from` at `at`, which are printed as given. The
+/// components are negative in the future. Hours, minutes and seconds are
+/// left out for the `dates` of dates without a time.
+pub fn write_age(
+    out: &mut impl Write,
+    from: &str,
+    at: &str,
+    age: &Age,
+    dates: bool,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_age(from, at, age, dates, output).as_bytes())
+}
+
+fn format_age(from: &str, at: &str, age: &Age, dates: bool, output: &OutputOptions) -> String {
+    let sign = if age.future { -1 } else { 1 };
+    let mut components = vec![
+        ("Years", "years", age.years),
+        ("Months", "months", age.months),
+        ("Days", "days", age.days),
+    ];
+    if !dates {
+        components.extend([
+            ("Hours", "hours", age.hours),
+            ("Minutes", "minutes", age.minutes),
+            ("Seconds", "seconds", age.seconds),
+        ]);
+    }
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let mut rows = vec![
+                ("From", Cell::Text(from.to_string())),
+                ("At", Cell::Text(at.to_string())),
+                ("Age", Cell::Text(age.humanize())),
+            ];
+            rows.extend(
+                components
+                    .iter()
+                    .map(|(label, _, value)| (*label, Cell::Number(sign * value))),
+            );
+            format_table(&rows)
+        }
+        OutputFormat::Json => {
+            let mut json = serde_json::Map::new();
+            json.insert("from".to_string(), from.into());
+            json.insert("at".to_string(), at.into());
+            for (_, name, value) in &components {
+                json.insert(name.to_string(), (sign * value).into());
+            }
+            json.insert("future".to_string(), age.future.into());
+            serde_json::Value::Object(json).to_string() + "\n"
+        }
+        OutputFormat::Csv => {
+            let mut header = vec!["from", "at"];
+            let mut row = vec![from.to_string(), at.to_string()];
+            for (_, name, value) in &components {
+                header.push(name);
+                row.push((sign * value).to_string());
+            }
+            format!("{}\n{}\n", header.join(","), row.join(","))
+        }
+    }
+}
+
+/// Writes the number of workdays from `from` to `to`.
+pub fn write_workdays(
+    out: &mut impl Write,
+    from: NaiveDate,
+    to: NaiveDate,
+    workdays: i64,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_workdays(from, to, workdays, output).as_bytes())
+}
+
+fn format_workdays(
+    from: NaiveDate,
+    to: NaiveDate,
+    workdays: i64,
+    output: &OutputOptions,
+) -> String {
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => format_table(&[
+            ("From", Cell::Text(from.to_string())),
+            ("To", Cell::Text(to.to_string())),
+            ("Workdays", Cell::Number(workdays)),
+        ]),
+        OutputFormat::Json => {
+            let json = json!({
+                "from": from.to_string(),
+                "to": to.to_string(),
+                "workdays": workdays,
+            });
+            json.to_string() + "\n"
+        }
+        OutputFormat::Csv => format!("from,to,workdays\n{},{},{}\n", from, to, workdays),
+    }
+}
+
+/// Writes the offset of a time zone at an instant and its transitions.
+pub fn write_zone_info(
+    out: &mut impl Write,
+    info: &ZoneInfo,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_zone_info(info, output).as_bytes())
+}
+
+fn format_zone_info(info: &ZoneInfo, output: &OutputOptions) -> String {
+    let local = info
+        .at
+        .with_timezone(&info.timezone)
+        .to_rfc3339_opts(output.precision.seconds_format(), true);
+    let offset = &info.offset;
+    let yes_no = |dst: bool| if dst { "yes" } else { "no" }.to_string();
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let transition = |transition: &Option<Transition>| {
+                Cell::Text(match transition {
+                    Some(transition) => format!(
+                        "{}, {} {} to {} {}",
+                        output.format_value(transition.at),
+                        transition.before.utc_offset,
+                        transition.before.abbreviation,
+                        transition.after.utc_offset,
+                        transition.after.abbreviation
+                    ),
+                    None => "no transitions".to_string(),
+                })
+            };
+            format_table(&[
+                ("Time zone", Cell::Text(info.timezone.name().to_string())),
+                ("Instant", Cell::Text(output.format_value(info.at))),
+                ("Local time", Cell::Text(local)),
+                ("UTC offset", Cell::Text(offset.utc_offset.to_string())),
+                ("Abbreviation", Cell::Text(offset.abbreviation.clone())),
+                ("DST", Cell::Text(yes_no(offset.is_dst()))),
+                ("Previous transition", transition(&info.previous)),
+                ("Next transition", transition(&info.next)),
+            ])
+        }
+        OutputFormat::Json => {
+            let transition = |transition: &Option<Transition>| {
+                transition
+                    .as_ref()
+                    .map(|transition| transition_json(transition, output))
+            };
+            let json = json!({
+                "timezone": info.timezone.name(),
+                "at": output.format_value(info.at),
+                "local": local,
+                "utc_offset": offset.utc_offset.to_string(),
+                "utc_offset_seconds": offset.utc_offset.local_minus_utc(),
+                "abbreviation": offset.abbreviation,
+                "dst": offset.is_dst(),
+                "previous": transition(&info.previous),
+                "next": transition(&info.next),
+            });
+            json.to_string() + "\n"
+        }
+        OutputFormat::Csv => {
+            let transition = |transition: &Option<Transition>| match transition {
+                Some(transition) => [
+                    output.format_value(transition.at),
+                    transition.before.utc_offset.to_string(),
+                    transition.after.utc_offset.to_string(),
+                ],
+                None => Default::default(),
+            };
+            let header = "timezone,at,local,utc_offset,abbreviation,dst,\
+                          previous,previous_before,previous_after,next,next_before,next_after";
+            let row = [
+                vec![
+                    info.timezone.name().to_string(),
+                    output.format_value(info.at),
+                    local,
+                    offset.utc_offset.to_string(),
+                    offset.abbreviation.clone(),
+                    yes_no(offset.is_dst()),
+                ],
+                transition(&info.previous).to_vec(),
+                transition(&info.next).to_vec(),
+            ]
+            .concat();
+            format!("{}\n{}\n", header, row.join(","))
+        }
+    }
+}
+
+/// Writes a transition of the time zone `timezone` with the jump of the
+/// wall clock.
+pub fn write_transition(
+    out: &mut impl Write,
+    timezone: Tz,
+    transition: &Transition,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_transition(timezone, transition, output).as_bytes())
+}
+
+fn format_transition(timezone: Tz, transition: &Transition, output: &OutputOptions) -> String {
+    let (before, after) = transition.wall_clock();
+    let offset = |offset: &ZoneOffset| format!("{} {}", offset.utc_offset, offset.abbreviation);
+    let iso = |time: NaiveDateTime| time.format("%Y-%m-%dT%H:%M:%S").to_string();
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let time = |time: NaiveDateTime| match time.second() {
+                0 => time.format("%H:%M").to_string(),
+                _ => time.format("%H:%M:%S").to_string(),
+            };
+            let jump = match before.date() == after.date() {
+                true => format!("{} {}→{}", before.date(), time(before), time(after)),
+                false => format!(
+                    "{} {}→{} {}",
+                    before.date(),
+                    time(before),
+                    after.date(),
+                    time(after)
+                ),
+            };
+            format_table(&[
+                ("Time zone", Cell::Text(timezone.name().to_string())),
+                ("Instant", Cell::Text(output.format_value(transition.at))),
+                ("Wall clock", Cell::Text(jump)),
+                ("Offset before", Cell::Text(offset(&transition.before))),
+                ("Offset after", Cell::Text(offset(&transition.after))),
+            ])
+        }
+        OutputFormat::Json => {
+            let json = json!({
+                "timezone": timezone.name(),
+                "at": output.format_value(transition.at),
+                "wall_clock_before": iso(before),
+                "wall_clock_after": iso(after),
+                "before": zone_offset_json(&transition.before),
+                "after": zone_offset_json(&transition.after),
+            });
+            json.to_string() + "\n"
+        }
+        OutputFormat::Csv => {
+            let header = "timezone,at,wall_clock_before,wall_clock_after,before,after";
+            let row = [
+                timezone.name().to_string(),
+                output.format_value(transition.at),
+                iso(before),
+                iso(after),
+                transition.before.utc_offset.to_string(),
+                transition.after.utc_offset.to_string(),
+            ];
+            format!("{}\n{}\n", header, row.join(","))
+        }
+    }
+}
+
+fn transition_json(transition: &Transition, output: &OutputOptions) -> serde_json::Value {
+    json!({
+        "at": output.format_value(transition.at),
+        "before": zone_offset_json(&transition.before),
+        "after": zone_offset_json(&transition.after),
+    })
+}
+
+fn zone_offset_json(offset: &ZoneOffset) -> serde_json::Value {
+    json!({
+        "utc_offset": offset.utc_offset.to_string(),
+        "utc_offset_seconds": offset.utc_offset.local_minus_utc(),
+        "abbreviation": offset.abbreviation,
+        "dst": offset.is_dst(),
+    })
+}
+
+/// Writes the CSV header line of [write_pair] records if CSV output is
+/// selected.
+pub fn write_pair_header(out: &mut impl Write, output: &OutputOptions) -> std::io::Result<()> {
+    if output.format == OutputFormat::Csv {
+        writeln!(out, "line,first,second,drift_ms")?;
+    }
+    Ok(())
+}
+
+/// Writes the date times of a line of `--pair` and the drift from the first
+/// to the second one in whole milliseconds, on a single line even as table.
+pub fn write_pair(
+    out: &mut impl Write,
+    line: usize,
+    (first, second): (DateTime<Utc>, DateTime<Utc>),
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    let drift = (second - first).num_milliseconds();
+    let (first, second) = (output.format_value(first), output.format_value(second));
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            writeln!(out, "{} {} {:+} ms", first, second, drift)
+        }
+        OutputFormat::Json => {
+            let json = json!({
+                "line": line,
+                "first": first,
+                "second": second,
+                "drift_ms": drift,
+            });
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Csv => {
+            let row = [line.to_string(), csv_field(&first), csv_field(&second)];
+            writeln!(out, "{},{}", row.join(","), drift)
+        }
+    }
+}
+
+/// Writes the summary of the drifts of `--pair`.
+pub fn write_drift(
+    out: &mut impl Write,
+    drift: &Durations,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_drift(drift, output).as_bytes())
+}
+
+fn format_drift(drift: &Durations, output: &OutputOptions) -> String {
+    let drifts = [drift.min, drift.mean(), drift.max];
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let [min, mean, max] = drifts
+                .map(|drift| Cell::Text(drift.map_or_else(|| "-".to_string(), format_breakdown)));
+            format_table(&[
+                ("Pairs", Cell::Number(drift.count as i64)),
+                ("Min drift", min),
+                ("Mean drift", mean),
+                ("Max drift", max),
+            ])
+        }
+        OutputFormat::Json => {
+            let [min, mean, max] = drifts.map(|drift| drift.map(|drift| drift.num_milliseconds()));
+            let json = json!({
+                "pairs": drift.count,
+                "min_drift_ms": min,
+                "mean_drift_ms": mean,
+                "max_drift_ms": max,
+            });
+            json.to_string() + "\n"
+        }
+        OutputFormat::Csv => {
+            let [min, mean, max] = drifts.map(|drift| {
+                drift.map_or_else(String::new, |drift| drift.num_milliseconds().to_string())
+            });
+            format!(
+                "pairs,min_drift_ms,mean_drift_ms,max_drift_ms\n{},{},{},{}\n",
+                drift.count, min, mean, max
+            )
+        }
+    }
+}
+
+/// Writes the CSV header line of [write_delta] records if CSV output is
+/// selected.
+pub fn write_delta_header(out: &mut impl Write, output: &OutputOptions) -> std::io::Result<()> {
+    if output.format == OutputFormat::Csv {
+        writeln!(out, "line,at,delta_ms,delta")?;
+    }
+    Ok(())
+}
+
+/// Writes the date time of a line of `--deltas` and the delta from the
+/// previous one, in whole milliseconds and in words, on a single line even
+/// as table.
+pub fn write_delta(
+    out: &mut impl Write,
+    line: usize,
+    at: DateTime<Utc>,
+    delta: Duration,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    let (at, millis, words) = (
+        output.format_value(at),
+        delta.num_milliseconds(),
+        humanize(delta),
+    );
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            writeln!(out, "{} {:+} ms ({})", at, millis, words)
+        }
+        OutputFormat::Json => {
+            let json = json!({
+                "line": line,
+                "at": at,
+                "delta_ms": millis,
+                "delta": words,
+            });
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Csv => {
+            writeln!(
+                out,
+                "{},{},{},{}",
+                line,
+                csv_field(&at),
+                millis,
+                csv_field(&words)
+            )
+        }
+    }
+}
+
+/// Writes the summary of the gaps of `--deltas` with the line of the
+/// largest one.
+pub fn write_gaps(
+    out: &mut impl Write,
+    gaps: &Durations,
+    largest_line: Option<usize>,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_gaps(gaps, largest_line, output).as_bytes())
+}
+
+fn format_gaps(gaps: &Durations, largest_line: Option<usize>, output: &OutputOptions) -> String {
+    let values = [gaps.min, gaps.mean(), gaps.max];
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let [min, mean, max] =
+                values.map(|gap| Cell::Text(gap.map_or_else(|| "-".to_string(), format_breakdown)));
+            let line = match largest_line {
+                Some(line) => Cell::Number(line as i64),
+                None => Cell::Text("-".to_string()),
+            };
+            format_table(&[
+                ("Gaps", Cell::Number(gaps.count as i64)),
+                ("Min gap", min),
+                ("Mean gap", mean),
+                ("Max gap", max),
+                ("Max gap line", line),
+            ])
+        }
+        OutputFormat::Json => {
+            let [min, mean, max] = values.map(|gap| gap.map(|gap| gap.num_milliseconds()));
+            let json = json!({
+                "gaps": gaps.count,
+                "min_gap_ms": min,
+                "mean_gap_ms": mean,
+                "max_gap_ms": max,
+                "max_gap_line": largest_line,
+            });
+            json.to_string() + "\n"
+        }
+        OutputFormat::Csv => {
+            let [min, mean, max] = values
+                .map(|gap| gap.map_or_else(String::new, |gap| gap.num_milliseconds().to_string()));
+            let line = largest_line.map_or_else(String::new, |line| line.to_string());
+            format!(
+                "gaps,min_gap_ms,mean_gap_ms,max_gap_ms,max_gap_line\n{},{},{},{},{}\n",
+                gaps.count, min, mean, max, line
+            )
+        }
+    }
+}
+
+/// Writes the summary of `--stats`.
+pub fn write_stats(
+    out: &mut impl Write,
+    stats: &Stats,
+    output: &OutputOptions,
+) -> std::io::Result<()> {
+    out.write_all(format_stats(stats, output).as_bytes())
+}
+
+fn format_stats(stats: &Stats, output: &OutputOptions) -> String {
+    let instant = |datetime: Option<DateTime<Utc>>| datetime.map(|d| output.format_value(d));
+    let earliest = instant(stats.earliest);
+    let latest = instant(stats.latest);
+    let mean = instant(stats.mean());
+    let span = stats.span();
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let text = |value: Option<String>| Cell::Text(value.unwrap_or_else(|| "-".to_string()));
+            format_table(&[
+                ("Parsed", Cell::Number(stats.parsed as i64)),
+                ("Failed", Cell::Number(stats.failed as i64)),
+                ("Blank", Cell::Number(stats.blank as i64)),
+                ("Earliest", text(earliest)),
+                ("Latest", text(latest)),
+                ("Mean", text(mean)),
+                ("Span", text(span.map(format_breakdown))),
+            ])
+        }
+        OutputFormat::Json => {
+            let json = json!({
+                "parsed": stats.parsed,
+                "failed": stats.failed,
+                "blank": stats.blank,
+                "earliest": earliest,
+                "latest": latest,
+                "mean": mean,
+                "span": span.map(format_iso),
+            });
+            json.to_string() + "\n"
+        }
+        OutputFormat::Csv => {
+            let row = [
+                stats.parsed.to_string(),
+                stats.failed.to_string(),
+                stats.blank.to_string(),
+                earliest.unwrap_or_default(),
+                latest.unwrap_or_default(),
+                mean.unwrap_or_default(),
+                span.map(format_iso).unwrap_or_default(),
+            ];
+            format!(
+                "parsed,failed,blank,earliest,latest,mean,span\n{}\n",
+                row.join(",")
+            )
+        }
+    }
+}
+
+/// Writes the buckets of `--histogram`, as table with bars of at most
+/// `width` characters per line.
+pub fn write_histogram(
+    out: &mut impl Write,
+    rows: &[(DateTime<Utc>, u64)],
+    output: &OutputOptions,
+    width: usize,
+) -> std::io::Result<()> {
+    out.write_all(format_histogram(rows, output, width).as_bytes())
+}
+
+fn format_histogram(rows: &[(DateTime<Utc>, u64)], output: &OutputOptions, width: usize) -> String {
+    let rows: Vec<(String, u64)> = rows
+        .iter()
+        .map(|(start, count)| (output.format_value(*start), *count))
+        .collect();
+    match output.format {
+        OutputFormat::Table | OutputFormat::Protobuf => {
+            let start_width = rows.iter().map(|(start, _)| start.chars().count()).max();
+            let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(1);
+            let count_width = max.to_string().len();
+            let bar_width = width.saturating_sub(start_width.unwrap_or(0) + count_width + 2);
+            rows.iter()
+                .map(|(start, count)| {
+                    let bar = (count * bar_width as u64).div_ceil(max.max(1));
+                    let line = format!(
+                        "{} {:>count_width$} {}",
+                        start,
+                        count,
+                        "#".repeat(bar as usize)
+                    );
+                    line.trim_end().to_string() + "\n"
+                })
+                .collect()
+        }
+        OutputFormat::Json => rows
+            .iter()
+            .map(|(start, count)| json!({"bucket": start, "count": count}).to_string() + "\n")
+            .collect(),
+        OutputFormat::Csv => {
+            let lines = rows
+                .iter()
+                .map(|(start, count)| format!("{},{}\n", start, count));
+            "bucket,count\n".to_string() + &lines.collect::<String>()
+        }
+    }
+}
+
+/// Formats `rows` of labels and values as a table which is at least as wide
+/// as the table of a single date time.
+fn format_table(rows: &[(&str, Cell)]) -> String {
+    let values: Vec<(bool, String)> = rows
+        .iter()
+        .map(|(_, cell)| match cell {
+            Cell::Text(text) => (false, text.clone()),
+            Cell::Number(number) => (true, number.to_string()),
+        })
+        .collect();
+    let label_width = rows
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .fold(18, usize::max);
+    let value_width = values
+        .iter()
+        .map(|(_, value)| value.chars().count())
+        .fold(24, usize::max);
+    let line = |left: &str, middle: &str, right: &str| {
+        format!(
+            "{}{}{}{}{}\n",
+            left,
+            "─".repeat(label_width + 2),
+            middle,
+            "─".repeat(value_width + 2),
+            right
+        )
+    };
+    let mut table = line("┌", "┬", "┐");
+    for (i, ((label, _), (numeric, value))) in rows.iter().zip(values).enumerate() {
+        if i > 0 {
+            table += &line("├", "┬", "┤");
+        }
+        if numeric {
+            table += &format!("│ {:label_width$} │ {:>value_width$} │\n", label, value);
+        } else {
+            table += &format!("│ {:label_width$} │ {:value_width$} │\n", label, value);
+        }
+    }
+    table + &line("└", "┴", "┘")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use crate::output::write_labeled;
+    use crate::output::{
+        csv_header, format_duration_output, format_output, format_stats, format_time,
+    };
+    use crate::output::{format_age, format_transition, format_zone_info};
+    use crate::output::{format_histogram, parse_time_format, weekday_warning, write_time};
+    use crate::output::{OutputField, OutputFormat, OutputOptions, Precision, WeekRule};
+    use crate::stats::Stats;
+    use crate::zone::ZoneInfo;
+    use crate::{parse_timezone, ParsedDateTime};
+
+    #[test]
+    fn table() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let expected = "\
+┌────────────────────┬──────────────────────────┐
+│ ISO 8601 timestamp │ 2023-02-16T12:34:56.789Z │
+├────────────────────┬──────────────────────────┤
+│ Epoch seconds      │               1676550896 │
+├────────────────────┬──────────────────────────┤
+│ Epoch milliseconds │            1676550896789 │
+└────────────────────┴──────────────────────────┘
+";
+        assert_eq!(
+            format_time(&datetime.into(), &OutputOptions::default()),
+            expected
+        );
+    }
+
+    #[test]
+    fn json() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Json,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_output(&datetime.into(), &output),
+            "{\"iso\":\"2023-02-16T12:34:56.789Z\",\"epoch_seconds\":1676550896,\
+             \"epoch_millis\":1676550896789,\
+             \"touch\":\"202302161234.56\",\"gnu_date\":\"2023-02-16T12:34:56.789Z\"}\n"
+        );
+    }
+
+    #[test]
+    fn protobuf() {
+        let datetime = Utc.timestamp_nanos(-1_500_000_000);
+        let output = OutputOptions {
+            format: OutputFormat::Protobuf,
+            precision: Precision::S,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_output(&datetime.into(), &output),
+            "\"1969-12-31T23:59:58.500Z\" seconds: -2 nanos: 500000000\n"
+        );
+        let to = datetime + Duration::nanoseconds(-1);
+        assert_eq!(
+            format_duration_output(datetime, to, &output),
+            "\"-0.000000001s\" seconds: 0 nanos: -1\n"
+        );
+    }
+
+    #[test]
+    fn duration_table() {
+        let from = Utc.timestamp_millis_opt(1676550896000).unwrap();
+        let to = Utc.timestamp_millis_opt(1677628800000).unwrap();
+        let expected = "\
+┌────────────────────┬─────────────────────────────────────────────┐
+│ From               │ 2023-02-16T12:34:56.000Z                    │
+├────────────────────┬─────────────────────────────────────────────┤
+│ To                 │ 2023-03-01T00:00:00.000Z                    │
+├────────────────────┬─────────────────────────────────────────────┤
+│ Seconds            │                                     1077904 │
+├────────────────────┬─────────────────────────────────────────────┤
+│ Milliseconds       │                                  1077904000 │
+├────────────────────┬─────────────────────────────────────────────┤
+│ Duration           │ 12d 11:25:04.000                            │
+├────────────────────┬─────────────────────────────────────────────┤
+│ Humanized          │ 12 days, 11 hours, 25 minutes and 4 seconds │
+└────────────────────┴─────────────────────────────────────────────┘
+";
+        let output = OutputOptions::default();
+        assert_eq!(format_duration_output(from, to, &output), expected);
+    }
+
+    #[test]
+    fn negative_duration_with_mixed_precision() {
+        let from = Utc.timestamp_millis_opt(1677628800000).unwrap();
+        let to = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Json,
+            ..Default::default()
+        };
+        let json = format_duration_output(from, to, &output);
+        assert!(json.contains("\"seconds\":-1077903,\"millis\":-1077903211,"));
+        assert!(json.contains("\"duration\":\"-12d 11:25:03.211\""));
+    }
+
+    #[test]
+    fn prometheus_fields_never_use_scientific_notation() {
+        let field = |millis, only: OutputField| {
+            let datetime = Utc.timestamp_millis_opt(millis).unwrap();
+            only.format(&datetime.into(), &OutputOptions::default())
+        };
+        assert_eq!(field(1676550896789, OutputField::PromS), "1676550896.789");
+        assert_eq!(field(1676550896789, OutputField::PromMs), "1676550896789");
+        assert_eq!(field(1676550896000, OutputField::PromS), "1676550896.000");
+        assert_eq!(field(1676550896007, OutputField::PromS), "1676550896.007");
+        assert_eq!(
+            field(100000000000000, OutputField::PromS),
+            "100000000000.000"
+        );
+        assert_eq!(field(0, OutputField::PromS), "0.000");
+        assert_eq!(field(-1500, OutputField::PromS), "-1.500");
+        assert_eq!(field(-5, OutputField::PromS), "-0.005");
+        assert_eq!(
+            OutputField::PromS.format_duration(Duration::milliseconds(-1077903211)),
+            "-1077903.211"
+        );
+    }
+
+    #[test]
+    fn duration_only_millis() {
+        let from = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let to = Utc.timestamp_millis_opt(1676550896000).unwrap();
+        let output = OutputOptions {
+            only: Some(OutputField::Millis),
+            ..Default::default()
+        };
+        assert_eq!(format_duration_output(from, to, &output), "-789\n");
+    }
+
+    #[test]
+    fn csv() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(
+            csv_header(&output),
+            "iso,epoch_seconds,epoch_millis,touch,gnu_date"
+        );
+        assert_eq!(
+            format_output(&datetime.into(), &output),
+            "2023-02-16T12:34:56.789Z,1676550896,1676550896789,\
+             202302161234.56,2023-02-16T12:34:56.789Z\n"
+        );
+    }
+
+    #[test]
+    fn write_csv_with_header() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_time(&mut out, &datetime.into(), &output).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "iso,epoch_seconds,epoch_millis,touch,gnu_date\n\
+             2023-02-16T12:34:56.789Z,1676550896,1676550896789,\
+             202302161234.56,2023-02-16T12:34:56.789Z\n"
+        );
+    }
+
+    #[test]
+    fn csv_only() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            only: Some(OutputField::Seconds),
+            ..Default::default()
+        };
+        assert_eq!(csv_header(&output), "epoch_seconds");
+        assert_eq!(format_output(&datetime.into(), &output), "1676550896\n");
+    }
+
+    #[test]
+    fn duration_csv() {
+        let from = Utc.timestamp_millis_opt(1676550896000).unwrap();
+        let to = Utc.timestamp_millis_opt(1676554496000).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_duration_output(from, to, &output),
+            "from,to,seconds,millis,iso,duration,humanized\n\
+             2023-02-16T12:34:56.000Z,2023-02-16T13:34:56.000Z,3600,3600000,PT1H,0d 01:00:00.000,\"1 hour\"\n"
+        );
+    }
+
+    #[test]
+    fn zone_info_csv() {
+        let at = Utc.timestamp_millis_opt(1676550896000).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        let info = ZoneInfo::new(parse_timezone("Europe/Berlin").unwrap(), at);
+        assert_eq!(
+            format_zone_info(&info, &output).lines().nth(1).unwrap(),
+            "Europe/Berlin,2023-02-16T12:34:56.000Z,2023-02-16T13:34:56.000+01:00,+01:00,CET,no,\
+             2022-10-30T01:00:00.000Z,+02:00,+01:00,2023-03-26T01:00:00.000Z,+01:00,+02:00"
+        );
+        let info = ZoneInfo::new(parse_timezone("Etc/GMT+5").unwrap(), at);
+        assert!(format_zone_info(&info, &output).ends_with(",-05:00,-05,no,,,,,,\n"));
+    }
+
+    #[test]
+    fn transition_wall_clock() {
+        let timezone = parse_timezone("Europe/Berlin").unwrap();
+        let at = Utc.timestamp_millis_opt(1676550896000).unwrap();
+        let transition = crate::zone::next_transition(timezone, at).unwrap();
+        let table = format_transition(timezone, &transition, &OutputOptions::default());
+        assert!(
+            table.contains("│ Wall clock         │ 2023-03-26 02:00→03:00   │"),
+            "{table}"
+        );
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        let past = DateTime::from_timestamp(-3_000_000_000, 0).unwrap();
+        let transition = crate::zone::next_transition(timezone, past).unwrap();
+        assert_eq!(
+            format_transition(timezone, &transition, &output),
+            "timezone,at,wall_clock_before,wall_clock_after,before,after\n\
+             Europe/Berlin,1893-03-31T23:06:32.000Z,1893-04-01T00:00:00,1893-04-01T00:06:32,+00:53:28,+01:00\n"
+        );
+    }
+
+    #[test]
+    fn week_rules_around_new_year() {
+        let date = |year, month, day| chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        for (date, iso, sunday, simple) in [
+            // ISO week 1 of 2025 starts on Monday, December 30.
+            (date(2024, 12, 31), "2025-W01", "2024-W53", "2024-W53"),
+            (date(2025, 1, 1), "2025-W01", "2025-W01", "2025-W01"),
+            (date(2018, 12, 31), "2019-W01", "2018-W53", "2018-W53"),
+            // January 1, 2021 is a Friday in the last ISO week of 2020.
+            (date(2021, 1, 1), "2020-W53", "2021-W01", "2021-W01"),
+            (date(2021, 1, 3), "2020-W53", "2021-W02", "2021-W01"),
+            (date(2021, 1, 8), "2021-W01", "2021-W02", "2021-W02"),
+            (date(2022, 12, 31), "2022-W52", "2022-W53", "2022-W53"),
+            // A leap year starting on Saturday has a 54th Sunday week.
+            (date(2000, 12, 31), "2000-W52", "2000-W54", "2000-W53"),
+        ] {
+            assert_eq!(WeekRule::Iso.format(date), iso, "{date}");
+            assert_eq!(WeekRule::Sunday.format(date), sunday, "{date}");
+            assert_eq!(WeekRule::MondaySimple.format(date), simple, "{date}");
+        }
+    }
+
+    #[test]
+    fn week_in_time_zone() {
+        let datetime = Utc.timestamp_millis_opt(1704063600000).unwrap();
+        let output = OutputOptions {
+            only: Some(OutputField::Week),
+            week_rule: WeekRule::Sunday,
+            ..Default::default()
+        };
+        assert_eq!(output.format_value(datetime), "2023-W53");
+        let output = OutputOptions {
+            timezone: Some(parse_timezone("Europe/Berlin").unwrap()),
+            ..output
+        };
+        assert_eq!(output.format_value(datetime), "2024-W01");
+    }
+
+    #[test]
+    fn age_in_the_future_is_negative() {
+        let age = crate::duration::Age {
+            years: 1,
+            months: 2,
+            days: 3,
+            hours: 0,
+            minutes: 0,
+            seconds: 0,
+            future: true,
+        };
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_age("2024-04-19", "2023-02-16", &age, true, &output),
+            "from,at,years,months,days\n2024-04-19,2023-02-16,-1,-2,-3\n"
+        );
+        let output = OutputOptions {
+            format: OutputFormat::Json,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_age("a", "b", &age, false, &output),
+            "{\"from\":\"a\",\"at\":\"b\",\"years\":-1,\"months\":-2,\"days\":-3,\
+             \"hours\":0,\"minutes\":0,\"seconds\":0,\"future\":true}\n"
+        );
+    }
+
+    #[test]
+    fn shell_formats() {
+        let only = |only, timezone: Option<&str>, datetime: DateTime<Utc>| {
+            let output = OutputOptions {
+                only: Some(only),
+                timezone: timezone.map(|timezone| parse_timezone(timezone).unwrap()),
+                ..Default::default()
+            };
+            output.format_value(datetime)
+        };
+        let datetime = Utc.timestamp_nanos(1676550896999999999);
+        assert_eq!(only(OutputField::Touch, None, datetime), "202302161234.56");
+        assert_eq!(
+            only(OutputField::Touch, Some("Europe/Berlin"), datetime),
+            "202302161334.56"
+        );
+        assert_eq!(
+            only(OutputField::GnuDate, None, datetime),
+            "2023-02-16T12:34:56.999999999Z"
+        );
+        assert_eq!(
+            only(OutputField::GnuDate, Some("Asia/Kolkata"), datetime),
+            "2023-02-16T18:04:56.999999999+05:30"
+        );
+        // The century is always written, so years before 1969 are not read as 20YY.
+        let datetime = Utc.with_ymd_and_hms(1950, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(only(OutputField::Touch, None, datetime), "195001020304.05");
+        let datetime = Utc.with_ymd_and_hms(99, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(only(OutputField::Touch, None, datetime), "009901020304.05");
+        let datetime = Utc.with_ymd_and_hms(10000, 1, 1, 0, 0, 0).unwrap();
+        for field in [OutputField::Touch, OutputField::GnuDate] {
+            assert_eq!(only(field, None, datetime), "out of range");
+        }
+    }
+
+    #[test]
+    fn time_format() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            time_format: Some(parse_time_format("%H:%M:%S%.3f").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(csv_header(&output), "formatted");
+        assert_eq!(format_output(&datetime.into(), &output), "12:34:56.789\n");
+    }
+
+    #[test]
+    fn invalid_time_format() {
+        assert!(parse_time_format("%H:%Q").is_err());
+        assert!(parse_time_format("%").is_err());
+    }
+
+    #[test]
+    fn timezone_and_precision() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let output = OutputOptions {
+            format: OutputFormat::Json,
+            timezone: Some(parse_timezone("Europe/Berlin").unwrap()),
+            precision: Precision::Us,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_output(&datetime.into(), &output),
+            "{\"iso\":\"2023-02-16T13:34:56.789000+01:00\",\"epoch_seconds\":1676550896,\
+             \"epoch_millis\":1676550896789,\
+             \"touch\":\"202302161334.56\",\"gnu_date\":\"2023-02-16T13:34:56.789+01:00\"}\n"
+        );
+        let output = OutputOptions {
+            time_format: Some("%H:%M %Z".to_string()),
+            timezone: Some(parse_timezone("America/New_York").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(output.format_value(datetime), "07:34 EST");
+    }
+
+    #[test]
+    fn local_times_out_of_range() {
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            timezone: Some(parse_timezone("Pacific/Kiritimati").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_output(&DateTime::<Utc>::MAX_UTC.into(), &output),
+            "out of range,8210266876799,8210266876799999,,\n"
+        );
+        let output = OutputOptions {
+            time_format: Some("%Y".to_string()),
+            ..output
+        };
+        assert_eq!(
+            output.format_value(DateTime::<Utc>::MAX_UTC),
+            "out of range"
+        );
+        let output = OutputOptions {
+            timezone: Some(parse_timezone("America/Adak").unwrap()),
+            ..output
+        };
+        assert_eq!(output.format_value(DateTime::<Utc>::MAX_UTC), "+262142");
+    }
+
+    #[test]
+    fn weekdays_not_matching_the_date_are_warned_about() {
+        let parsed = crate::parse("Wed, 16 Feb 2023 12:34:56 +0000").unwrap();
+        assert_eq!(
+            weekday_warning(&parsed).unwrap(),
+            "warning: \"Wed, 16 Feb 2023 12:34:56 +0000\" says Wednesday, \
+             but 2023-02-16 is a Thursday"
+        );
+        let parsed = crate::parse("thu, 16 feb 2023 12:34:56 +0000").unwrap();
+        assert_eq!(weekday_warning(&parsed), None);
+    }
+
+    #[test]
+    fn microseconds_are_noted_in_tables_and_kept_in_json() {
+        let parsed = crate::parse("2023-02-16T12:34:56.000001Z").unwrap();
+        let output = OutputOptions::default();
+        assert_eq!(
+            output.precision_note(&parsed).unwrap(),
+            "note: \"2023-02-16T12:34:56.000001Z\" has microsecond precision, \
+             which --precision ms cuts off, see --precision auto"
+        );
+        let json = OutputOptions {
+            format: OutputFormat::Json,
+            ..Default::default()
+        };
+        assert_eq!(json.precision_note(&parsed), None);
+        assert_eq!(
+            format_output(&parsed, &json),
+            "{\"iso\":\"2023-02-16T12:34:56.000001Z\",\"epoch_seconds\":1676550896,\
+             \"epoch_millis\":1676550896000,\
+             \"touch\":\"202302161234.56\",\"gnu_date\":\"2023-02-16T12:34:56.000001Z\"}\n"
+        );
+        let millis = OutputOptions {
+            only: Some(OutputField::Millis),
+            ..Default::default()
+        };
+        assert_eq!(millis.precision_note(&parsed), None);
+        let micros = OutputOptions {
+            precision: Precision::Us,
+            ..Default::default()
+        };
+        assert_eq!(micros.precision_note(&parsed), None);
+        let parsed = crate::parse("2023-02-16T12:34:56.0000001Z").unwrap();
+        assert!(micros
+            .precision_note(&parsed)
+            .unwrap()
+            .contains("nanosecond"));
+    }
+
+    #[test]
+    fn original_local_time_of_offsets_other_than_utc() {
+        let parsed = crate::parse("2023-02-16T18:04:56+05:30").unwrap();
+        assert!(format_time(&parsed, &OutputOptions::default())
+            .ends_with("│ Original local time │ 2023-02-16T18:04:56.000+05:30 │\n└─────────────────────┴───────────────────────────────┘\n"));
+        let utc = crate::parse("2023-02-16T12:34:56Z").unwrap();
+        assert!(!format_time(&utc, &OutputOptions::default()).contains("Original"));
+    }
+
+    #[test]
+    fn precision_auto() {
+        let output = OutputOptions {
+            only: Some(OutputField::Iso),
+            precision: Precision::Auto,
+            ..Default::default()
+        };
+        let datetime = Utc.timestamp_millis_opt(1676550896000).unwrap();
+        assert_eq!(
+            format_output(&datetime.into(), &output),
+            "2023-02-16T12:34:56Z\n"
+        );
+        let datetime = Utc.timestamp_millis_opt(1676550896780).unwrap();
+        assert_eq!(
+            format_output(&datetime.into(), &output),
+            "2023-02-16T12:34:56.780Z\n"
+        );
+    }
+
+    #[test]
+    fn unknown_timezone() {
+        assert!(parse_timezone("Europe/Springfield").is_err());
+    }
+
+    #[test]
+    fn stats_table() {
+        let mut stats = Stats::default();
+        stats.add(Utc.timestamp_millis_opt(1676550896789).unwrap());
+        stats.add(Utc.timestamp_millis_opt(1676554496789).unwrap());
+        stats.add_failure(2);
+        let expected = "\
+┌────────────────────┬──────────────────────────┐
+│ Parsed             │                        2 │
+├────────────────────┬──────────────────────────┤
+│ Failed             │                        1 │
+├────────────────────┬──────────────────────────┤
+│ Blank              │                        0 │
+├────────────────────┬──────────────────────────┤
+│ Earliest           │ 2023-02-16T12:34:56.789Z │
+├────────────────────┬──────────────────────────┤
+│ Latest             │ 2023-02-16T13:34:56.789Z │
+├────────────────────┬──────────────────────────┤
+│ Mean               │ 2023-02-16T13:04:56.789Z │
+├────────────────────┬──────────────────────────┤
+│ Span               │ 0d 01:00:00.000          │
+└────────────────────┴──────────────────────────┘
+";
+        assert_eq!(format_stats(&stats, &OutputOptions::default()), expected);
+    }
+
+    #[test]
+    fn stats_of_empty_input() {
+        let stats = Stats::default();
+        let output = OutputOptions {
+            format: OutputFormat::Json,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_stats(&stats, &output),
+            "{\"parsed\":0,\"failed\":0,\"blank\":0,\"earliest\":null,\"latest\":null,\"mean\":null,\"span\":null}\n"
+        );
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_stats(&stats, &output),
+            "parsed,failed,blank,earliest,latest,mean,span\n0,0,0,,,,\n"
+        );
+    }
+
+    #[test]
+    fn histogram() {
+        let rows = [
+            (Utc.timestamp_opt(1676505600, 0).unwrap(), 4),
+            (Utc.timestamp_opt(1676509200, 0).unwrap(), 0),
+            (Utc.timestamp_opt(1676512800, 0).unwrap(), 10),
+        ];
+        let output = OutputOptions {
+            time_format: Some("%H:%M".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_histogram(&rows, &output, 29),
+            "00:00  4 ########\n01:00  0\n02:00 10 ####################\n"
+        );
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_histogram(&rows[..1], &output, 80),
+            "bucket,count\n2023-02-16T00:00:00.000Z,4\n"
+        );
+        let output = OutputOptions {
+            format: OutputFormat::Json,
+            ..output
+        };
+        assert_eq!(
+            format_histogram(&rows[..1], &output, 80),
+            "{\"bucket\":\"2023-02-16T00:00:00.000Z\",\"count\":4}\n"
+        );
+    }
+
+    #[test]
+    fn single_labeled_values_and_errors() {
+        let parsed = ParsedDateTime::from(Utc.timestamp_opt(1676550896, 0).unwrap());
+        let output = OutputOptions {
+            only: Some(OutputField::Seconds),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let labels = [("path", "deploy.log"), ("stamp", "mtime")];
+        write_labeled(&mut out, &labels, Ok(&parsed), &output).unwrap();
+        let labels = [("path", "deploy.log"), ("stamp", "created")];
+        write_labeled(&mut out, &labels, Err("not supported"), &output).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "deploy.log mtime: 1676550896\ndeploy.log created: not supported\n"
+        );
+    }
+}
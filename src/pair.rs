@@ -0,0 +1,158 @@
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Utc};
+
+use crate::input::Lines;
+use crate::output::{write_drift, write_pair, write_pair_header, OutputOptions};
+use crate::scan::find_datetimes;
+use crate::stats::{Durations, Stats};
+use crate::{get_datetime, Detectors};
+
+/// The two date times of `line`, like a send and a receive time. With a
+/// `delimiter`, the line is split at it into two inputs of any format.
+/// Otherwise the date times are found like those of --rewrite. The error
+/// tells how many were found instead.
+pub(crate) fn find_pair(
+    line: &str,
+    delimiter: Option<&str>,
+    detectors: &Detectors,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let Some(delimiter) = delimiter else {
+        return match &find_datetimes(line.as_bytes(), detectors)[..] {
+            [(_, first), (_, second)] => Ok((*first, *second)),
+            found => Err(format!("expected 2 date times, found {}", found.len())),
+        };
+    };
+    match line.split(delimiter).collect::<Vec<_>>()[..] {
+        [first, second] => {
+            let parse =
+                |input: &str| get_datetime(input.trim(), detectors).map_err(|e| e.to_string());
+            Ok((parse(first)?, parse(second)?))
+        }
+        ref fields => Err(format!(
+            "expected 2 fields separated by \"{}\", found {}",
+            delimiter,
+            fields.len()
+        )),
+    }
+}
+
+/// Writes both date times of each of the `lines` and the drift between them,
+/// followed by the smallest, mean and largest drift. Lines without exactly
+/// two date times are skipped and reported to `errors` unless `quiet` is
+/// set. Returns the statistics of the lines, counting one date time per
+/// pair.
+pub(crate) fn write_pairs(
+    lines: &mut Lines<impl BufRead>,
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    delimiter: Option<&str>,
+    detectors: &Detectors,
+    output: &OutputOptions,
+    quiet: bool,
+) -> std::io::Result<Stats> {
+    let mut stats = Stats::default();
+    let mut drift = Durations::default();
+    write_pair_header(out, output)?;
+    while let Some((number, line)) = lines.next_line()? {
+        let pair = line.map_err(|e| e.to_string());
+        match pair.and_then(|line| find_pair(line, delimiter, detectors)) {
+            Ok(pair) => {
+                stats.add(pair.0);
+                drift.add(pair.1 - pair.0);
+                write_pair(out, number, pair, output)?;
+            }
+            Err(e) => {
+                stats.add_failure(number);
+                if !quiet {
+                    writeln!(errors, "line {}: {}", number, e)?;
+                }
+            }
+        }
+    }
+    stats.blank = lines.blank();
+    write_drift(out, &drift, output)?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::input::Lines;
+    use crate::output::{OutputFormat, OutputOptions};
+    use crate::pair::{find_pair, write_pairs};
+    use crate::Detectors;
+
+    #[test]
+    fn pairs_of_mixed_formats() {
+        let detectors = Detectors::default();
+        let (first, second) = find_pair(
+            "sent=1676550896789 received=2023-02-16T12:34:57.001Z",
+            None,
+            &detectors,
+        )
+        .unwrap();
+        assert_eq!((second - first).num_milliseconds(), 212);
+        let line = "Thu, 16 Feb 2023 12:34:56 +0000 | 1676550895";
+        let (first, second) = find_pair(line, Some("|"), &detectors).unwrap();
+        assert_eq!((second - first).num_seconds(), -1);
+    }
+
+    #[test]
+    fn lines_without_two_date_times() {
+        let detectors = Detectors::default();
+        assert_eq!(
+            find_pair("at 1676550896 only", None, &detectors),
+            Err("expected 2 date times, found 1".to_string())
+        );
+        assert_eq!(
+            find_pair("1676550896 1676550897 1676550898", None, &detectors),
+            Err("expected 2 date times, found 3".to_string())
+        );
+        assert_eq!(
+            find_pair("1676550896;1676550897;x", Some(";"), &detectors),
+            Err("expected 2 fields separated by \";\", found 3".to_string())
+        );
+        assert!(find_pair("1676550896;soon", Some(";"), &detectors).is_err());
+    }
+
+    #[test]
+    fn pairs_are_written_with_a_summary() {
+        let input = "1676550896000 2023-02-16T12:34:56.250Z\n\
+                     no pair here\n\
+                     \n\
+                     1676550897000 2023-02-16T12:34:56.900Z\n";
+        let mut lines = Lines::new(Cursor::new(input), 0, None).skip_blank();
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let stats = write_pairs(
+            &mut lines,
+            &mut out,
+            &mut errors,
+            None,
+            &Detectors::default(),
+            &output,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "line,first,second,drift_ms\n\
+             1,2023-02-16T12:34:56.000Z,2023-02-16T12:34:56.250Z,250\n\
+             4,2023-02-16T12:34:57.000Z,2023-02-16T12:34:56.900Z,-100\n\
+             pairs,min_drift_ms,mean_drift_ms,max_drift_ms\n2,-100,75,250\n"
+        );
+        assert_eq!(
+            String::from_utf8(errors).unwrap(),
+            "line 2: expected 2 date times, found 0\n"
+        );
+        assert_eq!(
+            stats.summary(),
+            "4 lines read, 2 converted, 1 failed, 1 blank (line 2)"
+        );
+    }
+}
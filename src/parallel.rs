@@ -0,0 +1,169 @@
+use std::io::BufRead;
+use std::num::NonZeroUsize;
+use std::thread;
+
+use datetime_helper::{DateTimeError, ParsedDateTime};
+
+use crate::input::Lines;
+
+/// The number of lines parsed by one thread at a time.
+const CHUNK_SIZE: usize = 4096;
+
+type Parsed = (usize, Result<ParsedDateTime, DateTimeError>);
+/// Parses a line.
+pub(crate) type Parse<'a> = &'a (dyn Fn(&str) -> Result<ParsedDateTime, DateTimeError> + Sync);
+
+/// The number of threads for --jobs, where 0 selects the available
+/// parallelism.
+pub(crate) fn thread_count(jobs: usize) -> usize {
+    match jobs {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        jobs => jobs,
+    }
+}
+
+/// Parses the lines with `parse` on `jobs` threads and passes the results to `f` in
+/// the order of the lines. Lines are read in batches and each thread parses
+/// a chunk of a batch, so the results of a batch are available once its
+/// slowest chunk is parsed. Without threads, no line is copied.
+///
+/// Lines which are not valid UTF-8 are passed to `f` as failures. An error
+/// reading the lines is returned after the lines read before it are passed
+/// to `f`.
+pub(crate) fn parse_lines<R: BufRead>(
+    lines: &mut Lines<R>,
+    parse: Parse,
+    jobs: usize,
+    mut f: impl FnMut(usize, Result<ParsedDateTime, DateTimeError>) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    if jobs <= 1 {
+        while let Some((number, line)) = lines.next_line()? {
+            f(number, line.and_then(parse))?;
+        }
+        return Ok(());
+    }
+    let batch_size = jobs * CHUNK_SIZE;
+    // Lines which are not valid UTF-8 are None.
+    let mut batch: Vec<(usize, Option<String>)> = Vec::with_capacity(batch_size);
+    loop {
+        batch.clear();
+        let mut error = None;
+        while batch.len() < batch_size {
+            match lines.next_line() {
+                Ok(Some((number, line))) => batch.push((number, line.ok().map(str::to_string))),
+                Ok(None) => break,
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+        for (number, result) in parse_batch(&batch, parse, jobs) {
+            f(number, result)?;
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
+        if batch.len() < batch_size {
+            return Ok(());
+        }
+    }
+}
+
+fn parse_batch(batch: &[(usize, Option<String>)], parse: Parse, jobs: usize) -> Vec<Parsed> {
+    let chunk_size = batch.len().div_ceil(jobs).max(1);
+    thread::scope(|scope| {
+        let chunks: Vec<_> = batch
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(number, line)| match line {
+                            Some(line) => (*number, parse(line)),
+                            None => (*number, Err(DateTimeError::InvalidUtf8)),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.join().expect("parser thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::input::Lines;
+    use crate::parallel::{parse_lines, thread_count};
+    use datetime_helper::Detectors;
+
+    fn parse(input: &str, jobs: usize) -> Vec<(usize, Option<i64>)> {
+        let mut lines = Lines::new(Cursor::new(input), 0, None);
+        let mut parsed = Vec::new();
+        let detectors = Detectors::default();
+        parse_lines(
+            &mut lines,
+            &|line| detectors.parse(line),
+            jobs,
+            |number, result| {
+                parsed.push((number, result.ok().map(|parsed| parsed.epoch_seconds())));
+                Ok(())
+            },
+        )
+        .unwrap();
+        parsed
+    }
+
+    #[test]
+    fn results_keep_the_order_of_the_lines() {
+        let input: String = (1..=20_000)
+            .map(|number| match number % 7 {
+                0 => "not a date\n".to_string(),
+                _ => format!("{}\n", 1_676_550_000 + number),
+            })
+            .collect();
+        let sequential = parse(&input, 1);
+        let parallel = parse(&input, 3);
+        assert_eq!(parallel.len(), 20_000);
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel[6], (7, None));
+        assert_eq!(parallel[7], (8, Some(1_676_550_008)));
+    }
+
+    #[test]
+    fn invalid_utf8_fails_only_its_line() {
+        for jobs in [1, 2] {
+            let input = &b"1676550896\n\xff\n1676550897\n"[..];
+            let mut lines = Lines::new(Cursor::new(input), 0, None);
+            let mut parsed = Vec::new();
+            let detectors = Detectors::default();
+            let parse = |line: &str| detectors.parse(line);
+            parse_lines(&mut lines, &parse, jobs, |number, result| {
+                let result = result.map(|parsed| parsed.epoch_seconds());
+                parsed.push((number, result.map_err(|e| e.to_string())));
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(
+                parsed,
+                [
+                    (1, Ok(1676550896)),
+                    (2, Err("invalid UTF-8".to_string())),
+                    (3, Ok(1676550897))
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn empty_input_and_thread_count() {
+        assert_eq!(parse("", 4), []);
+        assert_eq!(thread_count(3), 3);
+        assert!(thread_count(0) >= 1);
+    }
+}
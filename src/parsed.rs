@@ -0,0 +1,309 @@
+use chrono::{
+    DateTime, Datelike, FixedOffset, NaiveDate, Offset, SecondsFormat, Timelike, Utc, Weekday,
+};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+use crate::{names, EpochUnit, InputKind};
+
+/// A parsed date time together with how it was given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ParsedDateTime {
+    /// The parsed instant.
+    pub datetime: DateTime<Utc>,
+    /// The input without surrounding whitespace.
+    pub input: String,
+    /// The kind of the input, e.g. [InputKind::EpochMicros] for epoch times
+    /// read with [ParseOptions::unit](crate::ParseOptions::unit) `us`.
+    pub format: InputKind,
+    /// The unit of an epoch time, `None` for other formats.
+    pub unit: Option<EpochUnit>,
+    /// The offset from UTC given in the input, `None` for epoch times and for
+    /// the unknown local offset `-00:00` of RFC 3339 or `-0000` of RFC 2822.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_offset"))]
+    pub offset: Option<FixedOffset>,
+    /// The precision of the input, e.g. milliseconds for epoch milliseconds
+    /// or for an RFC 3339 timestamp with one to three fractional digits.
+    pub precision: Precision,
+}
+
+impl ParsedDateTime {
+    /// The parsed date time of `input` of kind `format`, as created by a
+    /// [Detector](crate::Detector). The unit, offset and precision follow
+    /// from the kind and the input.
+    pub fn new(input: &str, format: InputKind, datetime: DateTime<FixedOffset>) -> Self {
+        let unit = format.unit();
+        let precision = match (unit, format) {
+            (_, InputKind::FloatSeconds) => Precision::of_digits(fraction_digits(input)),
+            (_, InputKind::Protobuf) => Precision::Nanos,
+            (Some(unit), _) => Precision::of_unit(unit),
+            (None, InputKind::Rfc3339 | InputKind::Local) => {
+                Precision::of_digits(fraction_digits(input))
+            }
+            (None, _) => Precision::Seconds,
+        };
+        ParsedDateTime {
+            datetime: datetime.with_timezone(&Utc),
+            input: input.to_string(),
+            format,
+            unit,
+            offset: (unit.is_none() && !has_unknown_offset(input, format))
+                .then(|| *datetime.offset()),
+            precision,
+        }
+    }
+
+    /// The seconds since 1970-01-01T00:00:00Z, rounded down.
+    pub fn epoch_seconds(&self) -> i64 {
+        self.datetime.timestamp()
+    }
+
+    /// The milliseconds since 1970-01-01T00:00:00Z, rounded down.
+    pub fn epoch_millis(&self) -> i64 {
+        self.datetime.timestamp_millis()
+    }
+
+    /// The local time of the input if it has an offset other than UTC.
+    pub fn original_local(&self) -> Option<DateTime<FixedOffset>> {
+        let offset = self.offset.filter(|offset| offset.local_minus_utc() != 0)?;
+        Some(self.datetime.with_timezone(&offset))
+    }
+
+    /// The weekday given in an RFC 2822 input and the date of the input if
+    /// they do not match, like for `Wed, 16 Feb 2023 12:34:56 +0000`.
+    pub fn weekday_mismatch(&self) -> Option<(Weekday, NaiveDate)> {
+        if self.format != InputKind::Rfc2822 {
+            return None;
+        }
+        let (given, _) = names::split_weekday(&self.input)?;
+        let offset = self.offset.unwrap_or(Utc.fix());
+        let date = self.datetime.with_timezone(&offset).date_naive();
+        (given != date.weekday()).then_some((given, date))
+    }
+
+    /// The instant as RFC 3339 timestamp in UTC with the fractional digits
+    /// of `precision`.
+    ///
+    /// ```
+    /// use datetime_helper::Precision;
+    ///
+    /// let parsed = datetime_helper::parse("2023-02-16T13:34:56.789+01:00").unwrap();
+    /// assert_eq!(parsed.to_rfc3339_with(Precision::Seconds), "2023-02-16T12:34:56Z");
+    /// assert_eq!(parsed.to_rfc3339_with(parsed.precision), "2023-02-16T12:34:56.789Z");
+    /// ```
+    pub fn to_rfc3339_with(&self, precision: Precision) -> String {
+        self.datetime
+            .to_rfc3339_opts(precision.seconds_format(), true)
+    }
+}
+
+/// Date times which were not parsed, like the current time, are treated like
+/// their RFC 3339 timestamp in UTC with the precision they need.
+impl From<DateTime<Utc>> for ParsedDateTime {
+    fn from(datetime: DateTime<Utc>) -> Self {
+        let precision = Precision::of_nanos(datetime.nanosecond());
+        ParsedDateTime {
+            datetime,
+            input: datetime.to_rfc3339_opts(precision.seconds_format(), true),
+            format: InputKind::Rfc3339,
+            unit: None,
+            offset: Some(Utc.fix()),
+            precision,
+        }
+    }
+}
+
+/// The fractional digits of a date time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "lowercase"))]
+pub enum Precision {
+    /// Full seconds
+    Seconds,
+    /// Three fractional digits
+    Millis,
+    /// Six fractional digits
+    Micros,
+    /// Nine fractional digits
+    Nanos,
+}
+
+impl Precision {
+    /// The chrono format printing the digits of this precision.
+    pub fn seconds_format(self) -> SecondsFormat {
+        match self {
+            Precision::Seconds => SecondsFormat::Secs,
+            Precision::Millis => SecondsFormat::Millis,
+            Precision::Micros => SecondsFormat::Micros,
+            Precision::Nanos => SecondsFormat::Nanos,
+        }
+    }
+
+    fn of_unit(unit: EpochUnit) -> Self {
+        match unit {
+            EpochUnit::S => Precision::Seconds,
+            EpochUnit::Ms => Precision::Millis,
+            EpochUnit::Us => Precision::Micros,
+            EpochUnit::Ns => Precision::Nanos,
+        }
+    }
+
+    fn of_digits(digits: usize) -> Self {
+        match digits {
+            0 => Precision::Seconds,
+            1..=3 => Precision::Millis,
+            4..=6 => Precision::Micros,
+            _ => Precision::Nanos,
+        }
+    }
+
+    /// The least precision representing `nanos` exactly.
+    fn of_nanos(nanos: u32) -> Self {
+        match nanos {
+            0 => Precision::Seconds,
+            nanos if nanos % 1_000_000 == 0 => Precision::Millis,
+            nanos if nanos % 1_000 == 0 => Precision::Micros,
+            _ => Precision::Nanos,
+        }
+    }
+}
+
+/// Whether the input says that its local offset is unknown, which both RFC
+/// 3339 and RFC 2822 write as negative zero offset.
+fn has_unknown_offset(input: &str, format: InputKind) -> bool {
+    match format {
+        InputKind::Rfc3339 => input.ends_with("-00:00"),
+        InputKind::Rfc2822 => input.ends_with("-0000"),
+        _ => false,
+    }
+}
+
+/// The number of fractional digits of the seconds of an RFC 3339 timestamp.
+fn fraction_digits(input: &str) -> usize {
+    match input.split_once('.') {
+        Some((_, fraction)) => fraction.bytes().take_while(u8::is_ascii_digit).count(),
+        None => 0,
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_offset<S: Serializer>(
+    offset: &Option<FixedOffset>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    offset
+        .map(|offset| offset.to_string())
+        .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate, TimeZone, Utc, Weekday};
+
+    use crate::{parse, EpochUnit, InputKind, ParseOptions, ParsedDateTime, Precision};
+
+    #[test]
+    fn offset_and_precision_of_rfc3339() {
+        let parsed = parse(" 2023-02-16T13:34:56.789123+01:00 ").unwrap();
+        assert_eq!(parsed.input, "2023-02-16T13:34:56.789123+01:00");
+        assert_eq!(parsed.offset, FixedOffset::east_opt(3600));
+        assert_eq!(parsed.precision, Precision::Micros);
+        assert_eq!(parsed.epoch_millis(), 1676550896789);
+        assert_eq!(
+            parsed.to_rfc3339_with(Precision::Nanos),
+            "2023-02-16T12:34:56.789123000Z"
+        );
+        assert_eq!(
+            parse("2023-02-16T12:34:56Z").unwrap().precision,
+            Precision::Seconds
+        );
+    }
+
+    #[test]
+    fn original_offset_of_rfc3339() {
+        let parsed = parse("2023-02-16T18:04:56+05:30").unwrap();
+        assert_eq!(parsed.offset, FixedOffset::east_opt(19800));
+        let local = parsed.original_local().unwrap();
+        assert_eq!(local.to_rfc3339(), "2023-02-16T18:04:56+05:30");
+        let parsed = parse("2023-02-16T12:34:56Z").unwrap();
+        assert_eq!(parsed.offset, FixedOffset::east_opt(0));
+        assert_eq!(parsed.original_local(), None);
+        let parsed = parse("2023-02-16T12:34:56-00:00").unwrap();
+        assert_eq!((parsed.offset, parsed.original_local()), (None, None));
+        assert_eq!(parsed.epoch_seconds(), 1676550896);
+        let parsed = parse("Thu, 16 Feb 2023 12:34:56 -0000").unwrap();
+        assert_eq!(parsed.offset, None);
+        let parsed = parse("Thu, 16 Feb 2023 12:34:56 +0000").unwrap();
+        assert_eq!(parsed.offset, FixedOffset::east_opt(0));
+    }
+
+    #[test]
+    fn weekdays_not_matching_the_date() {
+        let date = NaiveDate::from_ymd_opt(2023, 2, 16).unwrap();
+        let parsed = parse("Wed, 16 Feb 2023 12:34:56 +0000").unwrap();
+        assert_eq!(parsed.weekday_mismatch(), Some((Weekday::Wed, date)));
+        for input in [
+            "Thu, 16 Feb 2023 12:34:56 +0000",
+            "THURSDAY, 16 Feb 2023 12:34:56 +0000",
+            "16 Feb 2023 12:34:56 +0000",
+            "2023-02-16T12:34:56Z",
+        ] {
+            assert_eq!(parse(input).unwrap().weekday_mismatch(), None, "{input}");
+        }
+        // The date is the local one of the input, not the one in UTC.
+        let parsed = parse("Fri, 17 Feb 2023 01:34:56 +1300").unwrap();
+        assert_eq!(parsed.weekday_mismatch(), None);
+        let parsed = parse("Thu, 17 Feb 2023 01:34:56 +1300").unwrap();
+        assert_eq!(
+            parsed.weekday_mismatch(),
+            Some((Weekday::Thu, date.succ_opt().unwrap()))
+        );
+    }
+
+    #[test]
+    fn precision_of_epoch_times() {
+        let parsed = parse("1676550896").unwrap();
+        assert_eq!(
+            (parsed.offset, parsed.precision),
+            (None, Precision::Seconds)
+        );
+        assert_eq!(parse("1676550896789").unwrap().precision, Precision::Millis);
+        let options = ParseOptions {
+            unit: Some(EpochUnit::Ns),
+            ..Default::default()
+        };
+        let parsed = options.parse("1676550896789123456").unwrap();
+        assert_eq!(parsed.precision, Precision::Nanos);
+        assert_eq!(parsed.epoch_seconds(), 1676550896);
+    }
+
+    #[test]
+    fn from_date_time() {
+        let datetime = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let parsed = ParsedDateTime::from(datetime);
+        assert_eq!(parsed.input, "2023-02-16T12:34:56.789Z");
+        assert_eq!(parsed.format, InputKind::Rfc3339);
+        assert_eq!(parsed.precision, Precision::Millis);
+        assert_eq!(parse(&parsed.input).unwrap(), parsed);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_field_names() {
+        let parsed = parse("2023-02-16T13:34:56.789+01:00").unwrap();
+        assert_eq!(
+            serde_json::to_string(&parsed).unwrap(),
+            "{\"datetime\":\"2023-02-16T12:34:56.789Z\",\
+             \"input\":\"2023-02-16T13:34:56.789+01:00\",\
+             \"format\":\"rfc3339\",\
+             \"unit\":null,\
+             \"offset\":\"+01:00\",\
+             \"precision\":\"millis\"}"
+        );
+        let parsed = parse("1676550896").unwrap();
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(json["format"], "epoch-seconds");
+        assert_eq!(json["unit"], "s");
+        assert_eq!(json["offset"], serde_json::Value::Null);
+    }
+}
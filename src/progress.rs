@@ -0,0 +1,193 @@
+use std::io::{BufRead, Read, Write};
+use std::time::{Duration, Instant};
+
+/// The minimum time between two redraws.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+/// The number of characters of the progress bar between the brackets.
+const BAR_WIDTH: usize = 30;
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Wraps a reader to show the progress of reading it on `messages`, which is
+/// standard error outside of tests: a bar if the total size in bytes is
+/// known and a spinner with the number of lines read otherwise. The progress
+/// is removed when the input is exhausted or the reader is dropped.
+pub(crate) struct Progress<R, W: Write> {
+    inner: R,
+    messages: W,
+    total: Option<u64>,
+    bytes: u64,
+    lines: u64,
+    ticks: usize,
+    last_draw: Option<Instant>,
+}
+
+impl<R, W: Write> Progress<R, W> {
+    pub(crate) fn new(inner: R, total: Option<u64>, messages: W) -> Self {
+        Progress {
+            inner,
+            messages,
+            total,
+            bytes: 0,
+            lines: 0,
+            ticks: 0,
+            last_draw: None,
+        }
+    }
+
+    fn draw(&mut self) {
+        let now = Instant::now();
+        if self
+            .last_draw
+            .is_some_and(|last| now - last < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        let line = match self.total {
+            Some(total) => bar(self.bytes, total),
+            None => spinner(self.ticks, self.lines),
+        };
+        self.ticks += 1;
+        self.last_draw = Some(now);
+        // The cursor is moved back to the start, so that messages printed
+        // while reading overwrite the progress instead of being appended.
+        let _ = write!(self.messages, "\x1b[2K{}\r", line);
+        let _ = self.messages.flush();
+    }
+
+    fn clear(&mut self) {
+        if self.last_draw.take().is_some() {
+            let _ = write!(self.messages, "\x1b[2K");
+            let _ = self.messages.flush();
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Read for Progress<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<R: BufRead, W: Write> BufRead for Progress<R, W> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.inner.fill_buf()?.is_empty() {
+            self.clear();
+        }
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if let Ok(buffer) = self.inner.fill_buf() {
+            let consumed = &buffer[..amount.min(buffer.len())];
+            self.lines += consumed.iter().filter(|b| **b == b'\n').count() as u64;
+        }
+        self.inner.consume(amount);
+        self.bytes += amount as u64;
+        self.draw();
+    }
+}
+
+impl<R, W: Write> Drop for Progress<R, W> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A progress bar like `[#####-----]  50% 1.0 MiB / 2.0 MiB`.
+fn bar(bytes: u64, total: u64) -> String {
+    let fraction = if total == 0 {
+        1.0
+    } else {
+        (bytes as f64 / total as f64).min(1.0)
+    };
+    let filled = (fraction * BAR_WIDTH as f64) as usize;
+    format!(
+        "[{}{}] {:3.0}% {} / {}",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        fraction * 100.0,
+        size(bytes),
+        size(total)
+    )
+}
+
+/// A spinner followed by the number of lines read, like `/ 1234 lines read`.
+fn spinner(ticks: usize, lines: u64) -> String {
+    let unit = if lines == 1 { "line" } else { "lines" };
+    format!("{} {} {} read", SPINNER[ticks % SPINNER.len()], lines, unit)
+}
+
+fn size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, Cursor};
+
+    use crate::progress::{bar, size, spinner, Progress};
+
+    #[test]
+    fn bar_shows_fraction_and_sizes() {
+        assert_eq!(
+            bar(0, 2048),
+            "[------------------------------]   0% 0 B / 2.0 KiB"
+        );
+        assert_eq!(
+            bar(1536 * 1024, 3 * 1024 * 1024),
+            "[###############---------------]  50% 1.5 MiB / 3.0 MiB"
+        );
+        assert_eq!(
+            bar(10, 0),
+            "[##############################] 100% 10 B / 0 B"
+        );
+    }
+
+    #[test]
+    fn spinner_turns_and_counts_lines() {
+        assert_eq!(spinner(0, 0), "| 0 lines read");
+        assert_eq!(spinner(5, 1234), "/ 1234 lines read");
+        assert_eq!(spinner(1, 1), "/ 1 line read");
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(size(1023), "1023 B");
+        assert_eq!(size(1024), "1.0 KiB");
+        assert_eq!(size(5 * 1024 * 1024 * 1024), "5.0 GiB");
+    }
+
+    #[test]
+    fn reading_is_unchanged() {
+        let input = "1676550896\nfoo\r\n2023-02-16T12:34:56Z";
+        let mut messages = Vec::new();
+        let mut progress =
+            Progress::new(Cursor::new(input), Some(input.len() as u64), &mut messages);
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        while progress.read_line(&mut line).unwrap() > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        assert_eq!(lines.concat(), input);
+        assert_eq!((progress.bytes, progress.lines), (input.len() as u64, 2));
+        drop(progress);
+        let messages = String::from_utf8(messages).unwrap();
+        assert!(messages.starts_with("\x1b[2K[###"), "{messages:?}");
+        assert!(messages.ends_with("\r\x1b[2K"), "{messages:?}");
+    }
+}
@@ -0,0 +1,201 @@
+#[cfg(feature = "cli")]
+use chrono::{DateTime, SecondsFormat};
+use chrono::{Duration, TimeZone, Utc};
+
+use crate::detect::parse_epoch_time;
+use crate::{DateTimeError, Detector, EpochUnit, InputKind, ParsedDateTime};
+
+/// The fields of a `google.protobuf.Timestamp` in the text format like
+/// `seconds: 1676550896 nanos: 789000000`, as printed by gRPC tools. The
+/// fields may be in any order, separated by commas and surrounded by braces.
+/// Missing fields are 0 like in proto3. Nanoseconds outside of `0..1e9` are
+/// normalized, so `seconds: 0 nanos: -1` is a nanosecond before the epoch.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProtobufDetector;
+
+impl Detector for ProtobufDetector {
+    fn kind(&self) -> InputKind {
+        InputKind::Protobuf
+    }
+
+    /// The text format starts with the name of a field.
+    fn looks_like(&self, input: &str) -> bool {
+        let fields = input.strip_prefix('{').unwrap_or(input);
+        let fields = fields.trim_start_matches(separator);
+        fields.starts_with("seconds") || fields.starts_with("nanos")
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedDateTime, DateTimeError> {
+        let invalid = |reason: &str| DateTimeError::InvalidProtobuf {
+            value: input.to_string(),
+            reason: reason.to_string(),
+        };
+        let fields = match (input.strip_prefix('{'), input.ends_with('}')) {
+            (Some(fields), true) => &fields[..fields.len() - 1],
+            (None, false) => input,
+            _ => return Err(invalid("unbalanced braces")),
+        };
+        let (mut seconds, mut nanos) = (None, None);
+        let mut rest = fields.trim_start_matches(separator);
+        while !rest.is_empty() {
+            let (name, value) = rest
+                .split_once(':')
+                .ok_or_else(|| invalid(&format!("\"{}\" is not a field", rest)))?;
+            let value = value.trim_start();
+            let end = value.find(separator).unwrap_or(value.len());
+            let (value, after) = value.split_at(end);
+            rest = after.trim_start_matches(separator);
+            let slot = match name.trim() {
+                "seconds" => &mut seconds,
+                "nanos" => &mut nanos,
+                name => return Err(invalid(&format!("unknown field \"{}\"", name))),
+            };
+            if slot.replace(value).is_some() {
+                return Err(invalid(&format!("{} is given twice", name.trim())));
+            }
+        }
+        if seconds.is_none() && nanos.is_none() {
+            return Err(invalid("no seconds or nanos"));
+        }
+        let seconds = parse_epoch_time(seconds.unwrap_or("0"), EpochUnit::S)?;
+        let nanos: i32 = nanos.unwrap_or("0").parse()?;
+        // The epoch range leaves a day to spare for the nanoseconds.
+        let datetime = Utc.timestamp_opt(seconds, 0).unwrap() + Duration::nanoseconds(nanos.into());
+        Ok(ParsedDateTime::new(input, self.kind(), datetime.into()))
+    }
+}
+
+/// Separates the fields of the text format.
+fn separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+/// The JSON mapping of a `google.protobuf.Timestamp` like
+/// `"2023-02-16T12:34:56.789Z"`, always in UTC with 0, 3, 6 or 9 fractional
+/// digits so that no digit is cut off.
+#[cfg(feature = "cli")]
+pub(crate) fn timestamp_json(datetime: DateTime<Utc>) -> String {
+    format!(
+        "\"{}\"",
+        datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    )
+}
+
+/// The fields of a `google.protobuf.Timestamp`. The nanoseconds are always
+/// within `0..1e9` and the seconds carry the sign, so a millisecond before
+/// the epoch is `seconds: -1 nanos: 999000000`.
+#[cfg(feature = "cli")]
+pub(crate) fn timestamp_fields(datetime: DateTime<Utc>) -> String {
+    format!(
+        "seconds: {} nanos: {}",
+        datetime.timestamp(),
+        datetime.timestamp_subsec_nanos()
+    )
+}
+
+/// The JSON mapping of a `google.protobuf.Duration` like `"-1.500s"`.
+#[cfg(feature = "cli")]
+pub(crate) fn duration_json(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let nanos = duration.subsec_nanos().unsigned_abs();
+    let fraction = match nanos {
+        0 => String::new(),
+        _ if nanos.is_multiple_of(1_000_000) => format!(".{:03}", nanos / 1_000_000),
+        _ if nanos.is_multiple_of(1_000) => format!(".{:06}", nanos / 1_000),
+        _ => format!(".{:09}", nanos),
+    };
+    format!(
+        "\"{}{}{}s\"",
+        sign,
+        duration.num_seconds().unsigned_abs(),
+        fraction
+    )
+}
+
+/// The fields of a `google.protobuf.Duration`, which unlike those of a
+/// timestamp both have the sign of the duration.
+#[cfg(feature = "cli")]
+pub(crate) fn duration_fields(duration: Duration) -> String {
+    format!(
+        "seconds: {} nanos: {}",
+        duration.num_seconds(),
+        duration.subsec_nanos()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DateTimeError, Detector, InputKind, ProtobufDetector};
+
+    fn nanos(input: &str) -> Result<i64, DateTimeError> {
+        let parsed = ProtobufDetector.parse(input)?;
+        assert_eq!(parsed.format, InputKind::Protobuf);
+        Ok(parsed.datetime.timestamp_nanos_opt().unwrap())
+    }
+
+    #[test]
+    fn fields_in_any_order_with_braces_and_commas() {
+        for input in [
+            "seconds: 1676550896 nanos: 789000000",
+            "nanos: 789000000 seconds: 1676550896",
+            "{seconds: 1676550896, nanos: 789000000}",
+            "{ seconds:1676550896 ,nanos :789000000 }",
+        ] {
+            assert!(ProtobufDetector.looks_like(input), "{input}");
+            assert_eq!(nanos(input).unwrap(), 1676550896789000000, "{input}");
+        }
+        assert_eq!(nanos("seconds: 1676550896").unwrap(), 1676550896000000000);
+        assert_eq!(nanos("{nanos: 5}").unwrap(), 5);
+    }
+
+    #[test]
+    fn negative_nanos_are_normalized() {
+        let before = nanos("seconds: -2 nanos: 500000000").unwrap();
+        assert_eq!(before, -1_500_000_000);
+        assert_eq!(nanos("seconds: -1 nanos: -500000000").unwrap(), before);
+        assert_eq!(nanos("seconds: 0 nanos: -1").unwrap(), -1);
+    }
+
+    #[test]
+    fn invalid_fields() {
+        for input in [
+            "{seconds: 1",
+            "{}",
+            "",
+            "seconds 1",
+            "seconds: 1 nanos: 2 seconds: 3",
+            "seconds: 1 millis: 2",
+            "seconds: 1.5",
+            "seconds: 1 nanos: 3000000000",
+        ] {
+            assert!(ProtobufDetector.parse(input).is_err(), "{input}");
+        }
+        assert!(!ProtobufDetector.looks_like("1676550896"));
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn fields_and_json_mapping_of_instants_before_1970() {
+        use chrono::{DateTime, Duration, TimeZone, Utc};
+
+        use crate::protobuf::{duration_fields, duration_json, timestamp_fields, timestamp_json};
+
+        let before = Utc.timestamp_nanos(-1_500_000_000);
+        assert_eq!(timestamp_fields(before), "seconds: -2 nanos: 500000000");
+        assert_eq!(timestamp_json(before), "\"1969-12-31T23:59:58.500Z\"");
+        let datetime = Utc.timestamp_nanos(1676550896789123456);
+        assert_eq!(
+            timestamp_json(datetime),
+            "\"2023-02-16T12:34:56.789123456Z\""
+        );
+        assert_eq!(
+            timestamp_json(DateTime::<Utc>::UNIX_EPOCH),
+            "\"1970-01-01T00:00:00Z\""
+        );
+        let duration = Duration::milliseconds(-1500);
+        assert_eq!(duration_fields(duration), "seconds: -1 nanos: -500000000");
+        assert_eq!(duration_json(duration), "\"-1.500s\"");
+        assert_eq!(duration_json(Duration::microseconds(1)), "\"0.000001s\"");
+        assert_eq!(duration_json(Duration::seconds(3)), "\"3s\"");
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, Duration, Months, Utc};
+
+use crate::duration::CalendarDuration;
+use crate::DateTimeError;
+
+/// Iterates from `start` to `end` in steps of a duration.
+///
+/// The n-th date time is `start` plus n times the step rather than the
+/// previous date time plus the step, so months clamped to a shorter month do
+/// not shift the following date times: stepping by one month from January 31
+/// yields February 28, March 31, April 30 and so on.
+pub(crate) struct Range {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step: CalendarDuration,
+    exclusive_end: bool,
+    index: i64,
+    offset: Option<Duration>,
+}
+
+impl Range {
+    /// Creates a range, failing if `step` is not positive.
+    pub(crate) fn new(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        step: CalendarDuration,
+        exclusive_end: bool,
+    ) -> Result<Range, DateTimeError> {
+        let zero = Duration::zero();
+        let positive =
+            step.months >= 0 && step.exact >= zero && (step.months > 0 || step.exact > zero);
+        if !positive {
+            return Err(DateTimeError::InvalidStep(step.to_string()));
+        }
+        Ok(Range {
+            start,
+            end,
+            step,
+            exclusive_end,
+            index: 0,
+            offset: Some(zero),
+        })
+    }
+}
+
+impl Iterator for Range {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<DateTime<Utc>> {
+        let offset = self.offset?;
+        let months = Months::new(u32::try_from(self.index.checked_mul(self.step.months)?).ok()?);
+        let next = self
+            .start
+            .checked_add_months(months)
+            .and_then(|datetime| datetime.checked_add_signed(offset))
+            .filter(|next| *next < self.end || (*next == self.end && !self.exclusive_end));
+        match next {
+            Some(_) => {
+                self.index += 1;
+                self.offset = offset.checked_add(&self.step.exact);
+            }
+            None => self.offset = None,
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use crate::duration::{parse_duration, CalendarDuration};
+    use crate::range::Range;
+
+    fn datetime(input: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(input).unwrap().into()
+    }
+
+    fn range(start: &str, end: &str, step: &str, exclusive_end: bool) -> Vec<String> {
+        let step = parse_duration(step).unwrap();
+        Range::new(datetime(start), datetime(end), step, exclusive_end)
+            .unwrap()
+            .map(|datetime| datetime.to_rfc3339())
+            .collect()
+    }
+
+    #[test]
+    fn inclusive_end() {
+        let range = range("2023-02-01T00:00:00Z", "2023-02-02T00:00:00Z", "6h", false);
+        assert_eq!(
+            range,
+            [
+                "2023-02-01T00:00:00+00:00",
+                "2023-02-01T06:00:00+00:00",
+                "2023-02-01T12:00:00+00:00",
+                "2023-02-01T18:00:00+00:00",
+                "2023-02-02T00:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn exclusive_end() {
+        let range = range("2023-02-01T00:00:00Z", "2023-02-02T00:00:00Z", "12h", true);
+        assert_eq!(
+            range,
+            ["2023-02-01T00:00:00+00:00", "2023-02-01T12:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn end_between_steps() {
+        let range = range("2023-02-01T00:00:00Z", "2023-02-01T10:00:00Z", "6h", false);
+        assert_eq!(
+            range,
+            ["2023-02-01T00:00:00+00:00", "2023-02-01T06:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn end_before_start() {
+        let range = range("2023-02-02T00:00:00Z", "2023-02-01T00:00:00Z", "6h", false);
+        assert!(range.is_empty());
+    }
+
+    #[test]
+    fn calendar_steps_are_clamped() {
+        let range = range(
+            "2023-01-31T00:00:00Z",
+            "2023-05-01T00:00:00Z",
+            "1month",
+            false,
+        );
+        assert_eq!(
+            range,
+            [
+                "2023-01-31T00:00:00+00:00",
+                "2023-02-28T00:00:00+00:00",
+                "2023-03-31T00:00:00+00:00",
+                "2023-04-30T00:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn non_positive_steps_are_rejected() {
+        let start = datetime("2023-02-01T00:00:00Z");
+        let end = datetime("2023-02-02T00:00:00Z");
+        let mixed = CalendarDuration {
+            months: 1,
+            exact: -Duration::days(1),
+        };
+        let steps = ["0s", "-1h", "P0D", "-1mo"].map(|step| parse_duration(step).unwrap());
+        for step in steps.into_iter().chain([mixed]) {
+            assert!(Range::new(start, end, step, false).is_err(), "{step}");
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::ops::{Range, RangeInclusive};
+
+use chrono::{DateTime, Utc};
+
+use crate::{get_datetime, Detectors, InputKind};
+
+/// Finds all date times within `line` and returns them with their byte
+/// ranges in `line`, ordered by position and without overlaps.
+///
+/// Candidates are RFC 3339 timestamps like `2023-02-16T12:34:56.789Z` and
+/// numbers with the typical number of digits of epoch times, i.e. 10 to 13
+/// digits or the exact number of digits of the unit given with --unit.
+/// Candidates must not be directly preceded or followed by letters or digits
+/// and have to be accepted by the detectors.
+pub(crate) fn find_datetimes(
+    line: &[u8],
+    detectors: &Detectors,
+) -> Vec<(Range<usize>, DateTime<Utc>)> {
+    let digits = epoch_digits(detectors);
+    let mut found = Vec::new();
+    let mut position = 0;
+    while position < line.len() {
+        let starts_token = line[position].is_ascii_digit()
+            && (position == 0 || !line[position - 1].is_ascii_alphanumeric());
+        match starts_token.then(|| datetime_at(line, position, detectors, &digits)) {
+            Some(Some((end, datetime))) => {
+                found.push((position..end, datetime));
+                position = end;
+            }
+            _ => position += 1,
+        }
+    }
+    found
+}
+
+/// Replaces all date times found in `line` by the result of `render`. All
+/// other bytes are copied unchanged.
+pub(crate) fn rewrite(
+    line: &[u8],
+    detectors: &Detectors,
+    render: impl Fn(DateTime<Utc>) -> String,
+) -> Vec<u8> {
+    let mut rewritten = Vec::with_capacity(line.len());
+    let mut copied = 0;
+    for (range, datetime) in find_datetimes(line, detectors) {
+        rewritten.extend_from_slice(&line[copied..range.start]);
+        rewritten.extend_from_slice(render(datetime).as_bytes());
+        copied = range.end;
+    }
+    rewritten.extend_from_slice(&line[copied..]);
+    rewritten
+}
+
+/// Appends all date times found in `line` rendered by `render` and enclosed
+/// in the `open` and `close` delimiters. Annotations follow their date time
+/// or, with `end_of_line`, are appended in order before the line break.
+pub(crate) fn annotate(
+    line: &[u8],
+    detectors: &Detectors,
+    render: impl Fn(DateTime<Utc>) -> String,
+    (open, close): (&str, &str),
+    end_of_line: bool,
+) -> Vec<u8> {
+    let found = find_datetimes(line, detectors);
+    if found.is_empty() {
+        return line.to_vec();
+    }
+    let mut annotated = Vec::with_capacity(line.len() + found.len() * 32);
+    let annotation = |annotated: &mut Vec<u8>, datetime| {
+        annotated.extend_from_slice(open.as_bytes());
+        annotated.extend_from_slice(render(datetime).as_bytes());
+        annotated.extend_from_slice(close.as_bytes());
+    };
+    if end_of_line {
+        let content = line_content(line);
+        annotated.extend_from_slice(&line[..content]);
+        for (_, datetime) in found {
+            annotation(&mut annotated, datetime);
+        }
+        annotated.extend_from_slice(&line[content..]);
+    } else {
+        let mut copied = 0;
+        for (range, datetime) in found {
+            annotated.extend_from_slice(&line[copied..range.end]);
+            annotation(&mut annotated, datetime);
+            copied = range.end;
+        }
+        annotated.extend_from_slice(&line[copied..]);
+    }
+    annotated
+}
+
+/// The length of `line` without its trailing line break.
+fn line_content(line: &[u8]) -> usize {
+    match line {
+        [content @ .., b'\r', b'\n'] | [content @ .., b'\n'] => content.len(),
+        _ => line.len(),
+    }
+}
+
+/// Parses the longest candidate starting at `start`, returning its end.
+fn datetime_at(
+    line: &[u8],
+    start: usize,
+    detectors: &Detectors,
+    epoch_digits: &RangeInclusive<usize>,
+) -> Option<(usize, DateTime<Utc>)> {
+    let rest = &line[start..];
+    let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+    let candidates = [
+        rfc3339_length(rest),
+        epoch_digits.contains(&digits).then_some(digits),
+    ];
+    candidates.into_iter().flatten().find_map(|length| {
+        let end = start + length;
+        if line.get(end).is_some_and(u8::is_ascii_alphanumeric) {
+            return None;
+        }
+        let candidate = std::str::from_utf8(&line[start..end]).ok()?;
+        let datetime = get_datetime(candidate, detectors).ok()?;
+        Some((end, datetime))
+    })
+}
+
+/// The accepted number of digits of epoch times, from the typical digits of
+/// the first to those of the last epoch kind of the detectors, e.g. 10 to 13
+/// for epoch seconds and milliseconds.
+fn epoch_digits(detectors: &Detectors) -> RangeInclusive<usize> {
+    let mut digits = [
+        (InputKind::EpochSeconds, 10),
+        (InputKind::EpochMillis, 13),
+        (InputKind::EpochMicros, 16),
+        (InputKind::EpochNanos, 19),
+    ]
+    .into_iter()
+    .filter(|(kind, _)| detectors.contains(*kind))
+    .map(|(_, digits)| digits);
+    match (digits.next(), digits.next_back()) {
+        (Some(first), last) => first..=last.unwrap_or(first),
+        (None, _) => RangeInclusive::new(1, 0),
+    }
+}
+
+/// The length of the RFC 3339 timestamp at the start of `input`, if any. The
+/// timestamp is only checked syntactically.
+fn rfc3339_length(input: &[u8]) -> Option<usize> {
+    let mut length = 0;
+    for expected in "dddd-dd-ddTdd:dd:dd".bytes() {
+        let byte = *input.get(length)?;
+        let matches = match expected {
+            b'd' => byte.is_ascii_digit(),
+            b'T' => byte.eq_ignore_ascii_case(&b'T'),
+            _ => byte == expected,
+        };
+        if !matches {
+            return None;
+        }
+        length += 1;
+    }
+    if input.get(length) == Some(&b'.') {
+        let fraction = input[length + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if fraction == 0 {
+            return None;
+        }
+        length += 1 + fraction;
+    }
+    match input.get(length)? {
+        b'Z' | b'z' => Some(length + 1),
+        b'+' | b'-' => {
+            let offset = input.get(length + 1..length + 6)?;
+            let valid = offset.iter().enumerate().all(|(i, b)| {
+                if i == 2 {
+                    *b == b':'
+                } else {
+                    b.is_ascii_digit()
+                }
+            });
+            valid.then_some(length + 6)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::scan::{annotate, find_datetimes, rewrite};
+    use crate::{Detectors, EpochUnit, ParseOptions};
+
+    fn seconds(line: &str, detectors: &Detectors) -> Vec<(usize, usize, i64)> {
+        find_datetimes(line.as_bytes(), detectors)
+            .into_iter()
+            .map(|(range, datetime)| (range.start, range.end, datetime.timestamp()))
+            .collect()
+    }
+
+    fn rewrite_seconds(line: &str) -> String {
+        let rewritten = rewrite(line.as_bytes(), &Detectors::default(), |datetime| {
+            format!("<{}>", datetime.timestamp())
+        });
+        String::from_utf8(rewritten).unwrap()
+    }
+
+    #[test]
+    fn finds_rfc3339_and_epoch_times() {
+        let line = "at 2023-02-16T12:34:56.789Z took 1676550896 until 1676550897000.";
+        assert_eq!(
+            seconds(line, &Detectors::default()),
+            vec![
+                (3, 27, 1676550896),
+                (33, 43, 1676550896),
+                (50, 63, 1676550897)
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_offsets_and_lowercase_separator() {
+        let line = "[2023-02-16t13:34:56+01:00]";
+        let found = find_datetimes(line.as_bytes(), &Detectors::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 1..26);
+        assert_eq!(found[0].1, Utc.timestamp_opt(1676550896, 0).unwrap());
+    }
+
+    #[test]
+    fn ignores_numbers_within_words_and_other_lengths() {
+        let line = "id=x1676550896 port 8080 trace 16765508961234567 v1676550896";
+        assert!(seconds(line, &Detectors::default()).is_empty());
+    }
+
+    #[test]
+    fn ignores_invalid_timestamps() {
+        let line = "2023-02-30T12:34:56Z 2023-02-16T12:34:56 2023-02-16T12:34:56+0100";
+        assert!(seconds(line, &Detectors::default()).is_empty());
+    }
+
+    #[test]
+    fn unit_selects_number_of_digits() {
+        let detectors = ParseOptions {
+            unit: Some(EpochUnit::Us),
+            ..Default::default()
+        }
+        .detectors()
+        .unwrap();
+        assert_eq!(
+            seconds("1676550896 1676550896789000", &detectors),
+            vec![(11, 27, 1676550896)]
+        );
+    }
+
+    #[test]
+    fn rewrite_replaces_multiple_matches() {
+        assert_eq!(
+            rewrite_seconds("1676550896 - 2023-02-16T12:34:57Z: done 1676550898000"),
+            "<1676550896> - <1676550897>: done <1676550898>"
+        );
+    }
+
+    #[test]
+    fn rewrite_keeps_unmatched_bytes() {
+        let line = b"\xff  2023-02-16T12:34:56Z\tok \xe2\x9c\x93\r\n";
+        let rewritten = rewrite(line, &Detectors::default(), |_| "X".to_string());
+        assert_eq!(rewritten, b"\xff  X\tok \xe2\x9c\x93\r\n");
+        let untouched = b"  no date here \r\n";
+        let rewritten = rewrite(untouched, &Detectors::default(), |_| "X".to_string());
+        assert_eq!(rewritten, untouched);
+    }
+
+    #[test]
+    fn rewrite_with_adjacent_matches() {
+        assert_eq!(
+            rewrite_seconds("1676550896,1676550897;2023-02-16T12:34:58Z"),
+            "<1676550896>,<1676550897>;<1676550898>"
+        );
+    }
+
+    fn annotate_seconds(line: &str, end_of_line: bool) -> String {
+        let annotated = annotate(
+            line.as_bytes(),
+            &Detectors::default(),
+            |datetime| datetime.timestamp().to_string(),
+            (" [", "]"),
+            end_of_line,
+        );
+        String::from_utf8(annotated).unwrap()
+    }
+
+    #[test]
+    fn annotate_after_each_date_time() {
+        assert_eq!(
+            annotate_seconds("from 2023-02-16T12:34:56Z to 1676550897000\n", false),
+            "from 2023-02-16T12:34:56Z [1676550896] to 1676550897000 [1676550897]\n"
+        );
+    }
+
+    #[test]
+    fn annotate_at_end_of_line() {
+        assert_eq!(
+            annotate_seconds("from 2023-02-16T12:34:56Z to 1676550897000\r\n", true),
+            "from 2023-02-16T12:34:56Z to 1676550897000 [1676550896] [1676550897]\r\n"
+        );
+        assert_eq!(
+            annotate_seconds("at 1676550896", true),
+            "at 1676550896 [1676550896]"
+        );
+    }
+
+    #[test]
+    fn annotate_keeps_lines_without_date_times() {
+        assert_eq!(
+            annotate_seconds("  nothing here\n", true),
+            "  nothing here\n"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
+
+use crate::scan::find_datetimes;
+use crate::{get_datetime, Detectors};
+
+/// The date time a line is sorted by: the whole line if it is a date time,
+/// otherwise the first date time found within the line.
+fn sort_key(line: &[u8], detectors: &Detectors) -> Option<DateTime<Utc>> {
+    let whole_line = std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| get_datetime(line, detectors).ok());
+    whole_line.or_else(|| {
+        find_datetimes(line, detectors)
+            .first()
+            .map(|(_, datetime)| *datetime)
+    })
+}
+
+/// Sorts `lines` by their date times, ascending or with `reverse`
+/// descending. Lines with equal date times keep their order. Lines without
+/// a date time follow in their original order unless `drop_unparsed` is set.
+pub(crate) fn sort_lines(
+    lines: Vec<Vec<u8>>,
+    detectors: &Detectors,
+    reverse: bool,
+    drop_unparsed: bool,
+) -> Vec<Vec<u8>> {
+    let mut parsed = Vec::new();
+    let mut unparsed = Vec::new();
+    for line in lines {
+        match sort_key(&line, detectors) {
+            Some(key) => parsed.push((key, line)),
+            None => unparsed.push(line),
+        }
+    }
+    if reverse {
+        parsed.sort_by_key(|(key, _)| Reverse(*key));
+    } else {
+        parsed.sort_by_key(|(key, _)| *key);
+    }
+    let sorted = parsed.into_iter().map(|(_, line)| line);
+    if drop_unparsed {
+        sorted.collect()
+    } else {
+        sorted.chain(unparsed).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sort::sort_lines;
+    use crate::Detectors;
+
+    fn sort(lines: &[&str], reverse: bool, drop_unparsed: bool) -> Vec<String> {
+        let lines = lines.iter().map(|line| line.as_bytes().to_vec()).collect();
+        sort_lines(lines, &Detectors::default(), reverse, drop_unparsed)
+            .into_iter()
+            .map(|line| String::from_utf8(line).unwrap())
+            .collect()
+    }
+
+    const LINES: [&str; 6] = [
+        "b 2023-02-16T12:34:56.789Z first",
+        "no date",
+        "a 1676550896000",
+        "1676550896789",
+        "c 2023-02-16T13:34:56.789+01:00 second",
+        "also no date",
+    ];
+
+    #[test]
+    fn ascending_with_stable_ties() {
+        assert_eq!(
+            sort(&LINES, false, false),
+            [
+                "a 1676550896000",
+                "b 2023-02-16T12:34:56.789Z first",
+                "1676550896789",
+                "c 2023-02-16T13:34:56.789+01:00 second",
+                "no date",
+                "also no date",
+            ]
+        );
+    }
+
+    #[test]
+    fn descending_with_stable_ties() {
+        assert_eq!(
+            sort(&LINES, true, false),
+            [
+                "b 2023-02-16T12:34:56.789Z first",
+                "1676550896789",
+                "c 2023-02-16T13:34:56.789+01:00 second",
+                "a 1676550896000",
+                "no date",
+                "also no date",
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_unparsed() {
+        assert_eq!(sort(&LINES, false, true).len(), 4);
+    }
+}
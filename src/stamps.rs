@@ -0,0 +1,167 @@
+use std::fs::Metadata;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Duration, Utc};
+use datetime_helper::ParsedDateTime;
+
+use crate::output::{write_labeled, write_labeled_header, OutputOptions};
+
+/// The timestamps of a file which --mtime prints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Stamp {
+    /// The last modification of the content.
+    Mtime,
+    /// The last access.
+    Atime,
+    /// The last change of the content or the metadata, only on Unix.
+    Ctime,
+    /// The creation, on file systems which record it.
+    Created,
+}
+
+impl Stamp {
+    fn name(self) -> &'static str {
+        match self {
+            Stamp::Mtime => "mtime",
+            Stamp::Atime => "atime",
+            Stamp::Ctime => "ctime",
+            Stamp::Created => "created",
+        }
+    }
+
+    /// The timestamp in `metadata` or why there is none.
+    fn read(self, metadata: &Metadata) -> Result<DateTime<Utc>, &'static str> {
+        let time = match self {
+            Stamp::Mtime => metadata.modified(),
+            Stamp::Atime => metadata.accessed(),
+            Stamp::Created => metadata.created(),
+            Stamp::Ctime => return change_time(metadata),
+        };
+        time.map_err(|_| NOT_SUPPORTED)
+            .and_then(|time| from_system_time(time).ok_or(OUT_OF_RANGE))
+    }
+}
+
+const NOT_SUPPORTED: &str = "not supported";
+const OUT_OF_RANGE: &str = "out of range";
+
+/// Writes the `stamps` of each of the `paths`, labeled with the path and the
+/// name of the stamp. Stamps which the platform does not support are noted
+/// in their place. Paths which cannot be read are reported to `errors`
+/// unless `quiet` is set. Returns whether all paths could be read.
+pub(crate) fn write_stamps(
+    out: &mut impl Write,
+    errors: &mut impl Write,
+    paths: &[PathBuf],
+    stamps: &[Stamp],
+    output: &OutputOptions,
+    quiet: bool,
+) -> std::io::Result<bool> {
+    let mut valid = true;
+    write_labeled_header(out, &["path", "stamp"], output)?;
+    for path in paths {
+        let name = path.display().to_string();
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                valid = false;
+                if !quiet {
+                    writeln!(errors, "{}: {}", name, e)?;
+                }
+                continue;
+            }
+        };
+        for &stamp in stamps {
+            let result = stamp.read(&metadata).map(ParsedDateTime::from);
+            let labels = [("path", name.as_str()), ("stamp", stamp.name())];
+            write_labeled(out, &labels, result.as_ref().map_err(|e| *e), output)?;
+        }
+    }
+    Ok(valid)
+}
+
+/// Converts `time`, which is before the epoch for files from before 1970.
+/// Returns `None` if it is out of the range of [DateTime].
+fn from_system_time(time: SystemTime) -> Option<DateTime<Utc>> {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => DateTime::UNIX_EPOCH.checked_add_signed(Duration::from_std(after).ok()?),
+        Err(before) => {
+            DateTime::UNIX_EPOCH.checked_sub_signed(Duration::from_std(before.duration()).ok()?)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn change_time(metadata: &Metadata) -> Result<DateTime<Utc>, &'static str> {
+    use std::os::unix::fs::MetadataExt;
+
+    let nanos = u32::try_from(metadata.ctime_nsec()).map_err(|_| OUT_OF_RANGE)?;
+    DateTime::from_timestamp(metadata.ctime(), nanos).ok_or(OUT_OF_RANGE)
+}
+
+#[cfg(not(unix))]
+fn change_time(_metadata: &Metadata) -> Result<DateTime<Utc>, &'static str> {
+    Err(NOT_SUPPORTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::output::{OutputField, OutputFormat, OutputOptions};
+    use crate::stamps::{from_system_time, write_stamps, Stamp};
+
+    #[test]
+    fn system_times_before_and_after_the_epoch() {
+        let before = UNIX_EPOCH - Duration::from_millis(86_400_500);
+        assert_eq!(
+            from_system_time(before).unwrap().to_rfc3339(),
+            "1969-12-30T23:59:59.500+00:00"
+        );
+        let after = UNIX_EPOCH + Duration::from_secs(1676550896);
+        assert_eq!(from_system_time(after).unwrap().timestamp(), 1676550896);
+        // About a million years, beyond the range of chrono.
+        let beyond = UNIX_EPOCH + Duration::from_secs(1 << 45);
+        assert_eq!(from_system_time(beyond), None);
+    }
+
+    #[test]
+    fn stamps_are_labeled_and_missing_paths_reported() {
+        let path = std::env::temp_dir().join(format!("datetime-stamps-{}.txt", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(1676550896))
+            .unwrap();
+        let missing = path.with_extension("missing");
+        let output = OutputOptions {
+            format: OutputFormat::Csv,
+            only: Some(OutputField::Seconds),
+            ..Default::default()
+        };
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let paths = [path.clone(), missing.clone()];
+        let valid = write_stamps(
+            &mut out,
+            &mut errors,
+            &paths,
+            &[Stamp::Mtime],
+            &output,
+            false,
+        );
+        assert!(!valid.unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "path,stamp,epoch_seconds\n{},mtime,1676550896\n",
+                path.display()
+            )
+        );
+        let errors = String::from_utf8(errors).unwrap();
+        assert!(
+            errors.starts_with(&format!("{}: ", missing.display())),
+            "{errors}"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
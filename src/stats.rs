@@ -0,0 +1,225 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// The number of failed line numbers kept for the summary.
+const FAILED_LINES: usize = 5;
+
+/// A summary of all inputs of a run.
+#[derive(Default)]
+pub struct Stats {
+    pub parsed: u64,
+    pub failed: u64,
+    /// The number of skipped lines which are empty or only contain
+    /// whitespace.
+    pub blank: u64,
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+    /// The line numbers of the first failed inputs.
+    failed_lines: Vec<usize>,
+    /// The sum of the nanoseconds of all parsed date times since the earliest
+    /// one, which cannot overflow unlike the sum of epoch nanoseconds.
+    offset_nanos: i128,
+}
+
+impl Stats {
+    pub fn add(&mut self, datetime: DateTime<Utc>) {
+        let earliest = *self.earliest.get_or_insert(datetime);
+        if datetime < earliest {
+            self.offset_nanos += i128::from(self.parsed) * nanos(earliest - datetime);
+            self.earliest = Some(datetime);
+        } else {
+            self.offset_nanos += nanos(datetime - earliest);
+        }
+        self.latest = self.latest.max(Some(datetime));
+        self.parsed += 1;
+    }
+
+    pub fn add_failure(&mut self, line: usize) {
+        self.failed += 1;
+        if self.failed_lines.len() < FAILED_LINES {
+            self.failed_lines.push(line);
+        }
+    }
+
+    /// A one line summary like `3 lines read, 2 converted, 1 failed` followed
+    /// by the number of blank lines if there are any and the line numbers of
+    /// the first failures.
+    pub fn summary(&self) -> String {
+        let read = self.parsed + self.failed + self.blank;
+        let mut summary = format!(
+            "{} {} read, {} converted, {} failed",
+            read,
+            if read == 1 { "line" } else { "lines" },
+            self.parsed,
+            self.failed
+        );
+        if self.blank > 0 {
+            summary.push_str(&format!(", {} blank", self.blank));
+        }
+        if !self.failed_lines.is_empty() {
+            let lines: Vec<String> = self.failed_lines.iter().map(usize::to_string).collect();
+            let (label, more) = match self.failed {
+                1 => ("line", ""),
+                failed if failed > self.failed_lines.len() as u64 => ("lines", ", ..."),
+                _ => ("lines", ""),
+            };
+            summary.push_str(&format!(" ({} {}{})", label, lines.join(", "), more));
+        }
+        summary
+    }
+
+    /// The duration from the earliest to the latest date time.
+    pub fn span(&self) -> Option<Duration> {
+        Some(self.latest? - self.earliest?)
+    }
+
+    /// The mean of all date times, rounded down to nanoseconds.
+    pub fn mean(&self) -> Option<DateTime<Utc>> {
+        let mean_offset = self.offset_nanos / i128::from(self.parsed.max(1));
+        let seconds = i64::try_from(mean_offset.div_euclid(1_000_000_000)).ok()?;
+        let nanos = mean_offset.rem_euclid(1_000_000_000) as i64;
+        self.earliest?
+            .checked_add_signed(Duration::seconds(seconds) + Duration::nanoseconds(nanos))
+    }
+}
+
+/// The smallest, mean and largest of a series of durations, like the drifts
+/// of `--pair` or the gaps of `--deltas`.
+#[derive(Default)]
+pub struct Durations {
+    pub count: u64,
+    pub min: Option<Duration>,
+    pub max: Option<Duration>,
+    /// The sum of the nanoseconds of all durations.
+    total_nanos: i128,
+}
+
+impl Durations {
+    pub fn add(&mut self, duration: Duration) {
+        self.min = Some(self.min.map_or(duration, |min| min.min(duration)));
+        self.max = Some(self.max.map_or(duration, |max| max.max(duration)));
+        self.total_nanos += nanos(duration);
+        self.count += 1;
+    }
+
+    /// The mean of all durations, rounded towards zero to nanoseconds.
+    pub fn mean(&self) -> Option<Duration> {
+        self.min?;
+        let mean = self.total_nanos / i128::from(self.count);
+        // The mean lies between the smallest and the largest duration, so it
+        // is within the range of [Duration].
+        let seconds = (mean / 1_000_000_000) as i64;
+        Some(Duration::seconds(seconds) + Duration::nanoseconds((mean % 1_000_000_000) as i64))
+    }
+}
+
+fn nanos(duration: Duration) -> i128 {
+    i128::from(duration.num_seconds()) * 1_000_000_000 + i128::from(duration.subsec_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::stats::{Durations, Stats};
+
+    #[test]
+    fn empty() {
+        let stats = Stats::default();
+        assert_eq!((stats.parsed, stats.failed), (0, 0));
+        assert_eq!(stats.earliest, None);
+        assert_eq!(stats.span(), None);
+        assert_eq!(stats.mean(), None);
+    }
+
+    #[test]
+    fn earliest_latest_span_and_mean() {
+        let mut stats = Stats::default();
+        for millis in [1676550896789, 1676550890000, 1676550900000, 1676550896000] {
+            stats.add(Utc.timestamp_millis_opt(millis).unwrap());
+        }
+        stats.add_failure(5);
+        assert_eq!((stats.parsed, stats.failed), (4, 1));
+        assert_eq!(
+            stats.earliest,
+            Utc.timestamp_millis_opt(1676550890000).single()
+        );
+        assert_eq!(
+            stats.latest,
+            Utc.timestamp_millis_opt(1676550900000).single()
+        );
+        assert_eq!(stats.span(), Some(Duration::seconds(10)));
+        assert_eq!(
+            stats.mean(),
+            Utc.timestamp_opt(1676550895, 697250000).single()
+        );
+    }
+
+    #[test]
+    fn summary_with_blank_lines() {
+        let mut stats = Stats::default();
+        stats.add(Utc.timestamp_opt(0, 0).unwrap());
+        stats.add_failure(3);
+        stats.blank = 2;
+        assert_eq!(
+            stats.summary(),
+            "4 lines read, 1 converted, 1 failed, 2 blank (line 3)"
+        );
+    }
+
+    #[test]
+    fn summary_of_a_single_line() {
+        let mut stats = Stats::default();
+        stats.add(Utc.timestamp_opt(0, 0).unwrap());
+        assert_eq!(stats.summary(), "1 line read, 1 converted, 0 failed");
+        let mut stats = Stats::default();
+        stats.add_failure(5);
+        assert_eq!(
+            stats.summary(),
+            "1 line read, 0 converted, 1 failed (line 5)"
+        );
+    }
+
+    #[test]
+    fn summary_with_first_failed_lines() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.summary(), "0 lines read, 0 converted, 0 failed");
+        stats.add(Utc.timestamp_opt(0, 0).unwrap());
+        stats.add_failure(2);
+        assert_eq!(
+            stats.summary(),
+            "2 lines read, 1 converted, 1 failed (line 2)"
+        );
+        for line in 3..10 {
+            stats.add_failure(line);
+        }
+        assert_eq!(
+            stats.summary(),
+            "9 lines read, 1 converted, 8 failed (lines 2, 3, 4, 5, 6, ...)"
+        );
+    }
+
+    #[test]
+    fn min_mean_and_max_of_durations() {
+        let mut durations = Durations::default();
+        assert_eq!(
+            (durations.min, durations.mean(), durations.max),
+            (None, None, None)
+        );
+        for millis in [212, -40, 1000, 3] {
+            durations.add(Duration::milliseconds(millis));
+        }
+        durations.add(Duration::nanoseconds(-1));
+        assert_eq!(durations.count, 5);
+        assert_eq!(durations.min, Some(Duration::milliseconds(-40)));
+        assert_eq!(durations.max, Some(Duration::seconds(1)));
+        assert_eq!(durations.mean(), Some(Duration::nanoseconds(234_999_999)));
+    }
+
+    #[test]
+    fn mean_of_extreme_date_times() {
+        let mut stats = Stats::default();
+        stats.add(Utc.timestamp_opt(-8_000_000_000_000, 0).unwrap());
+        stats.add(Utc.timestamp_opt(8_000_000_000_000, 0).unwrap());
+        assert_eq!(stats.mean(), Utc.timestamp_opt(0, 0).single());
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::Write as _;
+use std::io::Write;
+use std::time::Instant;
+
+use datetime_helper::{DateTimeError, Detector, Detectors, ParsedDateTime, Trace};
+
+/// Prints each detector looking at an input for --verbose, with the time it
+/// took if `timing` is set.
+struct Tracer<W> {
+    out: W,
+    timing: bool,
+    started: Option<Instant>,
+}
+
+impl<W: Write> Trace for Tracer<W> {
+    fn start(&mut self, _detector: &dyn Detector) {
+        if self.timing {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    fn finish(
+        &mut self,
+        detector: &dyn Detector,
+        input: &str,
+        result: Option<&Result<ParsedDateTime, DateTimeError>>,
+    ) {
+        let elapsed = self.started.take().map(|started| started.elapsed());
+        let mut line = format!("  {}: ", detector.kind());
+        let _ = match result {
+            None => write!(line, "no, rejected by pre-filter"),
+            Some(Ok(parsed)) => write!(line, "ok → {}", parsed.to_rfc3339_with(parsed.precision)),
+            Some(Err(e)) => write!(line, "no, {}", e),
+        };
+        if let Some(reason) = detector.reason(input) {
+            let _ = write!(line, " ({})", reason);
+        }
+        if let Some(elapsed) = elapsed {
+            let _ = write!(line, " [{:?}]", elapsed);
+        }
+        // Tracing is best effort, like the progress.
+        let _ = writeln!(self.out, "{}", line);
+    }
+
+    fn clean(&mut self, cleaned: &str) {
+        let _ = writeln!(self.out, "Retrying without punctuation as \"{}\":", cleaned);
+    }
+}
+
+/// Parses `input` like [Detectors::parse] and prints the reasoning to `out`.
+pub(crate) fn parse(
+    out: impl Write,
+    detectors: &Detectors,
+    input: &str,
+    timing: bool,
+) -> Result<ParsedDateTime, DateTimeError> {
+    let mut tracer = Tracer {
+        out,
+        timing,
+        started: None,
+    };
+    let _ = writeln!(tracer.out, "Detecting \"{}\":", input.trim());
+    detectors.parse_traced(input, &mut tracer)
+}
+
+#[cfg(test)]
+mod tests {
+    use datetime_helper::{Detectors, InputKind};
+
+    use crate::trace::parse;
+
+    #[test]
+    fn ambiguous_number_is_traced() {
+        let mut out = Vec::new();
+        let parsed = parse(&mut out, &Detectors::default(), " 64060588800 ", false).unwrap();
+        assert_eq!(parsed.format, InputKind::EpochMillis);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Detecting \"64060588800\":\n  \
+             rfc3339: no, rejected by pre-filter\n  \
+             rfc2822: no, rejected by pre-filter\n  \
+             epoch-seconds: no, Invalid epoch time: 64060588800 (year 4000 ≥ cutoff 3000)\n  \
+             epoch-millis: ok → 1972-01-12T10:36:28.800Z\n"
+        );
+    }
+
+    #[test]
+    fn accepted_epoch_seconds_name_the_cutoff() {
+        let mut out = Vec::new();
+        parse(&mut out, &Detectors::default(), "1676550896", false).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.ends_with("  epoch-seconds: ok → 2023-02-16T12:34:56Z (year 2023 < cutoff 3000)\n")
+        );
+    }
+
+    #[test]
+    fn cleanup_is_traced() {
+        let mut out = Vec::new();
+        parse(&mut out, &Detectors::default(), "(1676550896)", false).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Retrying without punctuation as \"1676550896\":\n"));
+    }
+
+    #[test]
+    fn timing_per_detector() {
+        let mut out = Vec::new();
+        assert!(parse(&mut out, &Detectors::default(), "foo", true).is_err());
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 7);
+        assert!(
+            out.lines().skip(1).all(|line| line.ends_with("s]")),
+            "{out}"
+        );
+    }
+}
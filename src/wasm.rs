@@ -0,0 +1,15 @@
+use serde_json::json;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::parse;
+
+/// Parses `input` like [parse] and returns the parsed date time as JSON
+/// object with the fields of [ParsedDateTime](crate::ParsedDateTime), or an
+/// object with the message as `error` if the input cannot be parsed.
+#[wasm_bindgen(js_name = parseToJson)]
+pub fn parse_to_json(input: &str) -> String {
+    match parse(input) {
+        Ok(parsed) => json!(parsed).to_string(),
+        Err(e) => json!({ "error": e.to_string() }).to_string(),
+    }
+}
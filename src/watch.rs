@@ -0,0 +1,85 @@
+use std::io::Write;
+use std::thread::sleep;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::output::{format_output, OutputOptions};
+
+/// Moves the cursor to the start of the previous frame and clears the screen from there.
+fn clear_lines(count: usize) -> String {
+    format!("\x1b[{}A\x1b[J", count)
+}
+
+/// Renders the output for `now`. If `previous_lines` is given, the frame
+/// starts with control sequences replacing the previously drawn frame of
+/// this height.
+pub(crate) fn render_frame(
+    now: DateTime<Utc>,
+    output: &OutputOptions,
+    previous_lines: Option<usize>,
+) -> String {
+    let table = format_output(&now.into(), output);
+    match previous_lines {
+        Some(count) => clear_lines(count) + &table,
+        None => table,
+    }
+}
+
+/// Writes the current time to `out` every `interval` until the process is
+/// interrupted. Frames are redrawn in place if `redraw` is set and appended
+/// otherwise.
+pub(crate) fn run(
+    out: &mut impl Write,
+    interval: Duration,
+    output: &OutputOptions,
+    redraw: bool,
+) -> std::io::Result<()> {
+    let mut previous_lines = None;
+    loop {
+        let frame = render_frame(Utc::now(), output, previous_lines);
+        out.write_all(frame.as_bytes())?;
+        out.flush()?;
+        if redraw {
+            previous_lines = Some(frame.lines().count());
+        }
+        sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::output::{format_time, OutputField, OutputOptions};
+    use crate::watch::render_frame;
+
+    #[test]
+    fn first_frame_is_plain_table() {
+        let now = Utc.timestamp_millis_opt(1676550896789).unwrap();
+        let frame = render_frame(now, &OutputOptions::default(), None);
+        assert_eq!(frame, format_time(&now.into(), &OutputOptions::default()));
+    }
+
+    #[test]
+    fn following_frames_replace_previous_frame() {
+        let now = Utc.timestamp_millis_opt(1676550897789).unwrap();
+        let frame = render_frame(now, &OutputOptions::default(), Some(7));
+        assert!(frame.starts_with("\x1b[7A\x1b[J┌"));
+        assert!(frame.contains("2023-02-16T12:34:57.789Z"));
+        assert!(frame.contains("1676550897789"));
+    }
+
+    #[test]
+    fn only_selected_value() {
+        let now = Utc.timestamp_millis_opt(1676550897789).unwrap();
+        let output = OutputOptions {
+            only: Some(OutputField::Millis),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_frame(now, &output, Some(1)),
+            "\x1b[1A\x1b[J1676550897789\n"
+        );
+    }
+}
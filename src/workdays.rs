@@ -0,0 +1,179 @@
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+/// The days of the weekend.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Weekend {
+    /// Saturday and Sunday
+    #[default]
+    SatSun,
+    /// Friday and Saturday
+    FriSat,
+}
+
+impl Weekend {
+    /// Whether `weekday` is a day of the weekend.
+    pub fn contains(self, weekday: Weekday) -> bool {
+        match self {
+            Weekend::SatSun => matches!(weekday, Weekday::Sat | Weekday::Sun),
+            Weekend::FriSat => matches!(weekday, Weekday::Fri | Weekday::Sat),
+        }
+    }
+}
+
+/// Workdays, which are the days neither on the weekend nor holidays.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use datetime_helper::workdays::{Workdays, Weekend};
+///
+/// let date = |day| NaiveDate::from_ymd_opt(2023, 2, day).unwrap();
+/// let workdays = Workdays::new(Weekend::SatSun, [date(20)]);
+/// // Thursday to Friday, Tuesday and Wednesday, but not the holiday Monday.
+/// assert_eq!(workdays.count(date(16), date(23)), 4);
+/// assert_eq!(workdays.add(date(16), 2), Some(date(21)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Workdays {
+    weekend: Weekend,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Workdays {
+    /// The days outside of `weekend` except for `holidays`.
+    pub fn new(weekend: Weekend, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        Workdays {
+            weekend,
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+
+    /// Whether `date` is neither on the weekend nor a holiday.
+    pub fn is_workday(&self, date: NaiveDate) -> bool {
+        self.is_weekday(date) && !self.holidays.contains(&date)
+    }
+
+    /// Counts the workdays from `start` up to, but not including, `end`. If
+    /// `end` is before `start`, the count of the workdays from `end` up to
+    /// `start` is negative.
+    pub fn count(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        if end < start {
+            return -self.count(end, start);
+        }
+        self.weekdays(start, end) - self.weekday_holidays(start, end)
+    }
+
+    /// The `n`th workday after `start`, which itself is not counted, or
+    /// before it if `n` is negative. `start` is returned for 0. Returns
+    /// `None` if the result is out of range.
+    pub fn add(&self, start: NaiveDate, n: i64) -> Option<NaiveDate> {
+        let mut date = start;
+        let mut remaining = n;
+        // Holidays which are passed on the way are made up for from there.
+        while remaining != 0 {
+            let next = self.add_weekdays(date, remaining)?;
+            remaining = match remaining > 0 {
+                true => self.weekday_holidays(date.succ_opt()?, next.succ_opt()?),
+                false => -self.weekday_holidays(next, date),
+            };
+            date = next;
+        }
+        Some(date)
+    }
+
+    fn is_weekday(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(date.weekday())
+    }
+
+    /// Counts the days not on the weekend from `start` up to `end`, five
+    /// for each full week and the rest one by one.
+    fn weekdays(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        let days = (end - start).num_days();
+        let rest = start + Days::new((days - days % 7) as u64);
+        let rest = rest.iter_days().take((days % 7) as usize);
+        days / 7 * 5 + rest.filter(|date| self.is_weekday(*date)).count() as i64
+    }
+
+    /// Counts the holidays not on the weekend from `start` up to `end`.
+    fn weekday_holidays(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        let holidays = self.holidays.range(start..end);
+        holidays.filter(|date| self.is_weekday(**date)).count() as i64
+    }
+
+    /// The `n`th day not on the weekend after `start`, or before it if `n`
+    /// is negative. Full weeks are skipped at once.
+    fn add_weekdays(&self, start: NaiveDate, n: i64) -> Option<NaiveDate> {
+        let step = |date: NaiveDate| match n > 0 {
+            true => date.succ_opt(),
+            false => date.pred_opt(),
+        };
+        let back = |date: NaiveDate| match n > 0 {
+            true => date.pred_opt(),
+            false => date.succ_opt(),
+        };
+        // From a weekend day, the days not on the weekend are counted from
+        // the last one before it in the direction of `n`.
+        let mut date = start;
+        while !self.is_weekday(date) {
+            date = back(date)?;
+        }
+        let weeks = Days::new(n.unsigned_abs() / 5 * 7);
+        date = match n > 0 {
+            true => date.checked_add_days(weeks)?,
+            false => date.checked_sub_days(weeks)?,
+        };
+        for _ in 0..n.unsigned_abs() % 5 {
+            date = step(date)?;
+            while !self.is_weekday(date) {
+                date = step(date)?;
+            }
+        }
+        Some(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::workdays::{Weekend, Workdays};
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+    }
+
+    #[test]
+    fn start_is_inclusive_and_end_exclusive() {
+        let workdays = Workdays::default();
+        assert_eq!(workdays.count(date(2, 16), date(3, 31)), 31);
+        assert_eq!(workdays.count(date(3, 31), date(2, 16)), -31);
+        assert_eq!(workdays.count(date(2, 16), date(2, 16)), 0);
+        // Saturday to Monday.
+        assert_eq!(workdays.count(date(2, 18), date(2, 20)), 0);
+        assert_eq!(workdays.count(date(2, 18), date(2, 21)), 1);
+    }
+
+    #[test]
+    fn adding_skips_weekends_and_holidays() {
+        let workdays = Workdays::new(Weekend::SatSun, [date(2, 20), date(2, 25)]);
+        assert_eq!(workdays.add(date(2, 16), 1), Some(date(2, 17)));
+        assert_eq!(workdays.add(date(2, 16), 2), Some(date(2, 21)));
+        assert_eq!(workdays.add(date(2, 18), 1), Some(date(2, 21)));
+        assert_eq!(workdays.add(date(2, 21), -1), Some(date(2, 17)));
+        assert_eq!(workdays.add(date(2, 19), -1), Some(date(2, 17)));
+        assert_eq!(workdays.add(date(2, 16), 10), Some(date(3, 3)));
+        assert_eq!(workdays.add(date(2, 18), 0), Some(date(2, 18)));
+    }
+
+    #[test]
+    fn weekend_on_friday_and_saturday() {
+        let workdays = Workdays::new(Weekend::FriSat, []);
+        // Thursday to Sunday.
+        assert_eq!(workdays.add(date(2, 16), 1), Some(date(2, 19)));
+        assert_eq!(workdays.count(date(2, 16), date(2, 20)), 2);
+        assert!(!workdays.is_workday(date(2, 17)));
+        assert!(workdays.is_workday(date(2, 19)));
+    }
+}
@@ -0,0 +1,343 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+/// The offset of a time zone in effect at some instant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZoneOffset {
+    /// The offset from UTC including daylight saving time.
+    pub utc_offset: FixedOffset,
+    /// The part of [ZoneOffset::utc_offset] due to daylight saving time,
+    /// zero for standard time.
+    pub dst_offset: Duration,
+    /// The abbreviation like `CEST`, or the offset like `-05` for zones
+    /// without one.
+    pub abbreviation: String,
+}
+
+impl ZoneOffset {
+    /// The offset of `timezone` at `at`.
+    pub fn at(timezone: Tz, at: DateTime<Utc>) -> Self {
+        let offset = timezone.offset_from_utc_datetime(&at.naive_utc());
+        ZoneOffset {
+            utc_offset: offset.fix(),
+            dst_offset: offset.dst_offset(),
+            abbreviation: match offset.abbreviation() {
+                Some(abbreviation) => abbreviation.to_string(),
+                None => numeric_abbreviation(offset.fix()),
+            },
+        }
+    }
+
+    /// Whether daylight saving time is in effect.
+    pub fn is_dst(&self) -> bool {
+        !self.dst_offset.is_zero()
+    }
+}
+
+/// The abbreviation of zones which have none like `-05` or `+0530`, as in
+/// the tz database.
+fn numeric_abbreviation(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+    match minutes {
+        0 => format!("{}{:02}", sign, hours),
+        _ => format!("{}{:02}{:02}", sign, hours, minutes),
+    }
+}
+
+/// A change of the offset of a time zone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transition {
+    /// The first instant with the new offset.
+    pub at: DateTime<Utc>,
+    /// The offset up to the transition.
+    pub before: ZoneOffset,
+    /// The offset from the transition on.
+    pub after: ZoneOffset,
+}
+
+impl Transition {
+    /// The local times just before and at the transition, like 02:00 and
+    /// 03:00 when daylight saving time starts in Europe/Berlin.
+    pub fn wall_clock(&self) -> (NaiveDateTime, NaiveDateTime) {
+        let local = |offset: &ZoneOffset| self.at.with_timezone(&offset.utc_offset).naive_local();
+        (local(&self.before), local(&self.after))
+    }
+}
+
+/// The offset of a time zone at an instant and the transitions around it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZoneInfo {
+    /// The time zone looked up.
+    pub timezone: Tz,
+    /// The instant at which it is looked up.
+    pub at: DateTime<Utc>,
+    /// The offset in effect at `at`.
+    pub offset: ZoneOffset,
+    /// The last transition up to `at`, `None` if the offset never changed.
+    pub previous: Option<Transition>,
+    /// The first transition after `at`, `None` if no change is known.
+    pub next: Option<Transition>,
+}
+
+impl ZoneInfo {
+    /// Looks up `timezone` at `at`.
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use datetime_helper::zone::ZoneInfo;
+    /// use datetime_helper::Tz;
+    ///
+    /// let at = Utc.with_ymd_and_hms(2023, 2, 16, 12, 34, 56).unwrap();
+    /// let info = ZoneInfo::new(Tz::Europe__Berlin, at);
+    /// assert_eq!(info.offset.abbreviation, "CET");
+    /// let next = info.next.unwrap();
+    /// assert_eq!(next.at, Utc.with_ymd_and_hms(2023, 3, 26, 1, 0, 0).unwrap());
+    /// assert_eq!(next.after.abbreviation, "CEST");
+    /// ```
+    pub fn new(timezone: Tz, at: DateTime<Utc>) -> Self {
+        ZoneInfo {
+            timezone,
+            at,
+            offset: ZoneOffset::at(timezone, at),
+            previous: previous_transition(timezone, at),
+            next: next_transition(timezone, at),
+        }
+    }
+}
+
+/// The first transition of `timezone` after `at`, `None` if no change is
+/// known.
+pub fn next_transition(timezone: Tz, at: DateTime<Utc>) -> Option<Transition> {
+    transition(timezone, at, STEP)
+}
+
+/// The last transition of `timezone` up to `at`, which is a transition at
+/// `at` itself, `None` if the offset never changed before.
+pub fn previous_transition(timezone: Tz, at: DateTime<Utc>) -> Option<Transition> {
+    transition(timezone, at, -STEP)
+}
+
+/// The interval at which offsets are sampled. chrono-tz does not expose the
+/// transitions of its tz database, only the offset at an instant, so two
+/// transitions within one step back to the same offset would be skipped.
+/// The closest ones in the database are a week apart, like those of
+/// America/Boa_Vista in October 2000.
+const STEP: Duration = Duration::hours(1);
+
+/// The years within which transitions are searched. The tz database starts
+/// in the 19th century and daylight saving time rules are computed for
+/// decades ahead, so offsets are constant outside of them.
+const YEARS: (i32, i32) = (1800, 2200);
+
+/// The start of the first and the end of the last year of [YEARS].
+fn year_range() -> (DateTime<Utc>, DateTime<Utc>) {
+    let year = |year| {
+        NaiveDate::from_ymd_opt(year, 1, 1)
+            .unwrap()
+            .and_time(Default::default())
+            .and_utc()
+    };
+    (year(YEARS.0), year(YEARS.1))
+}
+
+/// Whether the offset of `timezone` ever changes. Each zone of the tz
+/// database with transitions starts in local mean time, `LMT`, which it
+/// never returns to, so the offsets before and after all of its transitions
+/// differ. Zones like `UTC` and `Etc/GMT+5` have a single offset.
+fn has_transitions(timezone: Tz) -> bool {
+    let (first, last) = year_range();
+    timezone.offset_from_utc_datetime(&first.naive_utc())
+        != timezone.offset_from_utc_datetime(&last.naive_utc())
+}
+
+/// The nearest transition of `timezone` from `at` in the direction of `step`.
+/// The offsets are sampled every `step`, then the transition is narrowed
+/// down to the second, at which the tz database has them. Zones without
+/// transitions are not sampled at all.
+fn transition(timezone: Tz, at: DateTime<Utc>, step: Duration) -> Option<Transition> {
+    if !has_transitions(timezone) {
+        return None;
+    }
+    let offset =
+        |timestamp| timezone.offset_from_utc_datetime(&from_timestamp(timestamp).naive_utc());
+    let (first, last) = year_range();
+    let start = offset(at.timestamp());
+    let mut near = at.clamp(first, last);
+    let far = loop {
+        let far = near + step;
+        if far < first || far > last {
+            return None;
+        }
+        if offset(far.timestamp()) != start {
+            break far;
+        }
+        near = far;
+    };
+    // The offset changes between `old`, which has the earlier offset, and
+    // `new`, which has the later one.
+    let (mut old, mut new) = (near.timestamp(), far.timestamp());
+    if step < Duration::zero() {
+        (old, new) = (new, old);
+    }
+    let earlier = offset(old);
+    while new - old > 1 {
+        let middle = old + (new - old) / 2;
+        match offset(middle) == earlier {
+            true => old = middle,
+            false => new = middle,
+        }
+    }
+    Some(Transition {
+        at: from_timestamp(new),
+        before: ZoneOffset::at(timezone, from_timestamp(old)),
+        after: ZoneOffset::at(timezone, from_timestamp(new)),
+    })
+}
+
+/// The instant of `timestamp`, which is within the range of [DateTime] as it
+/// lies between two instants.
+fn from_timestamp(timestamp: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(timestamp, 0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use chrono_tz::Tz;
+
+    use crate::zone::{has_transitions, next_transition, previous_transition, YEARS};
+    use crate::zone::{ZoneInfo, ZoneOffset};
+
+    #[test]
+    fn transitions_around_an_instant() {
+        let at = Utc.with_ymd_and_hms(2023, 7, 16, 12, 0, 0).unwrap();
+        let info = ZoneInfo::new(Tz::America__New_York, at);
+        assert_eq!(info.offset.abbreviation, "EDT");
+        assert!(info.offset.is_dst());
+        assert_eq!(info.offset.dst_offset, Duration::hours(1));
+        let previous = info.previous.unwrap();
+        assert_eq!(
+            previous.at,
+            Utc.with_ymd_and_hms(2023, 3, 12, 7, 0, 0).unwrap()
+        );
+        assert_eq!(previous.before.abbreviation, "EST");
+        assert_eq!(previous.after, info.offset);
+        let next = info.next.unwrap();
+        assert_eq!(next.at, Utc.with_ymd_and_hms(2023, 11, 5, 6, 0, 0).unwrap());
+        assert_eq!(next.after.utc_offset.local_minus_utc(), -5 * 3600);
+    }
+
+    #[test]
+    fn instants_at_a_transition_belong_to_the_new_offset() {
+        let at = Utc.with_ymd_and_hms(2023, 3, 26, 1, 0, 0).unwrap();
+        let info = ZoneInfo::new(Tz::Europe__Berlin, at);
+        assert_eq!(info.offset.abbreviation, "CEST");
+        assert_eq!(info.previous.unwrap().at, at);
+        let before = ZoneInfo::new(Tz::Europe__Berlin, at - Duration::seconds(1));
+        assert_eq!(before.next.unwrap().at, at);
+    }
+
+    #[test]
+    fn transitions_of_2023() {
+        let utc = |month, day, hour| Utc.with_ymd_and_hms(2023, month, day, hour, 0, 0).unwrap();
+        for (timezone, transitions) in [
+            (Tz::Europe__Berlin, [utc(3, 26, 1), utc(10, 29, 1)]),
+            (Tz::America__New_York, [utc(3, 12, 7), utc(11, 5, 6)]),
+            (Tz::Australia__Sydney, [utc(4, 1, 16), utc(9, 30, 16)]),
+        ] {
+            let at = utc(1, 1, 0);
+            let first = next_transition(timezone, at).unwrap().at;
+            let second = next_transition(timezone, first).unwrap().at;
+            assert_eq!([first, second], transitions, "{timezone}");
+            assert_eq!(previous_transition(timezone, second).unwrap().at, second);
+            let before = second - Duration::seconds(1);
+            assert_eq!(previous_transition(timezone, before).unwrap().at, first);
+        }
+    }
+
+    #[test]
+    fn wall_clock_jumps() {
+        let at = Utc.with_ymd_and_hms(2023, 2, 16, 12, 0, 0).unwrap();
+        let spring = next_transition(Tz::Europe__Berlin, at).unwrap();
+        let (before, after) = spring.wall_clock();
+        assert_eq!(
+            (before.to_string(), after.to_string()),
+            (
+                "2023-03-26 02:00:00".to_string(),
+                "2023-03-26 03:00:00".to_string()
+            )
+        );
+        let autumn = next_transition(Tz::Europe__Berlin, spring.at).unwrap();
+        assert_eq!(autumn.wall_clock().1.to_string(), "2023-10-29 02:00:00");
+    }
+
+    #[test]
+    fn instants_far_in_the_past_and_future() {
+        let past = Utc.with_ymd_and_hms(1000, 1, 1, 0, 0, 0).unwrap();
+        let first = next_transition(Tz::Europe__Berlin, past).unwrap();
+        assert_eq!(
+            first.at,
+            Utc.with_ymd_and_hms(1893, 3, 31, 23, 6, 32).unwrap()
+        );
+        assert_eq!(first.before.abbreviation, "LMT");
+        assert_eq!(previous_transition(Tz::Europe__Berlin, past), None);
+        let future = Utc.with_ymd_and_hms(2040, 1, 1, 0, 0, 0).unwrap();
+        let next = next_transition(Tz::Europe__Berlin, future).unwrap();
+        assert_eq!(next.at, Utc.with_ymd_and_hms(2040, 3, 25, 1, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn transitions_a_week_apart() {
+        let utc = |day, hour| Utc.with_ymd_and_hms(2000, 10, day, hour, 0, 0).unwrap();
+        let timezone = Tz::America__Boa_Vista;
+        let first = next_transition(timezone, utc(1, 0)).unwrap();
+        assert_eq!(first.at, utc(8, 4));
+        let second = next_transition(timezone, first.at).unwrap();
+        assert_eq!(second.at, utc(15, 3));
+        assert_eq!(second.after, first.before);
+        assert_eq!(
+            previous_transition(timezone, utc(14, 0)).unwrap().at,
+            utc(8, 4)
+        );
+    }
+
+    #[test]
+    fn zones_without_transitions_have_a_single_offset() {
+        let fixed: Vec<Tz> = chrono_tz::TZ_VARIANTS
+            .into_iter()
+            .filter(|timezone| !has_transitions(*timezone))
+            .collect();
+        assert!(fixed.contains(&Tz::UTC) && fixed.contains(&Tz::Etc__GMTPlus5));
+        assert!(!fixed.contains(&Tz::Asia__Kolkata));
+        for timezone in fixed {
+            let offset = |year| {
+                let at = Utc.with_ymd_and_hms(year, 7, 1, 0, 0, 0).unwrap();
+                ZoneOffset::at(timezone, at)
+            };
+            let first = offset(YEARS.0);
+            assert!(
+                (YEARS.0..YEARS.1).all(|year| offset(year) == first),
+                "{timezone}"
+            );
+        }
+    }
+
+    #[test]
+    fn fixed_offsets_have_no_transitions() {
+        let at = Utc.with_ymd_and_hms(2023, 7, 16, 12, 0, 0).unwrap();
+        for timezone in [Tz::UTC, Tz::Etc__GMTPlus5] {
+            let info = ZoneInfo::new(timezone, at);
+            assert_eq!((info.previous, info.next), (None, None), "{timezone}");
+            assert!(!info.offset.is_dst());
+        }
+        let info = ZoneInfo::new(Tz::Etc__GMTPlus5, at);
+        assert_eq!(info.offset.utc_offset.local_minus_utc(), -5 * 3600);
+        assert_eq!(info.offset.abbreviation, "-05");
+        let info = ZoneInfo::new(Tz::Asia__Kolkata, at);
+        assert_eq!(info.offset.abbreviation, "IST");
+        let info = ZoneInfo::new(Tz::Asia__Kathmandu, at);
+        assert_eq!(info.offset.abbreviation, "+0545");
+    }
+}
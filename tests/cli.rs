@@ -0,0 +1,1332 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], input: &str) -> Output {
+    run_with_env(args, &[], input)
+}
+
+fn run_with_env(args: &[&str], envs: &[(&str, &str)], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_datetime"))
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Written by a thread, so large outputs cannot block the child.
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().unwrap();
+    let _ = writer.join().unwrap();
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn errors_are_printed_to_stderr_and_reflected_in_exit_code() {
+    let output = run(&[], "1676550896\nnot a date\n");
+    assert!(stdout(&output).contains("2023-02-16T12:34:56.000Z"));
+    assert!(!stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn successful_run_exits_with_zero() {
+    let output = run(&["1676550896"], "");
+    assert!(stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn quiet_suppresses_errors_but_keeps_stdout_and_exit_code() {
+    let input = "1676550896\nnot a date\n";
+    let loud = run(&[], input);
+    for flag in ["-q", "--quiet", "--silent"] {
+        let quiet = run(&[flag], input);
+        assert!(!stderr(&quiet).contains("not a date"));
+        assert_eq!(stdout(&quiet), stdout(&loud));
+        assert_eq!(quiet.status.code(), loud.status.code());
+    }
+}
+
+#[test]
+fn summary_of_standard_input() {
+    let input = "1676550896\nnot a date\n2023-02-16T12:34:56Z\nfoo\n";
+    let output = run(&["-q"], input);
+    assert_eq!(
+        stderr(&output),
+        "4 lines read, 2 converted, 2 failed (lines 2, 4)\n"
+    );
+    let output = run(&[], "1676550896\n");
+    assert!(stderr(&output).ends_with("1 line read, 1 converted, 0 failed\n"));
+}
+
+#[test]
+fn summary_is_suppressed_by_silent_and_for_arguments() {
+    let output = run(&["--silent"], "1676550896\nfoo\n");
+    assert!(stderr(&output).is_empty());
+    let output = run(&["foo"], "");
+    assert!(!stderr(&output).contains("lines read"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn quiet_does_not_suppress_usage_errors() {
+    let output = run(&["--quiet", "--no-such-flag"], "");
+    assert!(stderr(&output).contains("--no-such-flag"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn read_errors_stop_with_an_error_even_when_silent() {
+    // Reading a directory fails after it was opened.
+    let directory = std::env::temp_dir();
+    let output = run(&["--silent", "--file", directory.to_str().unwrap()], "");
+    assert!(stdout(&output).is_empty());
+    assert!(!stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn epoch_cutoff_year_from_environment() {
+    let envs = [("DATETIME_HELPER_EPOCH_CUTOFF_YEAR", "3001")];
+    let output = run_with_env(&["32503680000"], &envs, "");
+    assert!(stdout(&output).contains("3000-01-01T00:00:00.000Z"));
+    let output = run_with_env(&["--epoch-cutoff-year", "3000", "32503680000"], &envs, "");
+    assert!(stdout(&output).contains("1971-01-12T"));
+}
+
+#[test]
+fn interactive_mode_keeps_stdout_clean() {
+    let output = run(&["--interactive"], "1676550896\nquit\n");
+    assert!(stdout(&output).starts_with("┌"));
+    assert!(stderr(&output).starts_with("Interactive mode."));
+    assert!(stderr(&output).contains("dt> "));
+}
+
+#[test]
+fn piped_input_has_no_banner() {
+    let output = run(&[], "1676550896\n");
+    assert!(!stderr(&output).contains("Interactive mode."));
+    assert!(!stderr(&output).contains("dt> "));
+}
+
+#[test]
+fn bare_value_is_still_converted() {
+    let output = run(&["--only", "millis", "2023-02-16T12:34:56.789Z"], "");
+    assert_eq!(stdout(&output), "1676550896789\n");
+}
+
+#[test]
+fn prometheus_timestamps() {
+    let output = run(
+        &["--prom", "--only", "prom_s"],
+        "1676550896.789\n1676550896789\n2023-02-16T12:34:56Z\n1676550896.7\n",
+    );
+    assert_eq!(
+        stdout(&output),
+        "1676550896.789\n1676550896.789\n1676550896.000\n1676550896.700\n"
+    );
+    let output = run(&["--prom", "--only", "prom_ms"], "1676550896.789\n");
+    assert_eq!(stdout(&output), "1676550896789\n");
+    let output = run(&["--only", "prom_ms"], "1676550896.789\n");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn protobuf_timestamps() {
+    let output = run(
+        &["--output", "protobuf"],
+        "seconds: 1676550896 nanos: 789000000\n{nanos: 5, seconds: 1676550896}\n\
+         2023-02-16T12:34:56.123456789Z\n",
+    );
+    assert_eq!(
+        stdout(&output),
+        "\"2023-02-16T12:34:56.789Z\" seconds: 1676550896 nanos: 789000000\n\
+         \"2023-02-16T12:34:56.000000005Z\" seconds: 1676550896 nanos: 5\n\
+         \"2023-02-16T12:34:56.123456789Z\" seconds: 1676550896 nanos: 123456789\n"
+    );
+    let output = run(
+        &["--only", "iso", "--precision", "auto"],
+        "seconds: 0 nanos: -1\n",
+    );
+    assert_eq!(stdout(&output), "1969-12-31T23:59:59.999999999Z\n");
+}
+
+#[test]
+fn file_stamps_before_1970() {
+    let path = input_file("mtime", "");
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    let modified = std::time::UNIX_EPOCH - std::time::Duration::from_secs(86400);
+    file.set_modified(modified).unwrap();
+    let name = path.to_str().unwrap();
+    let output = run(&["--mtime", name, "missing.log", "--output", "json"], "");
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{{\"path\":\"{}\",\"stamp\":\"mtime\",\"iso\":\"1969-12-31T00:00:00.000Z\",\
+             \"epoch_seconds\":-86400,\"epoch_millis\":-86400000,\"touch\":\"196912310000.00\",\
+             \"gnu_date\":\"1969-12-31T00:00:00Z\"}}\n",
+            name
+        )
+    );
+    assert!(stderr(&output).starts_with("missing.log: "));
+    assert_eq!(output.status.code(), Some(1));
+    let output = run(&["--mtime", name, name, "--atime", "--only", "seconds"], "");
+    let printed = stdout(&output);
+    let lines: Vec<&str> = printed.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], format!("{} mtime: -86400", name));
+    assert_eq!(lines[2], format!("{} mtime: -86400", name));
+}
+
+#[test]
+fn icalendar_property_lines() {
+    let input = "DTSTART;TZID=Europe/Berlin:20230716T123456\r\nDTEND:20230216T113456Z\r\n\
+                 DTSTART;VALUE=DATE:20230216\r\nDTSTART:20230216T123456\r\n";
+    let output = run(&["--only", "iso"], input);
+    assert_eq!(
+        stdout(&output),
+        "2023-07-16T10:34:56.000Z\n2023-02-16T11:34:56.000Z\n\
+         2023-02-16T00:00:00.000Z\n2023-02-16T12:34:56.000Z\n"
+    );
+    let output = run(&["--only", "iso", "--assume-tz", "Europe/Berlin"], input);
+    assert!(stdout(&output).ends_with("2023-02-15T23:00:00.000Z\n2023-02-16T11:34:56.000Z\n"));
+}
+
+#[test]
+fn strict_epoch_micros_and_nanos() {
+    let output = run(
+        &[
+            "--strict",
+            "epoch-micros",
+            "--only",
+            "millis",
+            "1676550896789123",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "1676550896789\n");
+    let output = run(
+        &[
+            "--strict",
+            "epoch-nanos",
+            "--only",
+            "millis",
+            "1676550896789123456",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "1676550896789\n");
+}
+
+#[test]
+fn now_with_global_options_before_and_after_command() {
+    for args in [
+        ["--only", "seconds", "now", "--plus", "1h"],
+        ["now", "--plus", "1h", "--only", "seconds"],
+    ] {
+        let output = run(&args, "");
+        let seconds: i64 = stdout(&output).trim().parse().unwrap();
+        assert!(seconds > 1676550896, "{args:?}");
+        assert_eq!(output.status.code(), Some(0));
+    }
+}
+
+#[test]
+fn now_rejects_date_time_argument() {
+    let output = run(&["1676550896", "now"], "");
+    assert!(stderr(&output).contains("cannot be used with a command"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn diff_between_two_date_times() {
+    let output = run(
+        &[
+            "diff",
+            "1676550896",
+            "2023-03-01T00:00:00Z",
+            "--only",
+            "seconds",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "1077904\n");
+}
+
+#[test]
+fn diff_against_now() {
+    let output = run(&["--only", "millis", "diff", "now"], "");
+    let millis: i64 = stdout(&output).trim().parse().unwrap();
+    assert!((0..60_000).contains(&millis));
+}
+
+#[test]
+fn diff_with_invalid_argument_fails_even_if_quiet() {
+    let output = run(&["--quiet", "diff", "foo", "now"], "");
+    assert!(!stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn add_and_sub() {
+    let output = run(
+        &["add", "2023-01-31T12:00:00Z", "1month", "--only", "iso"],
+        "",
+    );
+    assert_eq!(stdout(&output), "2023-02-28T12:00:00.000Z\n");
+    let output = run(&["add", "2023-01-31T12:00:00Z", "-1d", "--only", "iso"], "");
+    assert_eq!(stdout(&output), "2023-01-30T12:00:00.000Z\n");
+    let output = run(&["sub", "1676550896", "PT90M", "--only", "iso"], "");
+    assert_eq!(stdout(&output), "2023-02-16T11:04:56.000Z\n");
+}
+
+#[test]
+fn add_and_sub_dates() {
+    let output = run(&["add", "2023-01-31", "1month", "--only", "iso"], "");
+    assert_eq!(stdout(&output), "2023-02-28T00:00:00.000Z\n");
+    let output = run(&["add", "2024-02-29", "1y", "--only", "iso"], "");
+    assert_eq!(stdout(&output), "2025-02-28T00:00:00.000Z\n");
+    let output = run(&["sub", "2024-02-29", "1y", "--only", "iso"], "");
+    assert_eq!(stdout(&output), "2023-02-28T00:00:00.000Z\n");
+}
+
+#[test]
+fn add_out_of_range_fails() {
+    let output = run(&["add", "2023-01-31T12:00:00Z", "300000y"], "");
+    assert!(stderr(&output).contains("out of range"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn epoch_times_out_of_range_fail() {
+    for input in [
+        "9223372036854775807",
+        "-9223372036854775808",
+        "123456789012345678901234567890",
+    ] {
+        let output = run(&["--", input], "");
+        assert_eq!(output.status.code(), Some(1), "{input}");
+        assert!(
+            stderr(&output).contains(&format!("Epoch time out of range: {input} ms")),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
+
+#[test]
+fn local_times_out_of_range_are_printed_as_such() {
+    let args = [
+        "add",
+        "--tz",
+        "Pacific/Kiritimati",
+        "--only",
+        "iso",
+        "2023-01-01T00:00:00Z",
+        "260119y11mo30d23h",
+    ];
+    let output = run(&args, "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "out of range\n");
+}
+
+#[test]
+fn cut_off_microseconds_are_noted_once() {
+    let input = "2023-02-16T12:34:56.000001Z\n2023-02-16T12:34:56.000002Z\n";
+    let output = run(&["--only", "iso"], input);
+    assert_eq!(
+        stdout(&output),
+        "2023-02-16T12:34:56.000Z\n2023-02-16T12:34:56.000Z\n"
+    );
+    assert!(stderr(&output).starts_with(
+        "note: \"2023-02-16T12:34:56.000001Z\" has microsecond precision, \
+         which --precision ms cuts off, see --precision auto\n2 lines read"
+    ));
+    let output = run(&["--output", "json"], input);
+    assert!(stdout(&output).starts_with("{\"iso\":\"2023-02-16T12:34:56.000001Z\""));
+    assert!(!stderr(&output).contains("note"));
+    let output = run(&["--precision", "auto", "2023-02-16T12:34:56.000001Z"], "");
+    assert!(stderr(&output).is_empty());
+}
+
+#[test]
+fn original_offset_in_json() {
+    let output = run(&["--output", "json", "2023-02-16T18:04:56+05:30"], "");
+    assert_eq!(
+        stdout(&output),
+        "{\"iso\":\"2023-02-16T12:34:56.000Z\",\"epoch_seconds\":1676550896,\
+         \"epoch_millis\":1676550896000,\"touch\":\"202302161234.56\",\
+         \"gnu_date\":\"2023-02-16T12:34:56Z\",\"original_offset\":\"+05:30\",\
+         \"original_local\":\"2023-02-16T18:04:56.000+05:30\"}\n"
+    );
+    for input in ["2023-02-16T12:34:56-00:00", "2023-02-16T12:34:56Z"] {
+        let output = run(&["--output", "json", input], "");
+        assert_eq!(
+            stdout(&output),
+            "{\"iso\":\"2023-02-16T12:34:56.000Z\",\"epoch_seconds\":1676550896,\
+             \"epoch_millis\":1676550896000,\"touch\":\"202302161234.56\",\
+             \"gnu_date\":\"2023-02-16T12:34:56Z\"}\n"
+        );
+    }
+}
+
+#[test]
+fn wrong_weekdays_are_warned_about() {
+    let input = "Wed, 16 Feb 2023 12:34:56 +0000\nTHU, 16 FEB 2023 12:34:56 +0000\n";
+    let output = run(&["--only", "seconds"], input);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "1676550896\n1676550896\n");
+    assert!(stderr(&output).starts_with(
+        "warning: \"Wed, 16 Feb 2023 12:34:56 +0000\" says Wednesday, \
+         but 2023-02-16 is a Thursday\n2 lines read"
+    ));
+    let output = run(&["--only", "seconds", "-q"], input);
+    assert!(!stderr(&output).contains("warning"));
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn deltas_of_a_log_with_a_stall() {
+    let file = fixture("events.log");
+    let output = run(&["--deltas", "--output", "csv", "--file", &file], "");
+    assert_eq!(
+        stdout(&output),
+        "line,at,delta_ms,delta\n\
+         2,2023-02-16T12:34:57.000Z,1000,1 second\n\
+         3,2023-02-16T12:34:58.000Z,1000,1 second\n\
+         4,2023-02-16T12:35:00.000Z,2000,2 seconds\n\
+         6,2023-02-16T12:35:02.000Z,2000,2 seconds\n\
+         7,2023-02-16T12:37:02.000Z,120000,2 minutes\n\
+         gaps,min_gap_ms,mean_gap_ms,max_gap_ms,max_gap_line\n5,1000,25200,120000,7\n"
+    );
+    let stderr = stderr(&output);
+    assert!(
+        stderr.starts_with("line 5: Cannot parse \"not a date\""),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with("7 lines read, 6 converted, 1 failed (line 5)\n"),
+        "{stderr}"
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn csv_column_of_ragged_records() {
+    let file = fixture("ragged.csv");
+    let args = [
+        "--csv",
+        "--column",
+        "created",
+        "--format",
+        "%Y-%m-%d %H:%M:%S%.3f",
+    ];
+    let output = run(&[&args[..], &["--file", &file]].concat(), "");
+    let expected = std::fs::read_to_string(fixture("ragged.expected.csv")).unwrap();
+    assert_eq!(stdout(&output), expected);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = stderr(&output);
+    assert!(
+        stderr.starts_with("line 5: Cannot parse \"yesterday\""),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("\nline 7: Record has no column 2\n"),
+        "{stderr}"
+    );
+    assert!(stderr.ends_with("5 lines read, 3 converted, 2 failed (lines 5, 7)\n"));
+}
+
+#[test]
+fn csv_column_of_quoted_line_breaks() {
+    let input = std::fs::read_to_string(fixture("multiline.csv")).unwrap();
+    let args = [
+        "--csv",
+        "--column",
+        "3",
+        "--format",
+        "%Y-%m-%d %H:%M:%S%.3f",
+    ];
+    let output = run(&args, &input);
+    let expected = std::fs::read_to_string(fixture("multiline.expected.csv")).unwrap();
+    assert_eq!(stdout(&output), expected);
+    assert_eq!(output.status.code(), Some(0));
+    let output = run(&["--csv", "--column", "3"], &input);
+    assert!(stdout(&output).ends_with(",2023-02-16T12:34:56.790Z\r\n"));
+    let output = run(&["--csv", "--column", "0"], &input);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn json_rewrite_of_ndjson() {
+    let input = "{\"msg\":\"a\",\"meta\":{\"created_at\":1676550896789}}\n\
+                 {\"msg\": \"b\"}\n\
+                 {\"msg\":\"c\",\"meta\":{\"created_at\":\"2023-02-16T13:34:56+01:00\"}}\n";
+    let args = ["--json-rewrite", "--json-key", "meta.created_at"];
+    let output = run(&[&args[..], &["--only", "seconds"]].concat(), input);
+    assert_eq!(
+        stdout(&output),
+        "{\"msg\":\"a\",\"meta\":{\"created_at\":1676550896}}\n\
+         {\"msg\": \"b\"}\n\
+         {\"msg\":\"c\",\"meta\":{\"created_at\":1676550896}}\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stderr(&output),
+        "line 2: No value at meta.created_at\n3 lines read, 2 converted, 1 failed (line 2)\n"
+    );
+    let output = run(&args, "{\"meta\":{\"created_at\":1676550896789}}");
+    assert_eq!(
+        stdout(&output),
+        "{\"meta\":{\"created_at\":\"2023-02-16T12:34:56.789Z\"}}"
+    );
+    let output = run(&["--json-rewrite", "--json-key", "a..b"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn trailing_punctuation_is_tolerated_unless_strict() {
+    let input = "1676550896.\n[2023-02-16T12:34:56Z]\n";
+    let output = run(&["--only", "seconds"], input);
+    assert_eq!(stdout(&output), "1676550896\n1676550896\n");
+    let output = run(&["--only", "seconds", "--strict", "epoch-seconds"], input);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn invalid_calendar_dates_name_the_component() {
+    let output = run(&["2023-02-30T12:34:56Z"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with(
+        "Cannot parse \"2023-02-30T12:34:56Z\" as rfc3339: \
+         day 30 is out of range for February 2023 (max 28)\n"
+    ));
+    let args = ["--assume-tz", "Europe/Berlin"];
+    let output = run(&[&args[..], &["2023-02-16 24:00"]].concat(), "");
+    assert!(stderr(&output).contains("hour 24 is out of range (max 23)"));
+}
+
+#[test]
+fn strict_local_times() {
+    let args = ["--strict", "local", "--only", "iso"];
+    let input = "2023-02-16 13:34:56\n2023-02-16T12:34:56Z\n";
+    let output = run(
+        &[&args[..], &["--assume-tz", "Europe/Berlin"]].concat(),
+        input,
+    );
+    assert_eq!(stdout(&output), "2023-02-16T12:34:56.000Z\n");
+    assert!(stderr(&output).starts_with("Input is none of the allowed formats local: "));
+    let output = run(&args, input);
+    assert_eq!(
+        stderr(&output),
+        "Input kind \"local\" requires a time zone, see --assume-tz\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn local_times_of_assume_tz() {
+    let args = ["--assume-tz", "Europe/Berlin", "--only", "iso"];
+    let output = run(&args, "2023-02-16 13:34:56\n2023-03-26 02:30\n");
+    assert_eq!(stdout(&output), "2023-02-16T12:34:56.000Z\n");
+    assert!(stderr(&output).starts_with(
+        "Cannot parse \"2023-03-26 02:30\" as local: \
+         02:00–03:00 does not exist on 2023-03-26 in Europe/Berlin\n"
+    ));
+    let output = run(
+        &[&args[..], &["--dst", "shift", "2023-03-26 02:30"]].concat(),
+        "",
+    );
+    assert_eq!(stdout(&output), "2023-03-26T01:30:00.000Z\n");
+    let output = run(
+        &[&args[..], &["--dst", "latest", "-v", "2023-10-29 02:30"]].concat(),
+        "",
+    );
+    assert_eq!(stdout(&output), "2023-10-29T01:30:00.000Z\n");
+    assert!(stderr(&output).contains(
+        "  local: ok → 2023-10-29T01:30:00Z (ambiguous in Europe/Berlin, --dst latest)\n"
+    ));
+    let output = run(&["2023-02-16 13:34:56"], "");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn tzinfo_transitions_as_json() {
+    let output = run(
+        &[
+            "tzinfo",
+            "America/New_York",
+            "2023-07-16T12:00:00Z",
+            "--output",
+            "json",
+        ],
+        "",
+    );
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["local"], "2023-07-16T08:00:00.000-04:00");
+    assert_eq!(
+        (&json["abbreviation"], &json["dst"]),
+        (&"EDT".into(), &true.into())
+    );
+    assert_eq!(json["previous"]["at"], "2023-03-12T07:00:00.000Z");
+    assert_eq!(json["previous"]["before"]["utc_offset"], "-05:00");
+    assert_eq!(json["next"]["at"], "2023-11-05T06:00:00.000Z");
+    assert_eq!(json["next"]["after"]["abbreviation"], "EST");
+    let output = run(&["tzinfo", "Etc/GMT+5"], "");
+    assert_eq!(stdout(&output).matches("no transitions").count(), 2);
+    let output = run(&["tzinfo", "Europe/Springfield"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn dst_next_and_prev() {
+    let output = run(&["dst-next", "Europe/Berlin", "2023-02-16T12:00:00Z"], "");
+    assert!(stdout(&output).contains("2023-03-26 02:00→03:00"));
+    let args = [
+        "dst-prev",
+        "America/New_York",
+        "2023-02-16T12:00:00Z",
+        "--output",
+        "json",
+    ];
+    let json: serde_json::Value = serde_json::from_str(&stdout(&run(&args, ""))).unwrap();
+    assert_eq!(json["at"], "2022-11-06T06:00:00.000Z");
+    assert_eq!(json["wall_clock_before"], "2022-11-06T02:00:00");
+    assert_eq!(json["wall_clock_after"], "2022-11-06T01:00:00");
+    assert_eq!(json["after"]["abbreviation"], "EST");
+    let output = run(&["dst-next", "Etc/GMT+5", "2023-02-16T12:00:00Z"], "");
+    assert_eq!(
+        stderr(&output),
+        "Etc/GMT+5 has no transitions after 2023-02-16T12:00:00.000Z\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let output = run(&["dst-prev", "UTC", "--output", "json"], "");
+    assert_eq!(stdout(&output), "null\n");
+    assert!(stderr(&output).starts_with("UTC has no transitions up to "));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn week_rules() {
+    let input = "2024-12-31T12:00:00Z\n2021-01-03T12:00:00Z\n";
+    for (rule, expected) in [
+        ("iso", "2025-W01\n2020-W53\n"),
+        ("sunday", "2024-W53\n2021-W02\n"),
+        ("monday-simple", "2024-W53\n2021-W01\n"),
+    ] {
+        let output = run(&["--only", "week", "--week-rule", rule], input);
+        assert_eq!(stdout(&output), expected, "{rule}");
+    }
+}
+
+#[test]
+fn pairs_of_mixed_formats() {
+    let input = "sent=1676550896789 received=2023-02-16T12:34:57.001Z\n\
+                 sent=1676550897000\n\
+                 2023-02-16T12:34:58.000Z 1676550897950 late\n";
+    let output = run(&["--pair", "--output", "json"], input);
+    assert_eq!(
+        stdout(&output),
+        "{\"line\":1,\"first\":\"2023-02-16T12:34:56.789Z\",\"second\":\"2023-02-16T12:34:57.001Z\",\"drift_ms\":212}\n\
+         {\"line\":3,\"first\":\"2023-02-16T12:34:58.000Z\",\"second\":\"2023-02-16T12:34:57.950Z\",\"drift_ms\":-50}\n\
+         {\"pairs\":2,\"min_drift_ms\":-50,\"mean_drift_ms\":81,\"max_drift_ms\":212}\n"
+    );
+    assert_eq!(
+        stderr(&output),
+        "line 2: expected 2 date times, found 1\n3 lines read, 2 converted, 1 failed (line 2)\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let input = "Thu, 16 Feb 2023 12:34:56 +0000 | 1676550896250\n";
+    let args = ["--pair", "--pair-delimiter", "|", "--only", "seconds", "-q"];
+    let output = run(&args, input);
+    assert!(stdout(&output).starts_with("1676550896 1676550896 +250 ms\n"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn shell_tools_formats() {
+    let input = "1676550896789\n";
+    let output = run(&["--only", "touch"], input);
+    assert_eq!(stdout(&output), "202302161234.56\n");
+    let output = run(&["--only", "touch", "--tz", "America/New_York"], input);
+    assert_eq!(stdout(&output), "202302160734.56\n");
+    let output = run(&["--only", "gnu-date"], input);
+    assert_eq!(stdout(&output), "2023-02-16T12:34:56.789Z\n");
+    let output = run(&["--output", "json", "--tz", "Asia/Kolkata"], input);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["touch"], "202302161804.56");
+    assert_eq!(json["gnu_date"], "2023-02-16T18:04:56.789+05:30");
+    let output = run(&["--output", "json", "--unit", "s", "253402300800"], "");
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!((json.get("touch"), json.get("gnu_date")), (None, None));
+}
+
+#[test]
+fn age_in_calendar_components() {
+    let output = run(
+        &["age", "1987-06-15", "--at", "2023-06-15", "--output", "csv"],
+        "",
+    );
+    assert_eq!(
+        stdout(&output),
+        "from,at,years,months,days\n1987-06-15,2023-06-15,36,0,0\n"
+    );
+    let args = [
+        "age",
+        "2023-01-10T12:00:00Z",
+        "--at",
+        "2023-02-10T08:30:15Z",
+    ];
+    assert!(stdout(&run(&args, "")).contains("30 days, 20 hours, 30 minutes and 15 seconds"));
+    let output = run(&["age", "2100-01-01", "--output", "json"], "");
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["future"], true);
+    assert!(json["years"].as_i64().unwrap() < 0);
+    assert!(json.get("hours").is_none());
+}
+
+#[test]
+fn range_prints_one_value_per_line() {
+    let args = [
+        "range",
+        "2023-02-01T00:00:00Z",
+        "2023-02-02T00:00:00Z",
+        "--step",
+        "12h",
+    ];
+    let output = run(&args, "");
+    assert_eq!(
+        stdout(&output),
+        "2023-02-01T00:00:00.000Z\n2023-02-01T12:00:00.000Z\n2023-02-02T00:00:00.000Z\n"
+    );
+}
+
+#[test]
+fn range_as_csv_column() {
+    let args = [
+        "range",
+        "1676550896",
+        "1676550956",
+        "--step",
+        "30s",
+        "--only",
+        "seconds",
+        "--output",
+        "csv",
+    ];
+    let output = run(&args, "");
+    assert_eq!(
+        stdout(&output),
+        "epoch_seconds\n1676550896\n1676550926\n1676550956\n"
+    );
+}
+
+#[test]
+fn range_rejects_too_many_date_times() {
+    let args = [
+        "range",
+        "1676550896",
+        "1676550956",
+        "--step",
+        "1s",
+        "--limit",
+        "60",
+    ];
+    let output = run(&args, "");
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).contains("more than 60"));
+    assert_eq!(output.status.code(), Some(1));
+    let output = run(&[&args[..6], &["61"]].concat(), "");
+    assert_eq!(stdout(&output).lines().count(), 61);
+}
+
+#[test]
+fn range_rejects_non_positive_step() {
+    let output = run(&["range", "1676550896", "1676550956", "--step", "-1s"], "");
+    assert!(stderr(&output).contains("Step must be positive"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn rewrite_replaces_date_times_in_lines() {
+    let input = "1676550896 start\r\nno date\n\tend 2023-02-16T13:00:00Z, took 1676552400000";
+    let output = run(&["--rewrite", "--format", "%H:%M:%S"], input);
+    assert_eq!(
+        stdout(&output),
+        "12:34:56 start\r\nno date\n\tend 13:00:00, took 13:00:00"
+    );
+    assert!(stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn invalid_format_is_a_usage_error() {
+    let output = run(&["--format", "%Q", "1676550896"], "");
+    assert!(stderr(&output).contains("Invalid format"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn annotate_keeps_original_values() {
+    let input = "1676550896 start\nno date\n";
+    let output = run(&["--annotate"], input);
+    assert_eq!(
+        stdout(&output),
+        "1676550896 [2023-02-16T12:34:56.000Z] start\nno date\n"
+    );
+    let args = [
+        "--annotate-eol",
+        "--delimiters",
+        " # ",
+        "",
+        "--only",
+        "millis",
+    ];
+    let output = run(&args, "1676550896 to 1676550897\r\n");
+    assert_eq!(
+        stdout(&output),
+        "1676550896 to 1676550897 # 1676550896000 # 1676550897000\r\n"
+    );
+}
+
+/// Writes a config file to a new temporary directory and returns its path.
+fn config_file(name: &str, content: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("datetime-cli-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(dir.join("datetime-helper")).unwrap();
+    let path = dir.join("datetime-helper").join("config.toml");
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn config_file_precedence() {
+    let path = config_file(
+        "precedence",
+        "only = \"seconds\"\nepoch-cutoff-year = 3001\n",
+    );
+    let config = path.to_str().unwrap();
+    let output = run(&["--config", config, "32503680000"], "");
+    assert_eq!(stdout(&output), "32503680000\n");
+    let output = run(&["--config", config, "--only", "millis", "32503680000"], "");
+    assert_eq!(stdout(&output), "32503680000000\n");
+    let envs = [("DATETIME_HELPER_EPOCH_CUTOFF_YEAR", "3000")];
+    let output = run_with_env(&["--config", config, "32503680000"], &envs, "");
+    assert_eq!(stdout(&output), "32503680\n");
+    let output = run(&["--config", config, "--no-config", "32503680000"], "");
+    assert_eq!(output.status.code(), Some(2));
+    let output = run(
+        &[
+            "add",
+            "1676550896",
+            "1s",
+            "--config",
+            config,
+            "--format",
+            "%S",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "57\n");
+}
+
+#[test]
+fn config_file_at_default_location() {
+    let path = config_file("default", "only = \"seconds\"\n");
+    let home = path.parent().unwrap().parent().unwrap().to_str().unwrap();
+    let envs = [("XDG_CONFIG_HOME", home)];
+    let output = run_with_env(&["2023-02-16T12:34:56Z"], &envs, "");
+    assert_eq!(stdout(&output), "1676550896\n");
+    let output = run_with_env(&["--no-config", "2023-02-16T12:34:56Z"], &envs, "");
+    assert!(stdout(&output).starts_with("┌"));
+}
+
+#[test]
+fn invalid_config_file_is_reported() {
+    let path = config_file("invalid", "quiet = true\nunit = \"days\"\n");
+    let output = run(&["--config", path.to_str().unwrap(), "1676550896"], "");
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).contains("config.toml:2: Invalid value for key \"unit\""));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn time_zone_and_output_from_environment() {
+    let envs = [
+        ("DATETIME_HELPER_TZ", "Europe/Berlin"),
+        ("DATETIME_HELPER_OUTPUT", "csv"),
+    ];
+    let output = run_with_env(&["1676550896"], &envs, "");
+    assert_eq!(
+        stdout(&output),
+        "iso,epoch_seconds,epoch_millis,touch,gnu_date\n\
+         2023-02-16T13:34:56.000+01:00,1676550896,1676550896000,202302161334.56,2023-02-16T13:34:56+01:00\n"
+    );
+    let output = run_with_env(
+        &["--tz", "UTC", "--output", "json", "1676550896"],
+        &envs,
+        "",
+    );
+    assert!(stdout(&output).starts_with("{\"iso\":\"2023-02-16T12:34:56.000Z\""));
+}
+
+#[test]
+fn environment_overrides_config_file() {
+    let path = config_file(
+        "environment",
+        "tz = \"America/New_York\"\nprecision = \"s\"\n",
+    );
+    let config = path.to_str().unwrap();
+    let envs = [("DATETIME_HELPER_TZ", "Asia/Tokyo")];
+    let output = run_with_env(
+        &["--config", config, "--only", "iso", "1676550896"],
+        &envs,
+        "",
+    );
+    assert_eq!(stdout(&output), "2023-02-16T21:34:56+09:00\n");
+}
+
+#[test]
+fn invalid_environment_values_name_the_variable() {
+    for (variable, value) in [
+        ("DATETIME_HELPER_TZ", "Mars/Olympus_Mons"),
+        ("DATETIME_HELPER_OUTPUT", "xml"),
+    ] {
+        let output = run_with_env(&["1676550896"], &[(variable, value)], "");
+        assert!(stderr(&output).contains(value), "{variable}");
+        assert!(stderr(&output).contains(variable), "{variable}");
+        assert_eq!(output.status.code(), Some(2));
+        let output = run_with_env(
+            &["--tz", "UTC", "--output", "csv", "1676550896"],
+            &[(variable, value)],
+            "",
+        );
+        assert_eq!(output.status.code(), Some(0));
+    }
+}
+
+#[test]
+fn copy_fails_without_clipboard() {
+    let envs = [("DISPLAY", ""), ("WAYLAND_DISPLAY", "")];
+    let output = run_with_env(&["--copy=seconds"], &envs, "1676550896\n1676550897\n");
+    assert_eq!(stdout(&output).lines().count(), 14);
+    assert!(stderr(&output).contains("Cannot copy 1676550897 to the clipboard"));
+    assert_eq!(output.status.code(), Some(1));
+    let output = run_with_env(&["--copy", "1676550896"], &envs, "");
+    assert!(stderr(&output).contains("Cannot copy 2023-02-16T12:34:56.000Z"));
+    let output = run_with_env(&["1676550896"], &envs, "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn copy_conflicts_with_filter_modes() {
+    let output = run(&["--copy", "--rewrite"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn sort_lines_by_date_time() {
+    let input = "\
+b 2023-02-16T12:34:56.789Z
+unparsed
+a 1676550896789
+c 1676550896000";
+    let output = run(&["--sort"], input);
+    assert_eq!(
+        stdout(&output),
+        "c 1676550896000\nb 2023-02-16T12:34:56.789Z\na 1676550896789\nunparsed\n"
+    );
+    let output = run(&["--sort", "--reverse", "--sort-drop-unparsed"], input);
+    assert_eq!(
+        stdout(&output),
+        "b 2023-02-16T12:34:56.789Z\na 1676550896789\nc 1676550896000\n"
+    );
+    let output = run(&["--reverse"], input);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn stats_after_results() {
+    let output = run(
+        &["--stats", "--only", "seconds"],
+        "1676550896\nfoo\n1676550956\n",
+    );
+    let stdout = stdout(&output);
+    assert!(stdout.starts_with("1676550896\n1676550956\n┌"), "{stdout}");
+    assert!(stdout.contains("│ Failed             │                        1 │"));
+    assert!(stdout.contains("│ Span               │ 0d 00:01:00.000          │"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn stats_only_of_failed_inputs() {
+    let output = run(&["--stats-only", "--output", "json", "-q"], "foo\nbar\n");
+    assert_eq!(
+        stdout(&output),
+        "{\"parsed\":0,\"failed\":2,\"blank\":0,\"earliest\":null,\"latest\":null,\"mean\":null,\"span\":null}\n"
+    );
+    let output = run(&["--stats-only", "--output", "csv"], "");
+    assert_eq!(
+        stdout(&output),
+        "parsed,failed,blank,earliest,latest,mean,span\n0,0,0,,,,\n"
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn histogram_as_csv() {
+    let input = "2023-02-16T10:15:00Z\n2023-02-16T10:45:00Z\nfoo\n2023-02-16T13:00:00Z\n";
+    let args = [
+        "--histogram",
+        "hour",
+        "--output",
+        "csv",
+        "--format",
+        "%H:%M",
+        "-q",
+    ];
+    let output = run(&args, input);
+    assert_eq!(
+        stdout(&output),
+        "bucket,count\n10:00,2\n11:00,0\n12:00,0\n13:00,1\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+fn input_file(name: &str, content: &str) -> std::path::PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("datetime-cli-{}-{}.txt", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn workdays_with_holidays() {
+    let path = input_file("holidays", "# Carnival\n2023-02-20\n\n2023-02-21\n");
+    let holidays = path.to_str().unwrap();
+    let args = ["workdays", "2023-02-16", "2023-03-31", "--output", "csv"];
+    let output = run(&args, "");
+    assert_eq!(
+        stdout(&output),
+        "from,to,workdays\n2023-02-16,2023-03-31,31\n"
+    );
+    let output = run(&[&args[..], &["--holidays", holidays]].concat(), "");
+    assert_eq!(
+        stdout(&output),
+        "from,to,workdays\n2023-02-16,2023-03-31,29\n"
+    );
+    let args = [
+        "workdays",
+        "2023-02-16",
+        "--add",
+        "2",
+        "--holidays",
+        holidays,
+    ];
+    let output = run(&[&args[..], &["--output", "json"]].concat(), "");
+    assert_eq!(
+        stdout(&output),
+        "{\"from\":\"2023-02-16\",\"to\":\"2023-02-22\",\"workdays\":2}\n"
+    );
+    let args = [
+        "workdays",
+        "2023-02-20",
+        "2023-02-16",
+        "--weekend",
+        "fri-sat",
+    ];
+    let output = run(&[&args[..], &["--output", "csv"]].concat(), "");
+    assert_eq!(
+        stdout(&output),
+        "from,to,workdays\n2023-02-20,2023-02-16,-2\n"
+    );
+    let path = input_file("bad-holidays", "2023-02-30\n");
+    let output = run(
+        &[
+            "workdays",
+            "2023-02-16",
+            "--add",
+            "1",
+            "--holidays",
+            path.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(stderr(&output).ends_with("line 1: \"2023-02-30\" is not a date like 2023-12-25\n"));
+}
+
+#[test]
+fn check_clean_file() {
+    let path = input_file("clean", "2023-02-16T12:34:56Z\n2023-02-16T12:35:56Z\n");
+    let file = path.to_str().unwrap();
+    let output = run(
+        &["--check", "--strict", "rfc3339", "--file", file, "-v"],
+        "",
+    );
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn check_dirty_file_reports_line_numbers() {
+    let path = input_file("dirty", "2023-02-16T12:34:56Z\n1676550896\nfoo\n");
+    let file = path.to_str().unwrap();
+    let output = run(&["--check", "--strict", "rfc3339", "--file", file], "");
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).is_empty());
+    assert_eq!(output.status.code(), Some(1));
+    let output = run(
+        &[
+            "--check",
+            "--strict",
+            "rfc3339",
+            "--file",
+            file,
+            "--verbose",
+        ],
+        "",
+    );
+    let stderr = stderr(&output);
+    assert_eq!(stderr.lines().count(), 2, "{stderr}");
+    assert!(stderr.starts_with("line 2: "), "{stderr}");
+    assert!(stderr.contains("\nline 3: "), "{stderr}");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn check_empty_file_and_stdin() {
+    let path = input_file("empty", "");
+    let output = run(&["--check", "--file", path.to_str().unwrap()], "");
+    assert!(stdout(&output).is_empty());
+    assert_eq!(output.status.code(), Some(0));
+    let output = run(&["--check"], "1676550896\n2023-02-16T12:34:56Z\n");
+    assert!(stdout(&output).is_empty());
+    assert_eq!(output.status.code(), Some(0));
+    let output = run(&["--check"], "1676550896\nfoo\n");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn blank_lines_are_skipped() {
+    let input = "1676550896\n\n \t\n2023-02-16T12:34:56Z\n";
+    let output = run(&["--output", "csv"], input);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output).lines().count(), 3);
+    assert_eq!(
+        stderr(&output),
+        "4 lines read, 2 converted, 0 failed, 2 blank\n"
+    );
+}
+
+#[test]
+fn blank_lines_are_invalid_when_checking() {
+    let output = run(&["--check", "-v"], "1676550896\n \n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("line 2: "));
+    let output = run(&["--strict", "epoch-seconds", "-q"], "1676550896\n\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stderr(&output),
+        "2 lines read, 1 converted, 1 failed (line 2)\n"
+    );
+}
+
+#[test]
+fn windows_files_with_byte_order_mark_and_crlf() {
+    let input = "\u{FEFF}1676550896\r\n2023-02-16T12:34:56Z\r\n1676550896000\r\n";
+    let path = input_file("windows", input);
+    let file = path.to_str().unwrap();
+    for output in [
+        run(&["--tz", "UTC", "--output", "csv", "--file", file], ""),
+        run(&["--tz", "UTC", "--output", "csv"], input),
+    ] {
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        let lines: Vec<_> = stdout(&output).lines().skip(1).map(String::from).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.contains("2023-02-16")));
+    }
+    let output = run(&["--check", "--file", file], "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn file_replaces_standard_input() {
+    let path = input_file("convert", "1676550896\n");
+    let output = run(
+        &["--file", path.to_str().unwrap(), "--only", "iso"],
+        "1676550897\n",
+    );
+    assert_eq!(stdout(&output), "2023-02-16T12:34:56.000Z\n");
+    let output = run(&["--file", "/no/such/file"], "");
+    assert!(stderr(&output).contains("/no/such/file"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn jobs_keep_the_order_of_large_inputs() {
+    let input: String = (0..100_000)
+        .map(|i| match i % 1000 {
+            999 => "foo\n".to_string(),
+            _ => format!("{}\n", 1_676_550_000 + i),
+        })
+        .collect();
+    let args = ["--output", "csv", "--silent"];
+    let sequential = run(&args, &input);
+    let parallel = run(&[&args[..], &["--jobs", "4"]].concat(), &input);
+    assert_eq!(stdout(&parallel).lines().count(), 99_901);
+    assert_eq!(stdout(&parallel), stdout(&sequential));
+    assert_eq!(parallel.status.code(), Some(1));
+    let output = run(&["--jobs", "4", "--check", "-v"], &input);
+    assert!(stderr(&output).starts_with("line 1000: "));
+}
+
+#[test]
+fn jobs_conflict_with_line_modes() {
+    for mode in ["--pair", "--deltas"] {
+        let output = run(&["--jobs", "2", mode], "1676550896 1676550897\n");
+        assert_eq!(output.status.code(), Some(2), "{mode}");
+        assert!(stderr(&output).contains("cannot be used with"), "{mode}");
+    }
+}
+
+#[test]
+fn skip_and_limit_select_a_window() {
+    let input = "1676550891\n1676550892\nfoo\n1676550894\n1676550895\n";
+    let output = run(&["--skip", "1", "--limit", "3", "--only", "seconds"], input);
+    assert_eq!(stdout(&output), "1676550892\n1676550894\n");
+    assert!(stderr(&output).ends_with("3 lines read, 2 converted, 1 failed (line 3)\n"));
+    let output = run(&["--skip", "3", "--sort", "--reverse"], input);
+    assert_eq!(stdout(&output), "1676550895\n1676550894\n");
+    let output = run(&["--limit", "1", "--rewrite", "--only", "iso"], input);
+    assert_eq!(stdout(&output), "2023-02-16T12:34:51.000Z\n");
+}
+
+#[test]
+fn limit_stops_reading_endless_input() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_datetime"))
+        .args(["--limit", "2", "--only", "seconds"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    std::thread::spawn(move || while stdin.write_all(b"1676550896\n").is_ok() {});
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while child.try_wait().unwrap().is_none() {
+        if std::time::Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("--limit did not stop reading");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(stdout(&output), "1676550896\n1676550896\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn invalid_utf8_fails_only_its_line() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_datetime"))
+        .args(["--only", "seconds"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"1676550896\n\xff\n1676550897\n").unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(stdout(&output), "1676550896\n1676550897\n");
+    assert_eq!(
+        stderr(&output),
+        "line 2: invalid UTF-8\n3 lines read, 2 converted, 1 failed (line 2)\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn verbose_traces_the_detectors() {
+    let output = run(&["-v", "--only", "seconds"], "1676550896\n");
+    assert_eq!(stdout(&output), "1676550896\n");
+    assert!(stderr(&output).starts_with(
+        "Detecting \"1676550896\":\n  \
+         rfc3339: no, rejected by pre-filter\n  \
+         rfc2822: no, rejected by pre-filter\n  \
+         epoch-seconds: ok → 2023-02-16T12:34:56Z (year 2023 < cutoff 3000)\n"
+    ));
+}
+
+/// Answers one request on a local port with `headers`, returning the URL.
+#[cfg(feature = "http")]
+fn serve_once(headers: &'static str) -> String {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        let response = format!("HTTP/1.1 200 OK\r\n{}Content-Length: 0\r\n\r\n", headers);
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+    });
+    url
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn url_headers_are_converted() {
+    let url = serve_once(
+        "Date: Thu, 16 Feb 2023 12:34:56 GMT\r\nLast-Modified: Wed, 15 Feb 2023 08:00:00 GMT\r\n\
+         Retry-After: 120\r\n",
+    );
+    let output = run(&["--url", &url, "--only", "iso"], "");
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let stdout = stdout(&output);
+    assert!(
+        stdout.starts_with(
+            "Date: Thu, 16 Feb 2023 12:34:56 GMT\n2023-02-16T12:34:56.000Z\n\
+             Last-Modified: Wed, 15 Feb 2023 08:00:00 GMT\n2023-02-15T08:00:00.000Z\n\
+             Retry-After: 120\n2023-02-16T12:36:56.000Z\nReceived\n"
+        ),
+        "{stdout}"
+    );
+    assert!(stdout.contains("the local clock is ahead of Date\n"));
+    assert_eq!(stderr(&output), "Expires: not sent\n");
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn url_failures_are_reported() {
+    let url = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    };
+    let output = run(&["--url", &url], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with(&format!("Cannot fetch {}: ", url)));
+    let output = run(&["--url", &serve_once("Expires: 0\r\n")], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Expires: \"0\" is not an HTTP date: "));
+}
@@ -0,0 +1,168 @@
+//! Property tests of the parsing heuristics.
+//!
+//! Epoch times are only read back correctly within these windows:
+//!
+//! * RFC 3339 timestamps for all years from 0 to 9999, the range of the
+//!   format.
+//! * Epoch seconds before the epoch cutoff year 3000. Later values are
+//!   epoch milliseconds.
+//! * Epoch milliseconds from 1971-01-13T04:48:00Z, i.e. from the value
+//!   32503680000 which as epoch seconds is in the year 3000. Smaller values,
+//!   including all negative values, are epoch seconds.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use datetime_helper::{parse, EpochUnit, InputKind, ParseOptions};
+use proptest::prelude::*;
+
+/// 0000-01-01T00:00:00Z
+const MIN_RFC3339: i64 = -62167219200;
+/// 9999-12-31T23:59:59Z
+const MAX_RFC3339: i64 = 253402300799;
+/// 3000-01-01T00:00:00Z
+const CUTOFF: i64 = 32503680000;
+/// The time zones furthest from UTC, which move local times out of the range
+/// of chrono for the extreme epoch times.
+const TIME_ZONES: [Tz; 2] = [Tz::Etc__GMTPlus12, Tz::Etc__GMTMinus14];
+
+fn instant(seconds: i64, nanos: u32) -> DateTime<Utc> {
+    Utc.timestamp_opt(seconds, nanos).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn rfc3339_round_trip(seconds in MIN_RFC3339..=MAX_RFC3339, nanos in 0..1_000_000_000u32) {
+        let datetime = instant(seconds, nanos);
+        let input = datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        let parsed = parse(&input).unwrap();
+        prop_assert_eq!(parsed.datetime, datetime);
+        prop_assert_eq!(parsed.format, InputKind::Rfc3339);
+    }
+
+    #[test]
+    fn epoch_seconds_round_trip(seconds in MIN_RFC3339..CUTOFF) {
+        let parsed = parse(&seconds.to_string()).unwrap();
+        prop_assert_eq!(parsed.datetime, instant(seconds, 0));
+        prop_assert_eq!(parsed.format, InputKind::EpochSeconds);
+    }
+
+    #[test]
+    fn epoch_millis_round_trip(millis in CUTOFF..=MAX_RFC3339 * 1000) {
+        let parsed = parse(&millis.to_string()).unwrap();
+        prop_assert_eq!(parsed.datetime.timestamp_millis(), millis);
+        prop_assert_eq!(parsed.format, InputKind::EpochMillis);
+    }
+
+    /// Below the window, the cutoff reads epoch milliseconds as seconds.
+    #[test]
+    fn epoch_millis_before_window_are_seconds(millis in -CUTOFF..CUTOFF) {
+        let parsed = parse(&millis.to_string()).unwrap();
+        prop_assert_eq!(parsed.datetime, instant(millis, 0));
+        prop_assert_eq!(parsed.format, InputKind::EpochSeconds);
+    }
+
+    #[test]
+    fn epoch_times_with_unit_round_trip(
+        seconds in MIN_RFC3339..=MAX_RFC3339,
+        nanos in 0..1_000_000_000u32,
+    ) {
+        let datetime = instant(seconds, nanos);
+        for (unit, value, nanos_per_unit) in [
+            (EpochUnit::S, Some(datetime.timestamp()), 1_000_000_000),
+            (EpochUnit::Ms, Some(datetime.timestamp_millis()), 1_000_000),
+            (EpochUnit::Us, Some(datetime.timestamp_micros()), 1_000),
+            (EpochUnit::Ns, datetime.timestamp_nanos_opt(), 1),
+        ] {
+            let Some(value) = value else { continue };
+            let options = ParseOptions { unit: Some(unit), ..Default::default() };
+            let parsed = options.parse(&value.to_string()).unwrap();
+            prop_assert_eq!(parsed.datetime, instant(seconds, nanos - nanos % nanos_per_unit));
+        }
+    }
+
+    #[test]
+    fn parsing_never_panics(input in ".*") {
+        let _ = parse(&input);
+    }
+
+    #[test]
+    fn parsing_numbers_never_panics(
+        input in "[ +-]?[0-9]{1,25}",
+        unit in proptest::option::of(prop_oneof![
+            Just(EpochUnit::S),
+            Just(EpochUnit::Ms),
+            Just(EpochUnit::Us),
+            Just(EpochUnit::Ns),
+        ]),
+        epoch_cutoff_year in any::<i32>(),
+    ) {
+        let mut options = ParseOptions { kinds: InputKind::BUILT_IN.to_vec(), ..Default::default() };
+        (options.unit, options.epoch_cutoff_year) = (unit, epoch_cutoff_year);
+        for parsed in [options.parse(&input), parse(&input)].into_iter().flatten() {
+            for tz in TIME_ZONES {
+                let _ = parsed.datetime.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::Nanos, true);
+            }
+        }
+    }
+
+    /// Epoch times within two days of the range of chrono.
+    #[test]
+    fn extreme_epoch_times_have_local_times(
+        edge in prop_oneof![
+            Just(DateTime::<Utc>::MIN_UTC.timestamp()),
+            Just(DateTime::<Utc>::MAX_UTC.timestamp()),
+        ],
+        distance in -2 * 86_400i64..=2 * 86_400,
+        fraction in 0..1000i64,
+    ) {
+        for (unit, per_second) in [(EpochUnit::S, 1), (EpochUnit::Ms, 1000), (EpochUnit::Us, 1_000_000)] {
+            let value = (edge + distance) * per_second + fraction % per_second;
+            let options = ParseOptions { unit: Some(unit), ..Default::default() };
+            if let Ok(parsed) = options.parse(&value.to_string()) {
+                for tz in TIME_ZONES {
+                    let _ = parsed.datetime.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::Nanos, true);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_date_like_inputs_never_panics(
+        input in "[+-]?[0-9]{4,6}-[0-9]{2}-[0-9]{2}[Tt ][0-9]{2}:[0-9]{2}:[0-9]{2}(\\.[0-9]{0,12})?([Zz]|[+-][0-9]{2}:[0-9]{2})",
+    ) {
+        let _ = parse(&input);
+    }
+}
+
+#[cfg(feature = "rayon")]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4))]
+
+    #[test]
+    fn parallel_parsing_is_sequential_parsing(
+        inputs in prop::collection::vec(
+            prop_oneof![
+                any::<i64>().prop_map(|number| number.to_string()),
+                (MIN_RFC3339..=MAX_RFC3339)
+                    .prop_map(|seconds| instant(seconds, 0).to_rfc3339()),
+                ".{0,30}",
+            ],
+            50_000..100_000,
+        ),
+    ) {
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let parallel = datetime_helper::parse_many_parallel(inputs.clone());
+        prop_assert_eq!(parallel.len(), inputs.len());
+        for (input, result) in inputs.iter().zip(&parallel) {
+            match (parse(input), result) {
+                (Ok(expected), Ok(parsed)) => prop_assert_eq!(&expected, parsed),
+                (Err(expected), Err(error)) => {
+                    prop_assert_eq!(expected.to_string(), error.to_string())
+                }
+                (expected, result) => prop_assert!(false, "{:?} != {:?}", expected, result),
+            }
+        }
+    }
+}
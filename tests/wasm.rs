@@ -0,0 +1,36 @@
+//! Round trips through the JavaScript bindings. On wasm32 they run with
+//! `wasm-bindgen-test-runner` as test runner, natively like other tests.
+
+#![cfg(feature = "wasm")]
+
+use datetime_helper::parse_to_json;
+use serde_json::Value;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn parse(input: &str) -> Value {
+    serde_json::from_str(&parse_to_json(input)).unwrap()
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn parsed_date_time_round_trip() {
+    let json = parse(" 1676550896789 ");
+    assert_eq!(json["datetime"], "2023-02-16T12:34:56.789Z");
+    assert_eq!(json["input"], "1676550896789");
+    assert_eq!(json["format"], "epoch-millis");
+    let again = parse(json["datetime"].as_str().unwrap());
+    assert_eq!(again["datetime"], json["datetime"]);
+    assert_eq!(again["format"], "rfc3339");
+    assert_eq!(again["offset"], "+00:00");
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn errors_are_reported_as_message() {
+    let json = parse("foo");
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Cannot parse \"foo\" as rfc3339:"));
+}
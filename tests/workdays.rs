@@ -0,0 +1,47 @@
+//! Property tests of counting and adding workdays against going through the
+//! days one by one.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use chrono::{Days, NaiveDate};
+use datetime_helper::workdays::{Weekend, Workdays};
+use proptest::prelude::*;
+
+fn date(days: u64) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2000, 1, 1).unwrap() + Days::new(days)
+}
+
+fn weekend() -> impl Strategy<Value = Weekend> {
+    prop_oneof![Just(Weekend::SatSun), Just(Weekend::FriSat)]
+}
+
+/// Up to 40 holidays within the dates of the tests.
+fn workdays() -> impl Strategy<Value = Workdays> {
+    (weekend(), prop::collection::vec(0..1_200u64, 0..40))
+        .prop_map(|(weekend, holidays)| Workdays::new(weekend, holidays.into_iter().map(date)))
+}
+
+proptest! {
+    #[test]
+    fn count_matches_day_by_day(workdays in workdays(), start in 0..1_000u64, end in 0..1_000u64) {
+        let (start, end) = (date(start), date(end));
+        let (first, last) = (start.min(end), start.max(end));
+        let days = first.iter_days().take_while(|date| *date < last);
+        let count = days.filter(|date| workdays.is_workday(*date)).count() as i64;
+        let sign = if end < start { -1 } else { 1 };
+        prop_assert_eq!(workdays.count(start, end), sign * count);
+    }
+
+    #[test]
+    fn add_matches_day_by_day(workdays in workdays(), start in 400..800u64, n in -150..150i64) {
+        let start = date(start);
+        let mut expected = start;
+        for _ in 0..n.unsigned_abs() {
+            expected = if n > 0 { expected.succ_opt() } else { expected.pred_opt() }.unwrap();
+            while !workdays.is_workday(expected) {
+                expected = if n > 0 { expected.succ_opt() } else { expected.pred_opt() }.unwrap();
+            }
+        }
+        prop_assert_eq!(workdays.add(start, n), Some(expected));
+    }
+}